@@ -0,0 +1,812 @@
+use std::{collections::HashMap, future::Future, pin::Pin, sync::{Arc, Mutex}, task::{Context as TaskContext, Poll}};
+use futures_util::{SinkExt, Stream, StreamExt, stream::SplitStream};
+use http::Response;
+use tokio::{net::TcpStream, sync::{mpsc, oneshot}, task::JoinHandle};
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use tungstenite::Message;
+use serde::Serialize;
+use serde_json::json;
+use core::{error::{CloseUri, Error}, protocol::{Feature, IdGenerator, Serializer, meta::MetaCall, roles::Roles}, protocol::messages::{Authenticate, Call, Cancel, CancelMode, Challenge, Event, Goodbye, Hello, HelloDetails, Invocation, Messages, Publish, Published, Register, Registered, Subscribe, Subscribed, Unregister, Unregistered, Unsubscribe, Unsubscribed, WampError, WampErrorEvent, WampResult, Welcome, Yield}};
+
+use super::{auth::{self, Authenticator}, WampRequest, AGENT};
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+type AsyncStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+pub(crate) type AsyncInvocationHandler = Arc<dyn Fn(Invocation, Progress) -> BoxFuture<Result<Yield, WampError>> + Send + Sync>;
+pub(crate) type AsyncChallengeHandler = Box<dyn Fn(Challenge) -> Authenticate + Send>;
+pub(crate) type AsyncGoodbyeHandler = Box<dyn Fn(Goodbye) + Send>;
+
+/// Requests awaiting a reply from the router, and the handlers bound to live subscriptions and registrations.
+#[derive(Default)]
+struct Pending {
+    welcome: Option<oneshot::Sender<Result<Welcome, Error>>>,
+    goodbye: Option<oneshot::Sender<Goodbye>>,
+    calls: HashMap<u64, oneshot::Sender<Result<WampResult, WampError>>>,
+    progressive_calls: HashMap<u64, mpsc::UnboundedSender<Result<WampResult, WampError>>>,
+    publications: HashMap<u64, oneshot::Sender<Result<Published, WampError>>>,
+    subscriptions: HashMap<u64, (oneshot::Sender<Result<Subscribed, WampError>>, mpsc::UnboundedSender<Event>)>,
+    unsubscriptions: HashMap<u64, (oneshot::Sender<Result<Unsubscribed, WampError>>, u64)>,
+    registrations: HashMap<u64, (oneshot::Sender<Result<Registered, WampError>>, AsyncInvocationHandler)>,
+    unregistrations: HashMap<u64, (oneshot::Sender<Result<Unregistered, WampError>>, u64)>,
+    /// Every `EventStream` of a subscription, the router hands out the same subscription when one is made twice.
+    events: HashMap<u64, Vec<mpsc::UnboundedSender<Event>>>,
+    invocations: HashMap<u64, AsyncInvocationHandler>
+}
+
+#[derive(Default)]
+struct Shared {
+    pending: Pending,
+    on_challenge: Option<AsyncChallengeHandler>,
+    on_goodbye: Option<AsyncGoodbyeHandler>,
+    authenticator: Option<Box<dyn Authenticator>>
+}
+
+/// Stream of events delivered to a single subscription, dropping the last stream of the subscription unsubscribes.
+pub struct EventStream {
+    pub subscribed: Subscribed,
+    receiver: mpsc::UnboundedReceiver<Event>,
+    serializer: Serializer,
+    outgoing: mpsc::UnboundedSender<Message>,
+    shared: Arc<Mutex<Shared>>,
+    ids: Arc<IdGenerator>
+}
+
+impl Stream for EventStream {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl Drop for EventStream {
+    /// Unsubscribes when no other stream is left on the subscription and it was not already dropped, the router's reply is not awaited.
+    fn drop(&mut self) {
+        self.receiver.close();
+        let subscription = self.subscribed.subscription;
+        let mut shared = self.shared.lock().unwrap();
+        let Some(streams) = shared.pending.events.get_mut(&subscription) else {
+            return;
+        };
+        streams.retain(|events| !events.is_closed());
+        if streams.is_empty() {
+            shared.pending.events.remove(&subscription);
+            let unsubscribe = Unsubscribe { request_id: self.ids.next(), subscription };
+            if let Ok(message) = self.serializer.to_message(&unsubscribe) {
+                let _ = self.outgoing.send(message);
+            }
+        }
+    }
+}
+
+/// Results of a call made with `call_progress`, the progressive ones first and the final result or error last.
+/// Dropping it before the final result asks the dealer to cancel the call.
+pub struct ResultStream {
+    pub request_id: u64,
+    receiver: mpsc::UnboundedReceiver<Result<WampResult, WampError>>,
+    serializer: Serializer,
+    outgoing: mpsc::UnboundedSender<Message>,
+    shared: Arc<Mutex<Shared>>
+}
+
+impl Stream for ResultStream {
+    type Item = Result<WampResult, WampError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl Drop for ResultStream {
+    fn drop(&mut self) {
+        if self.shared.lock().unwrap().pending.progressive_calls.remove(&self.request_id).is_some() {
+            if let Ok(message) = self.serializer.to_message(&Cancel::new(self.request_id, CancelMode::KillNoWait)) {
+                let _ = self.outgoing.send(message);
+            }
+        }
+    }
+}
+
+/// Sends progressive results of one invocation from inside an `AsyncClient` handler, the `Yield` the handler resolves with still ends the invocation.
+#[derive(Clone)]
+pub struct Progress {
+    request_id: u64,
+    receive_progress: bool,
+    serializer: Serializer,
+    outgoing: mpsc::UnboundedSender<Message>
+}
+
+impl Progress {
+    /// Whether the caller asked for progressive results, `send` fails when it did not.
+    pub fn is_requested(&self) -> bool {
+        self.receive_progress
+    }
+
+    pub fn send(&self, r#yield: Yield) -> Result<(), Error> {
+        if !self.receive_progress {
+            return Err(Error::Error("Dealer did not ask for progressive results for this invocation."));
+        }
+        let mut r#yield = r#yield.progress();
+        r#yield.request_id = self.request_id;
+        self.outgoing.send(self.serializer.to_message(&r#yield)?).map_err(|_| Error::Close)
+    }
+}
+
+/// Tokio based WAMP client.
+///
+/// Frames are read by a background task which resolves pending requests, feeds event streams
+/// and runs invocation handlers, so every method can be awaited concurrently from a shared reference.
+pub struct AsyncClient {
+    serializer: Serializer,
+    outgoing: mpsc::UnboundedSender<Message>,
+    shared: Arc<Mutex<Shared>>,
+    ids: Arc<IdGenerator>,
+    reader: JoinHandle<()>,
+    writer: JoinHandle<()>
+}
+
+impl AsyncClient {
+    pub async fn connect<U: ToString + Unpin, P: ToString + Unpin>(request: WampRequest<U, P>) -> Result<(AsyncClient, Response<Option<Vec<u8>>>), Error> {
+        let serializer = Serializer::from_protocol(request.protocol.to_string()).ok_or(Error::Error("Unsupported WAMP subprotocol, expected wamp.2.json, wamp.2.msgpack or wamp.2.cbor."))?;
+        let (socket, response) = connect_async(request).await?;
+        let (mut sink, stream) = socket.split();
+        let (outgoing, mut receiver) = mpsc::unbounded_channel::<Message>();
+        let shared = Arc::new(Mutex::new(Shared::default()));
+
+        let writer = tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                if sink.send(message).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let reader = tokio::spawn(read_loop(stream, serializer, outgoing.clone(), Arc::clone(&shared)));
+
+        Ok((
+            Self {
+                serializer,
+                outgoing,
+                shared,
+                ids: Arc::new(IdGenerator::new()),
+                reader,
+                writer
+            },
+            response
+        ))
+    }
+
+    /// Request IDs for this session, pass it to the `core::*!` macros.
+    pub fn ids(&self) -> &IdGenerator {
+        &self.ids
+    }
+
+    /// Queues a message for the writer task.
+    pub fn send<T: Serialize>(&self, message: T) -> Result<(), Error> {
+        self.outgoing.send(self.serializer.to_message(&message)?).map_err(|_| Error::Close)
+    }
+
+    pub fn on_challenge(&self, on_challenge: AsyncChallengeHandler) -> &Self {
+        self.shared.lock().unwrap().on_challenge = Some(on_challenge);
+        self
+    }
+
+    /// Called when the router closes the session, after it was answered with `wamp.close.goodbye_and_out`.
+    pub fn on_goodbye(&self, on_goodbye: AsyncGoodbyeHandler) -> &Self {
+        self.shared.lock().unwrap().on_goodbye = Some(on_goodbye);
+        self
+    }
+
+    /// Answers challenges for the authenticator's method with `Authenticate` automatically,
+    /// challenges for any other method still go to `on_challenge`.
+    pub fn authenticator<A: Authenticator + 'static>(&self, authenticator: A) -> &Self {
+        self.shared.lock().unwrap().authenticator = Some(Box::new(authenticator));
+        self
+    }
+
+    /// Roles and features this client implements, along with the authenticator when one is set.
+    pub fn hello_details(&self) -> HelloDetails {
+        let details = HelloDetails::new()
+            .feature(Roles::Caller, Feature::CallCanceling)
+            .feature(Roles::Caller, Feature::CallTimeout)
+            .feature(Roles::Caller, Feature::ProgressiveCallResults)
+            .feature(Roles::Caller, Feature::ProgressiveCallInvocations)
+            .feature(Roles::Caller, Feature::CallerIdentification)
+            .feature(Roles::Callee, Feature::ProgressiveCallResults)
+            .feature(Roles::Callee, Feature::ProgressiveCallInvocations)
+            .feature(Roles::Callee, Feature::PatternBasedRegistration)
+            .feature(Roles::Callee, Feature::CallerIdentification)
+            .feature(Roles::Callee, Feature::SharedRegistration)
+            .feature(Roles::Publisher, Feature::PublisherExclusion)
+            .feature(Roles::Publisher, Feature::PublisherIdentification)
+            .feature(Roles::Publisher, Feature::SubscriberBlackwhiteListing)
+            .feature(Roles::Subscriber, Feature::PatternBasedSubscription)
+            .feature(Roles::Subscriber, Feature::PublisherIdentification)
+            .agent(AGENT);
+        match &self.shared.lock().unwrap().authenticator {
+            Some(authenticator) => auth::describe(authenticator.as_ref(), details),
+            None => details
+        }
+    }
+
+    /// Sends `Hello` and resolves once the router welcomes or aborts the session,
+    /// any details it leaves out are derived from `hello_details`.
+    pub async fn join(&self, mut hello: Hello) -> Result<Welcome, Error> {
+        self.hello_details().merge_into(&mut hello.details);
+        let (sender, receiver) = oneshot::channel();
+        {
+            let mut shared = self.shared.lock().unwrap();
+            if let Some(authenticator) = &shared.authenticator {
+                auth::announce(authenticator.as_ref(), &mut hello.details);
+            }
+            shared.pending.welcome = Some(sender);
+        }
+        self.send(hello)?;
+        receiver.await.map_err(|_| Error::Close)?
+    }
+
+    /// Closes the session with `reason` and resolves with the router's `Goodbye`, the WebSocket is closed afterwards.
+    pub async fn leave(&self, reason: CloseUri) -> Result<Goodbye, Error> {
+        let (sender, receiver) = oneshot::channel();
+        self.shared.lock().unwrap().pending.goodbye = Some(sender);
+        self.send(core::goodbye!(reason.as_str()))?;
+        receiver.await.map_err(|_| Error::Close)
+    }
+
+    /// Resolves with the call's result, or with `wamp.error.timeout` once `options.timeout` runs out,
+    /// in which case the dealer is told to drop the call.
+    /// Calls asking for progressive results fail with `wamp.error.invalid_argument`, make them with `call_progress`.
+    pub async fn call(&self, call: Call) -> Result<WampResult, WampError> {
+        let request_id = call.request_id;
+        if call.receive_progress() {
+            return Err(core::error!(request_id, WampErrorEvent::Call, "wamp.error.invalid_argument"));
+        }
+        let timeout = call.timeout();
+        let (sender, receiver) = oneshot::channel();
+        self.shared.lock().unwrap().pending.calls.insert(request_id, sender);
+        if let Err(error) = self.send(call) {
+            self.shared.lock().unwrap().pending.calls.remove(&request_id);
+            return Err(send_failure(error, WampErrorEvent::Call, request_id));
+        }
+        let reply = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, receiver).await {
+                Ok(reply) => reply,
+                Err(_) => {
+                    self.shared.lock().unwrap().pending.calls.remove(&request_id);
+                    let _ = self.send(Cancel::new(request_id, CancelMode::KillNoWait));
+                    return Err(core::error!(request_id, WampErrorEvent::Call, "wamp.error.timeout"));
+                }
+            },
+            None => receiver.await
+        };
+        reply.unwrap_or_else(|_| Err(network_failure(WampErrorEvent::Call, request_id)))
+    }
+
+    /// Makes the call with `options.receive_progress` set and streams its results, `options.timeout` is left to the dealer.
+    pub fn call_progress(&self, mut call: Call) -> Result<ResultStream, WampError> {
+        let request_id = call.request_id;
+        if !call.options.is_object() {
+            call.options = json!({});
+        }
+        call.options["receive_progress"] = json!(true);
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.shared.lock().unwrap().pending.progressive_calls.insert(request_id, sender);
+        if let Err(error) = self.send(call) {
+            self.shared.lock().unwrap().pending.progressive_calls.remove(&request_id);
+            return Err(send_failure(error, WampErrorEvent::Call, request_id));
+        }
+        Ok(ResultStream {
+            request_id,
+            receiver,
+            serializer: self.serializer,
+            outgoing: self.outgoing.clone(),
+            shared: Arc::clone(&self.shared)
+        })
+    }
+
+    /// Calls one of the router's meta procedures, built with `core::protocol::meta`, and reads its result.
+    /// A result that does not read as `T` fails with `wamp.error.protocol_violation`.
+    pub async fn meta<T>(&self, meta: MetaCall<T>) -> Result<T, WampError> {
+        let mut call = meta.call.clone();
+        call.request_id = self.ids.next();
+        let request_id = call.request_id;
+        let result = self.call(call).await?;
+        meta.read(&result).ok_or_else(|| core::error!(request_id, WampErrorEvent::Call, "wamp.error.protocol_violation"))
+    }
+
+    pub async fn subscribe(&self, subscribe: Subscribe) -> Result<EventStream, WampError> {
+        let request_id = subscribe.request_id;
+        let (sender, receiver) = oneshot::channel();
+        let (events, event_receiver) = mpsc::unbounded_channel();
+        self.shared.lock().unwrap().pending.subscriptions.insert(request_id, (sender, events));
+        if let Err(error) = self.send(subscribe) {
+            self.shared.lock().unwrap().pending.subscriptions.remove(&request_id);
+            return Err(send_failure(error, WampErrorEvent::Subscribe, request_id));
+        }
+        let subscribed = receiver.await.unwrap_or_else(|_| Err(network_failure(WampErrorEvent::Subscribe, request_id)))?;
+        Ok(EventStream {
+            subscribed,
+            receiver: event_receiver,
+            serializer: self.serializer,
+            outgoing: self.outgoing.clone(),
+            shared: Arc::clone(&self.shared),
+            ids: Arc::clone(&self.ids)
+        })
+    }
+
+    /// Unsubscribes, the subscription's `EventStream` ends once the router confirms it.
+    pub async fn unsubscribe(&self, unsubscribe: Unsubscribe) -> Result<Unsubscribed, WampError> {
+        let request_id = unsubscribe.request_id;
+        let (sender, receiver) = oneshot::channel();
+        self.shared.lock().unwrap().pending.unsubscriptions.insert(request_id, (sender, unsubscribe.subscription));
+        if let Err(error) = self.send(unsubscribe) {
+            self.shared.lock().unwrap().pending.unsubscriptions.remove(&request_id);
+            return Err(send_failure(error, WampErrorEvent::Unsubscribe, request_id));
+        }
+        receiver.await.unwrap_or_else(|_| Err(network_failure(WampErrorEvent::Unsubscribe, request_id)))
+    }
+
+    /// Registers a procedure, every matching `Invocation` is answered with the handler's `Yield` or `WampError`.
+    pub async fn register<F, Fut>(&self, register: Register, handler: F) -> Result<Registered, WampError>
+    where
+        F: Fn(Invocation) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Yield, WampError>> + Send + 'static
+    {
+        self.register_progressive(register, move |invocation, _| handler(invocation)).await
+    }
+
+    /// Registers a procedure whose handler may send progressive results through `Progress` before it resolves.
+    pub async fn register_progressive<F, Fut>(&self, register: Register, handler: F) -> Result<Registered, WampError>
+    where
+        F: Fn(Invocation, Progress) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Yield, WampError>> + Send + 'static
+    {
+        let request_id = register.request_id;
+        let handler: AsyncInvocationHandler = Arc::new(move |invocation, progress| Box::pin(handler(invocation, progress)));
+        let (sender, receiver) = oneshot::channel();
+        self.shared.lock().unwrap().pending.registrations.insert(request_id, (sender, handler));
+        if let Err(error) = self.send(register) {
+            self.shared.lock().unwrap().pending.registrations.remove(&request_id);
+            return Err(send_failure(error, WampErrorEvent::Register, request_id));
+        }
+        receiver.await.unwrap_or_else(|_| Err(network_failure(WampErrorEvent::Register, request_id)))
+    }
+
+    /// Unregisters, the handler is dropped once the router confirms it.
+    pub async fn unregister(&self, unregister: Unregister) -> Result<Unregistered, WampError> {
+        let request_id = unregister.request_id;
+        let (sender, receiver) = oneshot::channel();
+        self.shared.lock().unwrap().pending.unregistrations.insert(request_id, (sender, unregister.registration));
+        if let Err(error) = self.send(unregister) {
+            self.shared.lock().unwrap().pending.unregistrations.remove(&request_id);
+            return Err(send_failure(error, WampErrorEvent::Unregister, request_id));
+        }
+        receiver.await.unwrap_or_else(|_| Err(network_failure(WampErrorEvent::Unregister, request_id)))
+    }
+
+    /// Publishes the event, with `options.acknowledge` set it resolves with the broker's `Published` or its error,
+    /// otherwise with `None` as soon as the event is queued.
+    pub async fn publish(&self, publish: Publish) -> Result<Option<Published>, WampError> {
+        let request_id = publish.request_id;
+        if !publish.acknowledge() {
+            return self.send(publish).map(|_| None).map_err(|error| send_failure(error, WampErrorEvent::Publish, request_id));
+        }
+        let (sender, receiver) = oneshot::channel();
+        self.shared.lock().unwrap().pending.publications.insert(request_id, sender);
+        if let Err(error) = self.send(publish) {
+            self.shared.lock().unwrap().pending.publications.remove(&request_id);
+            return Err(send_failure(error, WampErrorEvent::Publish, request_id));
+        }
+        receiver.await.unwrap_or_else(|_| Err(network_failure(WampErrorEvent::Publish, request_id))).map(Some)
+    }
+}
+
+impl Drop for AsyncClient {
+    fn drop(&mut self) {
+        self.reader.abort();
+        self.writer.abort();
+    }
+}
+
+fn network_failure(event: WampErrorEvent, request_id: u64) -> WampError {
+    core::error!(request_id, event, "wamp.error.network_failure")
+}
+
+fn send_failure(error: Error, event: WampErrorEvent, request_id: u64) -> WampError {
+    match error {
+        Error::Close => network_failure(event, request_id),
+        _ => core::error!(request_id, event, "wamp.error.invalid_argument")
+    }
+}
+
+async fn read_loop(mut stream: SplitStream<AsyncStream>, serializer: Serializer, outgoing: mpsc::UnboundedSender<Message>, shared: Arc<Mutex<Shared>>) {
+    while let Some(Ok(frame)) = stream.next().await {
+        match frame {
+            Message::Text(_) | Message::Binary(_) => {
+                if let Ok(message) = serializer.from_message(frame) {
+                    dispatch(message, serializer, &outgoing, &shared);
+                }
+            },
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    // Dropping every pending sender wakes the awaiting callers with a network failure,
+    // result streams only end so they are told first.
+    let mut shared = shared.lock().unwrap();
+    for (request_id, results) in shared.pending.progressive_calls.drain() {
+        let _ = results.send(Err(network_failure(WampErrorEvent::Call, request_id)));
+    }
+    shared.pending = Pending::default();
+}
+
+fn dispatch(message: Messages, serializer: Serializer, outgoing: &mpsc::UnboundedSender<Message>, shared: &Arc<Mutex<Shared>>) {
+    let mut guard = shared.lock().unwrap();
+    let shared = &mut *guard;
+    let pending = &mut shared.pending;
+    match message {
+        Messages::Welcome(welcome) => {
+            if let Some(sender) = pending.welcome.take() {
+                let _ = sender.send(Ok(welcome));
+            }
+        },
+        Messages::Abort(abort) => {
+            if let Some(sender) = pending.welcome.take() {
+                let _ = sender.send(Err(Error::Abort(abort)));
+            }
+        },
+        Messages::Goodbye(goodbye) => {
+            match pending.goodbye.take() {
+                Some(sender) => {
+                    let _ = sender.send(goodbye);
+                },
+                None => {
+                    if let Ok(reply) = serializer.to_message(&core::goodbye!(CloseUri::GoodbyeAndOut.as_str())) {
+                        let _ = outgoing.send(reply);
+                    }
+                    if let Some(on_goodbye) = &shared.on_goodbye {
+                        on_goodbye(goodbye);
+                    }
+                }
+            }
+            let _ = outgoing.send(Message::Close(None));
+        },
+        Messages::Challenge(challenge) => {
+            if let Some(authenticator) = shared.authenticator.as_ref().filter(|authenticator| authenticator.authmethod() == challenge.authmethod) {
+                let authenticate = authenticator.authenticate(&challenge).and_then(|authenticate| serializer.to_message(&authenticate));
+                match authenticate {
+                    Ok(authenticate) => {
+                        let _ = outgoing.send(authenticate);
+                    },
+                    // The session cannot be established without an answer, fail the pending join.
+                    Err(error) => if let Some(sender) = pending.welcome.take() {
+                        let _ = sender.send(Err(error));
+                    }
+                }
+            } else if let Some(on_challenge) = &shared.on_challenge {
+                if let Ok(authenticate) = serializer.to_message(&on_challenge(challenge)) {
+                    let _ = outgoing.send(authenticate);
+                }
+            }
+        },
+        Messages::Result(result) if result.is_progress() => {
+            if let Some(results) = pending.progressive_calls.get(&result.request_id) {
+                let _ = results.send(Ok(result));
+            }
+        },
+        Messages::Result(result) => {
+            if let Some(sender) = pending.calls.remove(&result.request_id) {
+                let _ = sender.send(Ok(result));
+            } else if let Some(results) = pending.progressive_calls.remove(&result.request_id) {
+                let _ = results.send(Ok(result));
+            }
+        },
+        Messages::Published(published) => {
+            if let Some(sender) = pending.publications.remove(&published.request_id) {
+                let _ = sender.send(Ok(published));
+            }
+        },
+        Messages::Subscribed(subscribed) => {
+            if let Some((sender, events)) = pending.subscriptions.remove(&subscribed.request_id) {
+                pending.events.entry(subscribed.subscription).or_default().push(events);
+                let _ = sender.send(Ok(subscribed));
+            }
+        },
+        Messages::Unsubscribed(unsubscribed) => {
+            if let Some((sender, subscription)) = pending.unsubscriptions.remove(&unsubscribed.request_id) {
+                pending.events.remove(&subscription);
+                let _ = sender.send(Ok(unsubscribed));
+            }
+        },
+        Messages::Registered(registered) => {
+            if let Some((sender, handler)) = pending.registrations.remove(&registered.request_id) {
+                pending.invocations.insert(registered.registration, handler);
+                let _ = sender.send(Ok(registered));
+            }
+        },
+        Messages::Unregistered(unregistered) => {
+            if let Some((sender, registration)) = pending.unregistrations.remove(&unregistered.request_id) {
+                pending.invocations.remove(&registration);
+                let _ = sender.send(Ok(unregistered));
+            }
+        },
+        Messages::Event(event) => {
+            // Streams that were dropped stop being routed to, the last one to go unsubscribes.
+            if let Some(streams) = pending.events.get_mut(&event.subscription) {
+                streams.retain(|events| events.send(event.clone()).is_ok());
+            }
+        },
+        Messages::Invocation(invocation) => {
+            let outgoing = outgoing.clone();
+            match pending.invocations.get(&invocation.registration) {
+                Some(handler) => {
+                    let progress = Progress {
+                        request_id: invocation.request_id,
+                        receive_progress: invocation.receive_progress(),
+                        serializer,
+                        outgoing: outgoing.clone()
+                    };
+                    let reply = handler(invocation.clone(), progress);
+                    tokio::spawn(async move {
+                        let reply = reply.await;
+                        reply_invocation(serializer, &outgoing, &invocation, reply);
+                    });
+                },
+                None => {
+                    let error = core::error!(invocation.request_id, WampErrorEvent::Invocation, "wamp.error.no_such_registration");
+                    reply_invocation(serializer, &outgoing, &invocation, Err(error));
+                }
+            }
+        },
+        Messages::Error(error) => {
+            match error.event {
+                WampErrorEvent::Call => {
+                    if let Some(sender) = pending.calls.remove(&error.request_id) {
+                        let _ = sender.send(Err(error));
+                    } else if let Some(results) = pending.progressive_calls.remove(&error.request_id) {
+                        let _ = results.send(Err(error));
+                    }
+                },
+                WampErrorEvent::Publish => {
+                    if let Some(sender) = pending.publications.remove(&error.request_id) {
+                        let _ = sender.send(Err(error));
+                    }
+                },
+                WampErrorEvent::Subscribe => {
+                    if let Some((sender, _)) = pending.subscriptions.remove(&error.request_id) {
+                        let _ = sender.send(Err(error));
+                    }
+                },
+                WampErrorEvent::Unsubscribe => {
+                    if let Some((sender, _)) = pending.unsubscriptions.remove(&error.request_id) {
+                        let _ = sender.send(Err(error));
+                    }
+                },
+                WampErrorEvent::Register => {
+                    if let Some((sender, _)) = pending.registrations.remove(&error.request_id) {
+                        let _ = sender.send(Err(error));
+                    }
+                },
+                WampErrorEvent::Unregister => {
+                    if let Some((sender, _)) = pending.unregistrations.remove(&error.request_id) {
+                        let _ = sender.send(Err(error));
+                    }
+                },
+                // Invocations and cancels are only ever sent to the router, never answered with an error.
+                WampErrorEvent::Invocation | WampErrorEvent::Cancel => {}
+            }
+        },
+        _ => {}
+    }
+}
+
+/// Binds a handler's outcome to the invocation's request ID and queues it for the dealer.
+fn reply_invocation(serializer: Serializer, outgoing: &mpsc::UnboundedSender<Message>, invocation: &Invocation, reply: Result<Yield, WampError>) {
+    let message = match reply {
+        Ok(mut r#yield) => {
+            r#yield.request_id = invocation.request_id;
+            serializer.to_message(&r#yield)
+        },
+        Err(mut error) => {
+            error.event = WampErrorEvent::Invocation;
+            error.request_id = invocation.request_id;
+            serializer.to_message(&error)
+        }
+    };
+    if let Ok(message) = message {
+        let _ = outgoing.send(message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{SinkExt, StreamExt};
+    use http::HeaderValue;
+    use serde::Serialize;
+    use serde_json::{Value, json};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_tungstenite::{accept_hdr_async, WebSocketStream, tungstenite::{Message, handshake::server::{Request, Response}}};
+    use core::protocol::{Serializer, messages::{CancelMode, Event, Invocation, Messages, Published, Registered, Subscribed, Unregister, Unsubscribe, Unsubscribed, WampErrorEvent, WampResult, Yield}};
+    use crate::WampRequest;
+    use super::AsyncClient;
+
+    /// A client connected to a stand-in router, whose end of the WebSocket is returned along with it.
+    async fn pair() -> (AsyncClient, WebSocketStream<TcpStream>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let router = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            accept_hdr_async(stream, |_: &Request, mut response: Response| {
+                response.headers_mut().insert("Sec-WebSocket-Protocol", HeaderValue::from_static("wamp.2.json"));
+                Ok(response)
+            }).await.unwrap()
+        });
+        let (client, _) = AsyncClient::connect(WampRequest { uri: format!("ws://{address}"), protocol: "wamp.2.json" }).await.unwrap();
+        (client, router.await.unwrap())
+    }
+
+    async fn receive(router: &mut WebSocketStream<TcpStream>) -> Messages {
+        Serializer::Json.from_message(router.next().await.unwrap().unwrap()).unwrap()
+    }
+
+    async fn deliver<T: Serialize>(router: &mut WebSocketStream<TcpStream>, message: T) {
+        router.send(Message::Text(serde_json::to_string(&message).unwrap())).await.unwrap();
+    }
+
+    /// Runs a test on a fresh runtime, `#[tokio::test]` can not be used as this crate's `core` shadows the standard one.
+    fn run<F: std::future::Future>(test: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(test)
+    }
+
+    #[test]
+    fn unsubscribe() {
+        run(async {
+            let (client, mut router) = pair().await;
+            let subscribe = async {
+                let Messages::Subscribe(subscribe) = receive(&mut router).await else { panic!("expected Subscribe") };
+                deliver(&mut router, Subscribed { request_id: subscribe.request_id, subscription: 5 }).await;
+            };
+            let (events, _) = tokio::join!(client.subscribe(core::subscribe!(client.ids(), "com.myapp.topic")), subscribe);
+            let mut events = events.unwrap();
+
+            let unsubscribe = async {
+                let Messages::Unsubscribe(unsubscribe) = receive(&mut router).await else { panic!("expected Unsubscribe") };
+                assert_eq!(unsubscribe.subscription, 5);
+                deliver(&mut router, Unsubscribed { request_id: unsubscribe.request_id }).await;
+            };
+            let (unsubscribed, _) = tokio::join!(client.unsubscribe(core::unsubscribe!(client.ids(), 5)), unsubscribe);
+            assert!(unsubscribed.is_ok());
+            assert!(events.next().await.is_none());
+            // The subscription is gone already, dropping the stream sends nothing.
+            drop(events);
+
+            let subscribe = async {
+                let Messages::Subscribe(subscribe) = receive(&mut router).await else { panic!("expected Subscribe") };
+                deliver(&mut router, Subscribed { request_id: subscribe.request_id, subscription: 6 }).await;
+            };
+            let (events, _) = tokio::join!(client.subscribe(core::subscribe!(client.ids(), "com.myapp.topic")), subscribe);
+            drop(events.unwrap());
+            let Messages::Unsubscribe(unsubscribe) = receive(&mut router).await else { panic!("expected Unsubscribe") };
+            assert_eq!(unsubscribe.subscription, 6);
+
+            let unregister = async {
+                let Messages::Unregister(unregister) = receive(&mut router).await else { panic!("expected Unregister") };
+                deliver(&mut router, core::error!(unregister.request_id, WampErrorEvent::Unregister, "wamp.error.no_such_registration")).await;
+            };
+            let (unregistered, _) = tokio::join!(client.unregister(core::unregister!(client.ids(), 9)), unregister);
+            assert_eq!(unregistered.unwrap_err().error, "wamp.error.no_such_registration");
+        });
+    }
+
+    #[test]
+    fn subscribe_twice() {
+        run(async {
+            let (client, mut router) = pair().await;
+            let mut streams = Vec::new();
+            for _ in 0..2 {
+                // The router hands out the existing subscription to a repeated subscribe.
+                let subscribe = async {
+                    let Messages::Subscribe(subscribe) = receive(&mut router).await else { panic!("expected Subscribe") };
+                    deliver(&mut router, Subscribed { request_id: subscribe.request_id, subscription: 5 }).await;
+                };
+                let (events, _) = tokio::join!(client.subscribe(core::subscribe!(client.ids(), "com.myapp.topic")), subscribe);
+                streams.push(events.unwrap());
+            }
+            let event = |publication| Event { subscription: 5, publication, details: json!({}), args: Value::Null, kwargs: Value::Null };
+
+            deliver(&mut router, event(1)).await;
+            for events in &mut streams {
+                assert_eq!(events.next().await.unwrap().publication, 1);
+            }
+
+            let mut events = streams.pop().unwrap();
+            drop(streams);
+            deliver(&mut router, event(2)).await;
+            assert_eq!(events.next().await.unwrap().publication, 2);
+            // Another stream was left on the subscription, dropping the first one sent nothing.
+            assert_eq!(client.publish(core::publish!(client.ids(), "com.myapp.topic")).await.unwrap(), None);
+            assert!(matches!(receive(&mut router).await, Messages::Publish(_)));
+
+            drop(events);
+            let Messages::Unsubscribe(unsubscribe) = receive(&mut router).await else { panic!("expected Unsubscribe") };
+            assert_eq!(unsubscribe.subscription, 5);
+        });
+    }
+
+    #[test]
+    fn publish() {
+        run(async {
+            let (client, mut router) = pair().await;
+            assert_eq!(client.publish(core::publish!(client.ids(), "com.myapp.topic")).await.unwrap(), None);
+            assert!(matches!(receive(&mut router).await, Messages::Publish(_)));
+
+            let acknowledge = async {
+                let Messages::Publish(publish) = receive(&mut router).await else { panic!("expected Publish") };
+                deliver(&mut router, Published { request_id: publish.request_id, publication: 7 }).await;
+            };
+            let (published, _) = tokio::join!(client.publish(core::publish!(client.ids(), "com.myapp.topic", json!({"acknowledge": true}))), acknowledge);
+            assert_eq!(published.unwrap().unwrap().publication, 7);
+
+            let refuse = async {
+                let Messages::Publish(publish) = receive(&mut router).await else { panic!("expected Publish") };
+                deliver(&mut router, core::error!(publish.request_id, WampErrorEvent::Publish, "wamp.error.not_authorized")).await;
+            };
+            let (published, _) = tokio::join!(client.publish(core::publish!(client.ids(), "com.myapp.topic", json!({"acknowledge": true}))), refuse);
+            assert_eq!(published.unwrap_err().error, "wamp.error.not_authorized");
+        });
+    }
+
+    #[test]
+    fn progressive_results() {
+        run(async {
+            let (client, mut router) = pair().await;
+            let call = core::call!(client.ids(), "com.myapp.download", json!({"receive_progress": true}));
+            assert_eq!(client.call(call).await.unwrap_err().error, "wamp.error.invalid_argument");
+
+            let mut results = client.call_progress(core::call!(client.ids(), "com.myapp.download")).unwrap();
+            let Messages::Call(call) = receive(&mut router).await else { panic!("expected Call") };
+            assert!(call.receive_progress());
+            for (chunk, details) in [(1, json!({"progress": true})), (2, json!({"progress": true})), (3, json!({}))] {
+                deliver(&mut router, WampResult { request_id: call.request_id, details, args: json!([chunk]), kwargs: Value::Null }).await;
+            }
+            let mut chunks = vec![];
+            while let Some(result) = results.next().await {
+                let result = result.unwrap();
+                chunks.push((result.args[0].clone(), result.is_progress()));
+            }
+            assert_eq!(chunks, [(json!(1), true), (json!(2), true), (json!(3), false)]);
+
+            // Dropping the stream early cancels the call.
+            let results = client.call_progress(core::call!(client.ids(), "com.myapp.download")).unwrap();
+            assert!(matches!(receive(&mut router).await, Messages::Call(_)));
+            drop(results);
+            let Messages::Cancel(cancel) = receive(&mut router).await else { panic!("expected Cancel") };
+            assert_eq!(cancel.mode(), Some(CancelMode::KillNoWait));
+        });
+    }
+
+    #[test]
+    fn progressive_yields() {
+        run(async {
+            let (client, mut router) = pair().await;
+            let register = async {
+                let Messages::Register(register) = receive(&mut router).await else { panic!("expected Register") };
+                deliver(&mut router, Registered { request_id: register.request_id, registration: 3 }).await;
+            };
+            let (registered, _) = tokio::join!(client.register_progressive(core::register!(client.ids(), "com.myapp.download"), |invocation, progress| async move {
+                for chunk in [1, 2] {
+                    progress.send(Yield { request_id: 0, options: json!({}), args: json!([chunk]), kwargs: Value::Null }).unwrap();
+                }
+                Ok(Yield { request_id: invocation.request_id, options: json!({}), args: json!([3]), kwargs: Value::Null })
+            }), register);
+            registered.unwrap();
+
+            deliver(&mut router, Invocation { request_id: 8, registration: 3, details: json!({"receive_progress": true}), args: json!([]), kwargs: Value::Null }).await;
+            let mut yields = vec![];
+            for _ in 0..3 {
+                let Messages::Yield(r#yield) = receive(&mut router).await else { panic!("expected Yield") };
+                yields.push((r#yield.request_id, r#yield.args[0].clone(), r#yield.is_progress()));
+            }
+            assert_eq!(yields, [(8, json!(1), true), (8, json!(2), true), (8, json!(3), false)]);
+        });
+    }
+}
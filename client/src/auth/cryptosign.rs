@@ -0,0 +1,116 @@
+use ed25519_dalek::{Signer, SigningKey};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use core::{error::Error, protocol::messages::{Authenticate, Challenge}};
+
+use super::Authenticator;
+
+/// WAMP-Cryptosign, proves ownership of an Ed25519 key by signing the router's nonce.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-wamp-cryptosign
+pub struct Cryptosign {
+    key: SigningKey,
+    /// SHA-256 of the `tls-unique` value, mixed into the nonce when channel binding is used.
+    channel_id: Option<[u8; 32]>
+}
+
+impl Cryptosign {
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        Self {
+            key: SigningKey::from_bytes(seed),
+            channel_id: None
+        }
+    }
+
+    /// Loads the key from a 64 character hex encoded seed.
+    pub fn from_hex<T: AsRef<[u8]>>(seed: T) -> Result<Self, Error> {
+        let mut bytes = [0; 32];
+        hex::decode_to_slice(seed, &mut bytes).map_err(|_| Error::Error("Cryptosign seed must be 32 hex encoded bytes."))?;
+        Ok(Self::from_seed(&bytes))
+    }
+
+    /// Binds the signature to the TLS connection, `tls_unique` is the connection's first Finished message.
+    pub fn channel_binding(mut self, tls_unique: &[u8]) -> Self {
+        self.channel_id = Some(Sha256::digest(tls_unique).into());
+        self
+    }
+
+    /// Hex encoded public key, as announced in `Hello.details.authextra.pubkey`.
+    pub fn public_key(&self) -> String {
+        hex::encode(self.key.verifying_key().as_bytes())
+    }
+}
+
+impl Authenticator for Cryptosign {
+    fn authmethod(&self) -> &str {
+        "cryptosign"
+    }
+
+    fn authextra(&self) -> Map<String, Value> {
+        let mut authextra = Map::new();
+        authextra.insert("pubkey".to_string(), json!(self.public_key()));
+        if self.channel_id.is_some() {
+            authextra.insert("channel_binding".to_string(), json!("tls-unique"));
+        }
+        authextra
+    }
+
+    fn authenticate(&self, challenge: &Challenge) -> Result<Authenticate, Error> {
+        let nonce = challenge.details["challenge"].as_str().ok_or(Error::Error("Cryptosign challenge is missing details.challenge."))?;
+        let mut data = [0; 32];
+        hex::decode_to_slice(nonce, &mut data).map_err(|_| Error::Error("Cryptosign challenge must be 32 hex encoded bytes."))?;
+        match (&challenge.details["channel_binding"], &self.channel_id) {
+            (Value::Null, _) => {},
+            (binding, Some(channel_id)) if binding == "tls-unique" => {
+                data.iter_mut().zip(channel_id).for_each(|(byte, channel)| *byte ^= channel);
+            },
+            _ => return Err(Error::Error("Cryptosign challenge requests a channel binding this authenticator was not given."))
+        }
+        // The signature is followed by the signed data, as the router expects.
+        let signature = self.key.sign(&data);
+        Ok(Authenticate {
+            signature: hex::encode(signature.to_bytes()) + &hex::encode(data),
+            details: json!({})
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+    use serde_json::json;
+    use sha2::{Digest, Sha256};
+    use core::protocol::messages::Challenge;
+    use crate::auth::Authenticator;
+    use super::Cryptosign;
+
+    // RFC 8032 test 1.
+    const SEED: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+    const PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+    const NONCE: &str = "fa034062ad76352b53a25358fe50e2b0ff50d7f3a5f3cc4d1b4cf5d6bc8d4b1d";
+
+    fn verify(signature: &str, expected: &[u8]) {
+        let bytes = hex::decode(signature).unwrap();
+        assert_eq!(&bytes[64..], expected);
+        let key = VerifyingKey::from_bytes(&hex::decode(PUBLIC_KEY).unwrap().try_into().unwrap()).unwrap();
+        key.verify(expected, &Signature::from_slice(&bytes[..64]).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn sign() {
+        let cryptosign = Cryptosign::from_hex(SEED).unwrap();
+        assert_eq!(cryptosign.authextra()["pubkey"], json!(PUBLIC_KEY));
+        let challenge = Challenge { authmethod: "cryptosign".to_string(), details: json!({"challenge": NONCE, "channel_binding": null}) };
+        verify(&cryptosign.authenticate(&challenge).unwrap().signature, &hex::decode(NONCE).unwrap());
+        assert!(Cryptosign::from_hex("00").is_err());
+    }
+
+    #[test]
+    fn channel_binding() {
+        let challenge = Challenge { authmethod: "cryptosign".to_string(), details: json!({"challenge": NONCE, "channel_binding": "tls-unique"}) };
+        assert!(Cryptosign::from_hex(SEED).unwrap().authenticate(&challenge).is_err());
+        let cryptosign = Cryptosign::from_hex(SEED).unwrap().channel_binding(b"finished");
+        assert_eq!(cryptosign.authextra()["channel_binding"], json!("tls-unique"));
+        let expected: Vec<u8> = hex::decode(NONCE).unwrap().iter().zip(Sha256::digest(b"finished")).map(|(nonce, channel)| nonce ^ channel).collect();
+        verify(&cryptosign.authenticate(&challenge).unwrap().signature, &expected);
+    }
+}
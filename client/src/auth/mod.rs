@@ -0,0 +1,66 @@
+use serde_json::{json, Map, Value};
+use core::{error::Error, protocol::messages::{Authenticate, Challenge, HelloDetails}};
+
+pub mod wampcra;
+pub use wampcra::WampCra;
+pub mod cryptosign;
+pub use cryptosign::Cryptosign;
+
+/// Answers a router's `Challenge` for one authentication method without user code in the loop.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-authentication-methods
+pub trait Authenticator: Send {
+    /// Method this authenticator answers, as sent in `Hello.details.authmethods`.
+    fn authmethod(&self) -> &str;
+
+    /// Fields merged into `Hello.details.authextra`.
+    fn authextra(&self) -> Map<String, Value> {
+        Map::new()
+    }
+
+    fn authenticate(&self, challenge: &Challenge) -> Result<Authenticate, Error>;
+}
+
+/// Adds the authenticator's method and `authextra` to typed details.
+pub(crate) fn describe(authenticator: &dyn Authenticator, details: HelloDetails) -> HelloDetails {
+    authenticator.authextra().into_iter().fold(details.authmethod(authenticator.authmethod()), |details, (key, value)| details.authextra(key, value))
+}
+
+/// Announces the authenticator in `Hello.details`, adding its method to `authmethods` and its fields to `authextra`.
+pub(crate) fn announce(authenticator: &dyn Authenticator, details: &mut Value) {
+    if !details.is_object() {
+        *details = json!({});
+    }
+    if !details["authmethods"].is_array() {
+        details["authmethods"] = json!([]);
+    }
+    let authmethods = details["authmethods"].as_array_mut().unwrap();
+    if !authmethods.iter().any(|authmethod| authmethod == authenticator.authmethod()) {
+        authmethods.push(json!(authenticator.authmethod()));
+    }
+    let authextra = authenticator.authextra();
+    if !authextra.is_empty() {
+        if !details["authextra"].is_object() {
+            details["authextra"] = json!({});
+        }
+        details["authextra"].as_object_mut().unwrap().extend(authextra);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::{announce, Cryptosign, WampCra};
+
+    #[test]
+    fn announce_details() {
+        let mut details = json!({"roles": {"caller": {}}, "authmethods": ["ticket"], "authextra": {"device": 1}});
+        announce(&Cryptosign::from_seed(&[0; 32]), &mut details);
+        announce(&Cryptosign::from_seed(&[0; 32]), &mut details);
+        assert_eq!(details["authmethods"], json!(["ticket", "cryptosign"]));
+        assert_eq!(details["authextra"]["device"], json!(1));
+        assert!(details["authextra"]["pubkey"].is_string());
+        let mut details = json!({});
+        announce(&WampCra::new("secret"), &mut details);
+        assert_eq!(details, json!({"authmethods": ["wampcra"]}));
+    }
+}
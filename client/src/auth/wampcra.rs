@@ -0,0 +1,103 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use core::{error::Error, protocol::messages::{Authenticate, Challenge}};
+
+use super::Authenticator;
+
+/// Iteration count used when the router salts the secret without naming one.
+const DEFAULT_ITERATIONS: u32 = 1000;
+/// Derived key length used when the router salts the secret without naming one.
+const DEFAULT_KEYLEN: usize = 32;
+
+/// WAMP Challenge-Response Authentication, signs the challenge with HMAC-SHA256 keyed by the shared secret.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-wamp-challenge-response-aut
+pub struct WampCra {
+    secret: String
+}
+
+impl WampCra {
+    pub fn new<T: ToString>(secret: T) -> Self {
+        Self { secret: secret.to_string() }
+    }
+
+    /// Key the signature is computed with, the secret itself or its base64 PBKDF2 derivation when the challenge carries a salt.
+    fn key(&self, details: &Value) -> Result<Vec<u8>, Error> {
+        match details["salt"].as_str() {
+            Some(salt) => {
+                let iterations = match &details["iterations"] {
+                    Value::Null => DEFAULT_ITERATIONS,
+                    iterations => iterations.as_u64().and_then(|iterations| u32::try_from(iterations).ok()).ok_or(Error::Error("WAMP-CRA iterations must be a positive integer."))?
+                };
+                let keylen = match &details["keylen"] {
+                    Value::Null => DEFAULT_KEYLEN,
+                    keylen => keylen.as_u64().ok_or(Error::Error("WAMP-CRA keylen must be a positive integer."))? as usize
+                };
+                Ok(STANDARD.encode(derive_key(&self.secret, salt, iterations, keylen)).into_bytes())
+            },
+            None => Ok(self.secret.as_bytes().to_vec())
+        }
+    }
+}
+
+impl Authenticator for WampCra {
+    fn authmethod(&self) -> &str {
+        "wampcra"
+    }
+
+    fn authenticate(&self, challenge: &Challenge) -> Result<Authenticate, Error> {
+        let signed = challenge.details["challenge"].as_str().ok_or(Error::Error("WAMP-CRA challenge is missing details.challenge."))?;
+        // The signature covers the string exactly as received, parsing only rejects challenges that are not CRA ones.
+        serde_json::from_str::<Value>(signed)?.as_object().ok_or(Error::Error("WAMP-CRA details.challenge must be a JSON object."))?;
+        Ok(Authenticate {
+            signature: sign(&self.key(&challenge.details)?, signed),
+            details: json!({})
+        })
+    }
+}
+
+fn derive_key(secret: &str, salt: &str, iterations: u32, keylen: usize) -> Vec<u8> {
+    let mut key = vec![0; keylen];
+    pbkdf2::pbkdf2_hmac::<Sha256>(secret.as_bytes(), salt.as_bytes(), iterations, &mut key);
+    key
+}
+
+fn sign(key: &[u8], challenge: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(challenge.as_bytes());
+    STANDARD.encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use core::protocol::messages::Challenge;
+    use crate::auth::Authenticator;
+    use super::{derive_key, WampCra};
+
+    const CHALLENGE: &str = r#"{"authid":"peter","authrole":"user","authmethod":"wampcra","authprovider":"static","nonce":"LHRTC9zeOIrt_9U3","timestamp":"2014-06-22T16:36:25.448Z","session":3251278072152162}"#;
+
+    #[test]
+    fn pbkdf2() {
+        use base64::{Engine, engine::general_purpose::STANDARD};
+        assert_eq!(STANDARD.encode(derive_key("secret", "salt123", 100, 16)), "EX1Y3Q9BMM2oGLxsGqDgFQ==");
+    }
+
+    #[test]
+    fn sign() {
+        let challenge = Challenge {
+            authmethod: "wampcra".to_string(),
+            details: json!({"challenge": CHALLENGE})
+        };
+        let authenticate = WampCra::new("secret2").authenticate(&challenge).unwrap();
+        assert_eq!(authenticate.signature, "TqU/a044toQzULMBK73iKuw8/Q+aBZmmcEcBxsxoI9A=");
+
+        let salted = Challenge {
+            authmethod: "wampcra".to_string(),
+            details: json!({"challenge": CHALLENGE, "salt": "salt123", "iterations": 100, "keylen": 16})
+        };
+        assert_eq!(WampCra::new("secret2").authenticate(&salted).unwrap().signature, "1V0eadd+sRbIHHq13bP+Ad3NJt74XfKI/eLhmspRrJk=");
+        assert!(WampCra::new("secret2").authenticate(&Challenge { authmethod: "wampcra".to_string(), details: json!({}) }).is_err());
+    }
+}
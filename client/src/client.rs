@@ -1,18 +1,134 @@
-use std::{sync::{Arc, Mutex}, net::TcpStream, f32::consts::E};
+use std::{sync::{Arc, Mutex}, io::ErrorKind, net::{Shutdown, SocketAddr, TcpStream, ToSocketAddrs}, thread, time::{Duration, Instant}, f32::consts::E};
+#[cfg(unix)]
+use std::{os::unix::net::UnixStream, path::Path};
 use http::Response;
-use serde_json::from_str;
+use serde::Serialize;
 use tungstenite::{WebSocket, stream::MaybeTlsStream, connect, Message};
-use core::{error::Error, protocol::messages::{Register, Registered, Messages, challenge::{self, Challenge}, hello, invocation::{self, Invocation}, WampError, WampErrorEvent, unsubscribe::{self, Unsubscribe}, publish::{self, Publish}, unregister::{self, Unregister}, subscribe::{self, Subscribe}, cancel::{self, Cancel}, Welcome, Published, Unregistered, Event, Subscribed, Unsubscribed, WampResult, Call, Interrupt}};
+use core::{error::{CloseUri, Error}, protocol::{Feature, IdGenerator, Serializer, meta::MetaCall, rawsocket::Handshake, roles::Roles}, protocol::messages::{Register, Registered, Messages, challenge::{self, Challenge}, hello::{self, Hello, HelloDetails}, invocation::{self, Invocation}, WampError, WampErrorEvent, unsubscribe::{self, Unsubscribe}, publish::{self, Publish}, unregister::{self, Unregister}, subscribe::{self, Subscribe}, cancel::{self, Cancel, CancelMode}, Goodbye, Welcome, Published, Unregistered, Event, Subscribed, Unsubscribed, WampResult, Call, Yield}};
 
-use super::{context::{Context, CallBackResult, CallBack, self}, WampRequest};
+use super::{auth::{self, Authenticator}, context::{Context, CallBackResult, CallBack, CallHandle, InvocationCallBack, self}, rawsocket::RawSocket, ReconnectPolicy, WampRequest, AGENT};
 
-pub(crate) type Socket = Arc<Mutex<WebSocket<MaybeTlsStream<TcpStream>>>>;
+pub(crate) type Socket = Arc<Mutex<Connection>>;
+/// Opens a fresh connection to the same router, used when reconnecting.
+pub(crate) type Connector = Box<dyn Fn() -> Result<Connection, Error>>;
+
+/// Stream a session's frames travel over.
+pub enum Transport {
+    WebSocket(WebSocket<MaybeTlsStream<TcpStream>>),
+    RawSocket(RawSocket<TcpStream>),
+    #[cfg(unix)]
+    Unix(RawSocket<UnixStream>)
+}
+
+/// Transport paired with the serializer negotiated through `WampRequest.protocol` or the RawSocket handshake.
+pub struct Connection {
+    pub(crate) socket: Transport,
+    pub(crate) serializer: Serializer,
+    pub(crate) closed: bool
+}
+
+impl Connection {
+    pub(crate) fn new(socket: Transport, serializer: Serializer) -> Self {
+        Self { socket, serializer, closed: false }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Closes the transport, completing the WebSocket closing handshake when there is one.
+    pub fn close(&mut self) -> Result<(), Error> {
+        if self.closed {
+            return Ok(());
+        }
+        self.closed = true;
+        self.set_read_timeout(None)?;
+        match &mut self.socket {
+            Transport::WebSocket(socket) => {
+                socket.close(None)?;
+                loop {
+                    match socket.read() {
+                        Ok(_) => continue,
+                        Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => return Ok(()),
+                        Err(error) => return Err(error.into())
+                    }
+                }
+            },
+            Transport::RawSocket(socket) => Ok(socket.get_ref().shutdown(Shutdown::Both)?),
+            #[cfg(unix)]
+            Transport::Unix(socket) => Ok(socket.get_ref().shutdown(Shutdown::Both)?)
+        }
+    }
+
+    pub fn send<T: Serialize>(&mut self, message: &T) -> Result<(), Error> {
+        if self.closed {
+            return Err(Error::Close);
+        }
+        match &mut self.socket {
+            Transport::WebSocket(socket) => Ok(socket.send(self.serializer.to_message(message)?)?),
+            Transport::RawSocket(socket) => socket.send(&self.serializer.encode(message)?),
+            #[cfg(unix)]
+            Transport::Unix(socket) => socket.send(&self.serializer.encode(message)?)
+        }
+    }
+
+    /// Bounds how long `read` blocks, a read that runs out of time returns `None`.
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        match &mut self.socket {
+            Transport::WebSocket(socket) => match socket.get_ref() {
+                MaybeTlsStream::Plain(stream) => Ok(stream.set_read_timeout(timeout)?),
+                MaybeTlsStream::NativeTls(stream) => Ok(stream.get_ref().set_read_timeout(timeout)?),
+                _ => Ok(())
+            },
+            Transport::RawSocket(socket) => Ok(socket.get_ref().set_read_timeout(timeout)?),
+            #[cfg(unix)]
+            Transport::Unix(socket) => Ok(socket.get_ref().set_read_timeout(timeout)?)
+        }
+    }
+
+    pub fn read(&mut self) -> Result<Option<Messages>, Error> {
+        match self.read_message() {
+            Err(Error::IoError(error) | Error::TungsteniteError(tungstenite::Error::Io(error))) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => Ok(None),
+            read => read
+        }
+    }
+
+    fn read_message(&mut self) -> Result<Option<Messages>, Error> {
+        if self.closed {
+            return Err(Error::Close);
+        }
+        let data = match &mut self.socket {
+            Transport::WebSocket(socket) => match socket.read()? {
+                message @ (Message::Text(_) | Message::Binary(_)) => return Ok(Some(self.serializer.from_message(message)?)),
+                Message::Ping(_) => None,
+                Message::Close(_) => None,
+                Message::Pong(_) => None,
+                Message::Frame(_) => return Err(Error::Error("frame received from tungstenite, which their docs say isnt possible\nif this happened, run.")),
+            },
+            Transport::RawSocket(socket) => socket.read()?,
+            #[cfg(unix)]
+            Transport::Unix(socket) => socket.read()?
+        };
+        match data {
+            Some(data) => Ok(Some(self.serializer.decode(&data)?)),
+            None => Ok(None)
+        }
+    }
+}
 
 pub struct Client {
     pub socket: Socket,
     pub context: Context,
+    /// Request IDs of the session, shared with every context handed to callbacks.
+    ids: Arc<IdGenerator>,
     on_welcome: Option<super::context::CallBack<Welcome>>,
-    on_challenge: Option<super::context::CallBack<Challenge>>
+    on_challenge: Option<super::context::CallBack<Challenge>>,
+    on_goodbye: Option<super::context::CallBack<Goodbye>>,
+    authenticator: Option<Box<dyn Authenticator>>,
+    connector: Connector,
+    reconnect_policy: Option<ReconnectPolicy>,
+    /// `Hello` sent by `join`, replayed when reconnecting.
+    hello: Option<Hello>
 }
 
 macro_rules! client_context_link {
@@ -25,16 +141,57 @@ macro_rules! client_context_link {
 
 impl Client {
     pub fn connect<U: ToString, P: ToString>(request: WampRequest<U, P>) -> Result<(Client, Response<Option<Vec<u8>>>), Error> {
+        let serializer = Serializer::from_protocol(request.protocol.to_string()).ok_or(Error::Error("Unsupported WAMP subprotocol, expected wamp.2.json, wamp.2.msgpack or wamp.2.cbor."))?;
+        let (uri, protocol) = (request.uri.to_string(), request.protocol.to_string());
         let (socket, response) = connect(request)?;
-        Ok((
-            Self { 
-                socket: Arc::new(Mutex::new(socket)), 
-                context: Context::new(None),
-                on_welcome: None,
-                on_challenge: None
-            },
-            response
-        ))
+        let connector = move || {
+            let (socket, _) = connect(WampRequest { uri: uri.clone(), protocol: protocol.clone() })?;
+            Ok(Connection::new(Transport::WebSocket(socket), serializer))
+        };
+        Ok((Self::from_connection(Connection::new(Transport::WebSocket(socket), serializer), Box::new(connector)), response))
+    }
+
+    /// Connects over RawSocket on TCP, skipping the HTTP upgrade.
+    pub fn connect_rawsocket<A: ToSocketAddrs>(address: A, handshake: Handshake) -> Result<Client, Error> {
+        let addresses: Vec<SocketAddr> = address.to_socket_addrs()?.collect();
+        let connector = move || {
+            let socket = RawSocket::connect(TcpStream::connect(&addresses[..])?, handshake)?;
+            Ok(Connection::new(Transport::RawSocket(socket), handshake.serializer))
+        };
+        Ok(Self::from_connection(connector()?, Box::new(connector)))
+    }
+
+    /// Connects over RawSocket on a Unix domain socket.
+    #[cfg(unix)]
+    pub fn connect_unix<P: AsRef<Path>>(path: P, handshake: Handshake) -> Result<Client, Error> {
+        let path = path.as_ref().to_path_buf();
+        let connector = move || {
+            let socket = RawSocket::connect(UnixStream::connect(&path)?, handshake)?;
+            Ok(Connection::new(Transport::Unix(socket), handshake.serializer))
+        };
+        Ok(Self::from_connection(connector()?, Box::new(connector)))
+    }
+
+    fn from_connection(connection: Connection, connector: Connector) -> Self {
+        let socket = Arc::new(Mutex::new(connection));
+        let ids = Arc::new(IdGenerator::new());
+        Self {
+            context: Context::new(Some(Arc::clone(&socket)), Arc::clone(&ids)),
+            socket,
+            ids,
+            on_welcome: None,
+            on_challenge: None,
+            on_goodbye: None,
+            authenticator: None,
+            connector,
+            reconnect_policy: None,
+            hello: None
+        }
+    }
+
+    /// Request IDs for this session, pass it to the `core::*!` macros.
+    pub fn ids(&self) -> &IdGenerator {
+        &self.ids
     }
 
     client_context_link!(publish, Publish, CallBackResult<Published>);
@@ -43,11 +200,18 @@ impl Client {
     client_context_link!(event, Subscribed, CallBack<Event>);
     client_context_link!(unsubscribe, Unsubscribe, CallBackResult<Unsubscribed>);
     client_context_link!(subscribe, Subscribe, CallBackResult<Subscribed>);
-    client_context_link!(call, Call, CallBackResult<WampResult>);
-    client_context_link!(invocation, Registered, CallBackResult<Invocation>);
-    client_context_link!(cancel, Cancel, CallBackResult<Interrupt>);
+    client_context_link!(invocation, Registered, InvocationCallBack);
+
+    /// Sends the call, the returned handle cancels it.
+    /// With `options.timeout` set the dealer is asked to cancel it in time and the callback fails with `wamp.error.timeout` once it runs out.
+    pub fn call(&mut self, call: Call, callback: CallBackResult<WampResult>) -> Result<CallHandle, Error> {
+        self.context.call(call, callback)
+    }
 
-    
+    /// Calls one of the router's meta procedures, see `Context::meta`.
+    pub fn meta<T: 'static>(&mut self, meta: MetaCall<T>, callback: CallBackResult<T>) -> Result<CallHandle, Error> {
+        self.context.meta(meta, callback)
+    }
 
     pub fn on_welcome(&mut self, on_welcome: CallBack<Welcome>) -> &mut Self {
         self.on_welcome = Some(on_welcome);
@@ -59,6 +223,63 @@ impl Client {
         self
     }
 
+    /// Called when the router closes the session, after it was answered with `wamp.close.goodbye_and_out`.
+    pub fn on_goodbye(&mut self, on_goodbye: CallBack<Goodbye>) -> &mut Self {
+        self.on_goodbye = Some(on_goodbye);
+        self
+    }
+
+    /// Answers challenges for the authenticator's method with `Authenticate` automatically,
+    /// challenges for any other method still go to `on_challenge`.
+    pub fn authenticator<A: Authenticator + 'static>(&mut self, authenticator: A) -> &mut Self {
+        self.authenticator = Some(Box::new(authenticator));
+        self
+    }
+
+    /// Roles and features this client implements, along with the authenticator when one is set.
+    pub fn hello_details(&self) -> HelloDetails {
+        let details = HelloDetails::new()
+            .feature(Roles::Caller, Feature::CallCanceling)
+            .feature(Roles::Caller, Feature::CallTimeout)
+            .feature(Roles::Caller, Feature::ProgressiveCallResults)
+            .feature(Roles::Caller, Feature::ProgressiveCallInvocations)
+            .feature(Roles::Caller, Feature::CallerIdentification)
+            .feature(Roles::Callee, Feature::ProgressiveCallResults)
+            .feature(Roles::Callee, Feature::ProgressiveCallInvocations)
+            .feature(Roles::Callee, Feature::PatternBasedRegistration)
+            .feature(Roles::Callee, Feature::CallerIdentification)
+            .feature(Roles::Callee, Feature::SharedRegistration)
+            .feature(Roles::Publisher, Feature::PublisherExclusion)
+            .feature(Roles::Publisher, Feature::PublisherIdentification)
+            .feature(Roles::Publisher, Feature::SubscriberBlackwhiteListing)
+            .feature(Roles::Subscriber, Feature::PatternBasedSubscription)
+            .feature(Roles::Subscriber, Feature::PublisherIdentification)
+            .agent(AGENT);
+        match &self.authenticator {
+            Some(authenticator) => auth::describe(authenticator.as_ref(), details),
+            None => details
+        }
+    }
+
+    /// Sends `Hello`, deriving any details it leaves out from `hello_details`.
+    pub fn join(&mut self, mut hello: Hello) -> Result<(), Error> {
+        self.hello_details().merge_into(&mut hello.details);
+        if let Some(authenticator) = &self.authenticator {
+            auth::announce(authenticator.as_ref(), &mut hello.details);
+        }
+        self.hello = Some(hello.clone());
+        self.send(hello)
+    }
+
+    /// Reconnects with the policy when the transport drops after `join`, the session is joined again with the same `Hello`
+    /// and every live subscription and registration is replayed onto its existing handler.
+    /// Calls and acknowledged publications still waiting on the lost session fail with `wamp.error.network_failure`.
+    /// `on_welcome` is not called again for the new session.
+    pub fn reconnect_policy(&mut self, reconnect_policy: ReconnectPolicy) -> &mut Self {
+        self.reconnect_policy = Some(reconnect_policy);
+        self
+    }
+
     pub fn handle_and_empty_contexts(&mut self, message: Messages) -> Result<Option<Messages>, Error> {
             match message {
                 Messages::Error(error) => {
@@ -104,8 +325,9 @@ impl Client {
         }
     }
 
+    /// Dispatches incoming messages until the session is closed.
     pub fn event_loop(&mut self) -> Result<(), Error> {
-        loop {
+        while !self.socket.lock().unwrap().is_closed() {
             let message = self.read()?;
             if let Some(_) = message {
                 self.read_contexts(message)?;
@@ -114,6 +336,24 @@ impl Client {
         Ok(())
     }
 
+    /// Closes the session with `reason`, messages arriving before the router's `Goodbye` are still dispatched.
+    /// Returns the router's `Goodbye` once the transport is closed.
+    pub fn leave(&mut self, reason: CloseUri) -> Result<Goodbye, Error> {
+        self.send(core::goodbye!(reason.as_str()))?;
+        loop {
+            match self.read()? {
+                Some(Messages::Goodbye(goodbye)) => {
+                    self.socket.lock().unwrap().close()?;
+                    return Ok(goodbye);
+                },
+                Some(message) => {
+                    self.read_contexts(Some(message))?;
+                },
+                None => {}
+            }
+        }
+    }
+
     pub fn read_contexts(&mut self, message: Option<Messages>) -> Result<Option<Messages>, Error> {
         let ctx = self.get_message_context(message)?;
         let ctx = self.extend_context(ctx)?;
@@ -139,8 +379,8 @@ impl Client {
                     Messages::Error(error) => {
                         match error.event {
                             WampErrorEvent::Call => {
-                                if let Some((call, callback)) = self.context.find_by_error_call(&error) {
-                                    let context = callback(Context::new(Some(Arc::clone(&self.socket))), Err(error.clone()));
+                                if let Some((_, mut callback)) = self.context.finish_call(error.request_id) {
+                                    let context = callback(Context::new(Some(Arc::clone(&self.socket)), Arc::clone(&self.ids)), Err(error.clone()));
                                     Ok(Some((Messages::from(error), Some(context))))
                                 } else {
                                     Ok(Some((Messages::from(error), None)))
@@ -148,7 +388,7 @@ impl Client {
                             },
                             WampErrorEvent::Unsubscribe => {
                                 if let Some((unsubscribe, callback)) = self.context.find_by_error_unsubscribe(&error) {
-                                    let context = callback(Context::new(Some(Arc::clone(&self.socket))), Err(error.clone()));
+                                    let context = callback(Context::new(Some(Arc::clone(&self.socket)), Arc::clone(&self.ids)), Err(error.clone()));
                                     Ok(Some((Messages::from(error), Some(context))))
                                 } else {
                                     Ok(Some((Messages::from(error), None)))
@@ -156,15 +396,15 @@ impl Client {
                             },
                             WampErrorEvent::Subscribe => {
                                 if let Some((subscribe, callback)) = self.context.find_by_error_subscribe(&error) {
-                                    let context = callback(Context::new(Some(Arc::clone(&self.socket))), Err(error.clone()));
+                                    let context = callback(Context::new(Some(Arc::clone(&self.socket)), Arc::clone(&self.ids)), Err(error.clone()));
                                     Ok(Some((Messages::from(error), Some(context))))
                                 } else {
                                     Ok(Some((Messages::from(error), None)))
                                 }
                             },
                             WampErrorEvent::Publish => {
-                                if let Some((publish, callback)) = self.context.find_by_error_publish(&error) {
-                                    let context = callback(Context::new(Some(Arc::clone(&self.socket))), Err(error.clone()));
+                                if let Some((_, mut callback)) = self.context.finish_publish(error.request_id) {
+                                    let context = callback(Context::new(Some(Arc::clone(&self.socket)), Arc::clone(&self.ids)), Err(error.clone()));
                                     Ok(Some((Messages::from(error), Some(context))))
                                 } else {
                                     Ok(Some((Messages::from(error), None)))
@@ -172,7 +412,7 @@ impl Client {
                             },
                             WampErrorEvent::Register => {
                                 if let Some((register, callback)) = self.context.find_by_error_register(&error) {
-                                    let context = callback(Context::new(Some(Arc::clone(&self.socket))), Err(error.clone()));
+                                    let context = callback(Context::new(Some(Arc::clone(&self.socket)), Arc::clone(&self.ids)), Err(error.clone()));
                                     Ok(Some((Messages::from(error), Some(context))))
                                 } else {
                                     Ok(Some((Messages::from(error), None)))
@@ -180,7 +420,7 @@ impl Client {
                             },
                             WampErrorEvent::Unregister => {
                                 if let Some((unregister, callback)) = self.context.find_by_error_unregister(&error) {
-                                    let context = callback(Context::new(Some(Arc::clone(&self.socket))), Err(error.clone()));
+                                    let context = callback(Context::new(Some(Arc::clone(&self.socket)), Arc::clone(&self.ids)), Err(error.clone()));
                                     Ok(Some((Messages::from(error), Some(context))))
                                 } else {
                                     Ok(Some((Messages::from(error), None)))
@@ -189,38 +429,50 @@ impl Client {
                             WampErrorEvent::Invocation => {
                                 Ok(Some((Messages::from(error), None)))
                             },
+                            // Cancel is answered on the call it cancels.
                             WampErrorEvent::Cancel => {
-                                if let Some((cancel, callback)) = self.context.find_by_error_cancel(&error) {
-                                    let context = callback(Context::new(Some(self.socket.clone())), Err(error.clone()));
-                                    Ok(Some((Messages::from(error), Some(context))))
-                                } else {
-                                    Ok(Some((Messages::from(error), None)))
-                                }
+                                Ok(Some((Messages::from(error), None)))
                             }
                         }
                     },
                     Messages::Event(event) => {
                         if let Some((subscribe, callback)) = self.context.events.iter_mut().find(|(subscribed, _)| subscribed.subscription == event.subscription) {
-                            let context = callback(Context::new(Some(Arc::clone(&self.socket))), event.clone());
+                            let context = callback(Context::new(Some(Arc::clone(&self.socket)), Arc::clone(&self.ids)), event.clone());
                             Ok(Some((Messages::from(event), Some(context))))
                         } else {
                             Ok(Some((Messages::from(event), None)))
                         }
                     },
                     Messages::Goodbye(goodbye) => {
-                        todo!("Goodbye handlers not implemented.")
+                        self.send(core::goodbye!(CloseUri::GoodbyeAndOut.as_str()))?;
+                        self.socket.lock().unwrap().close()?;
+                        if let Some(callback) = &mut self.on_goodbye {
+                            let context = callback(Context::new(Some(self.socket.clone()), Arc::clone(&self.ids)), goodbye.clone());
+                            Ok(Some((Messages::from(goodbye), Some(context))))
+                        } else {
+                            Ok(Some((Messages::from(goodbye), None)))
+                        }
                     },
-                    Messages::Interrupt(interrupt) => {
-                        if let Some((cancel, callback)) = self.context.cancelations.iter_mut().find(|(cancel, _)| cancel.request_id == interrupt.request_id) {
-                            let context = callback(Context::new(Some(self.socket.clone())), Ok(interrupt.clone()));
-                            Ok(Some((Messages::from(interrupt), Some(context))))
+                    // Invocation handlers run to completion before the next read, so the invocation was already answered.
+                    Messages::Interrupt(interrupt) => Ok(Some((Messages::from(interrupt), None))),
+                    Messages::Invocation(invocation) => {
+                        if let Some((_, callback)) = self.context.find_invocation(&invocation) {
+                            let (context, reply) = callback(Context::new(Some(self.socket.clone()), Arc::clone(&self.ids)), invocation.clone());
+                            match reply {
+                                Ok(Some(r#yield)) => self.reply_invocation(&invocation, Ok(r#yield))?,
+                                Err(error) => self.reply_invocation(&invocation, Err(error))?,
+                                // Nothing to send until a later chunk or the handler's own context answers.
+                                Ok(None) => {}
+                            }
+                            Ok(Some((Messages::from(invocation), Some(context))))
                         } else {
-                            Ok(Some((Messages::from(interrupt), None)))
+                            self.reply_invocation(&invocation, Err(core::error!(invocation.request_id, WampErrorEvent::Invocation, "wamp.error.no_such_registration")))?;
+                            Ok(Some((Messages::from(invocation), None)))
                         }
                     },
                     Messages::Published(published) => {
-                        if let Some((publish, callback)) = self.context.publications.iter_mut().find(|(publish, _)| publish.request_id == published.request_id) {
-                            let context = callback(Context::new(Some(Arc::clone(&self.socket))), Ok(published.clone()));
+                        if let Some((_, mut callback)) = self.context.finish_publish(published.request_id) {
+                            let context = callback(Context::new(Some(Arc::clone(&self.socket)), Arc::clone(&self.ids)), Ok(published.clone()));
                             Ok(Some((Messages::from(published), Some(context))))
                         } else {
                             Ok(Some((Messages::from(published), None)))
@@ -228,23 +480,27 @@ impl Client {
                     },
                     Messages::Registered(registered) => {
                         if let Some((register, callback)) = self.context.registrations.iter_mut().find(|(register, _)| register.request_id == registered.request_id) {
-                            let context = callback(Context::new(Some(Arc::clone(&self.socket))), Ok(registered.clone()));
+                            let context = callback(Context::new(Some(Arc::clone(&self.socket)), Arc::clone(&self.ids)), Ok(registered.clone()));
                             Ok(Some((Messages::from(registered), Some(context))))
                         } else {
                             Ok(Some((Messages::from(registered), None)))
                         }
                     },
                     Messages::Result(result) => {
-                        if let Some((call, callback)) = self.context.calls.iter_mut().find(|(call, _)| call.request_id == result.request_id) {
-                            let context = callback(Context::new(Some(self.socket.clone())), Ok(result.clone()));
-                            Ok(Some((Messages::from(result), Some(context))))
+                        let context = if result.is_progress() {
+                            // More results follow, the call stays pending.
+                            self.context.calls.iter_mut()
+                                .find(|(call, _)| call.request_id == result.request_id)
+                                .map(|(_, callback)| callback(Context::new(Some(self.socket.clone()), Arc::clone(&self.ids)), Ok(result.clone())))
                         } else {
-                            Ok(Some((Messages::from(result), None)))
-                        }
+                            self.context.finish_call(result.request_id)
+                                .map(|(_, mut callback)| callback(Context::new(Some(self.socket.clone()), Arc::clone(&self.ids)), Ok(result.clone())))
+                        };
+                        Ok(Some((Messages::from(result), context)))
                     },
                     Messages::Subscribed(subscribed) => {
                         if let Some((subscribe, callback)) = self.context.subscriptions.iter_mut().find(|(subscribe, _)| subscribe.request_id == subscribed.request_id) {
-                            let context = callback(Context::new(Some(self.socket.clone())), Ok(subscribed.clone()));
+                            let context = callback(Context::new(Some(self.socket.clone()), Arc::clone(&self.ids)), Ok(subscribed.clone()));
                             Ok(Some((Messages::from(subscribed), Some(context))))
                         } else {
                             Ok(Some((Messages::from(subscribed), None)))
@@ -252,7 +508,7 @@ impl Client {
                     },
                     Messages::Unregistered(unregistered) => {
                         if let Some((unregister, callback)) = self.context.find_unregister(&unregistered) {
-                            let context = callback(Context::new(Some(self.socket.clone())), Ok(unregistered.clone()));
+                            let context = callback(Context::new(Some(self.socket.clone()), Arc::clone(&self.ids)), Ok(unregistered.clone()));
                             Ok(Some((Messages::from(unregistered), Some(context))))
                         } else {
                             Ok(Some((Messages::from(unregistered), None)))
@@ -260,7 +516,7 @@ impl Client {
                     },
                     Messages::Unsubscribed(unsubscribed) => {
                         if let Some((unsubscribe, callback)) = self.context.unsubscriptions.iter_mut().find(|(unsubscribe, _)| unsubscribe.request_id == unsubscribed.request_id) {
-                            let context = callback(Context::new(Some(self.socket.clone())), Ok(unsubscribed.clone()));
+                            let context = callback(Context::new(Some(self.socket.clone()), Arc::clone(&self.ids)), Ok(unsubscribed.clone()));
                             Ok(Some((Messages::from(unsubscribed), Some(context))))
                         } else {
                             Ok(Some((Messages::from(unsubscribed), None)))
@@ -268,7 +524,7 @@ impl Client {
                     },
                     Messages::Welcome(welcome) => {
                         if let Some(callback) = &mut self.on_welcome {
-                            let context = callback(Context::new(Some(self.socket.clone())), welcome.clone());
+                            let context = callback(Context::new(Some(self.socket.clone()), Arc::clone(&self.ids)), welcome.clone());
                             Ok(Some((Messages::from(welcome), Some(context))))
                         } else {
                             Ok(Some((Messages::from(welcome), None)))
@@ -276,8 +532,15 @@ impl Client {
                     },
                     Messages::Challenge(challenge) => {
                         println!("challenge received");
-                        if let Some(callback) = &mut self.on_challenge {
-                            let context = callback(Context::new(Some(self.socket.clone())), challenge.clone());
+                        let authenticate = match &self.authenticator {
+                            Some(authenticator) if authenticator.authmethod() == challenge.authmethod => Some(authenticator.authenticate(&challenge)?),
+                            _ => None
+                        };
+                        if let Some(authenticate) = authenticate {
+                            self.send(authenticate)?;
+                            Ok(Some((Messages::from(challenge), None)))
+                        } else if let Some(callback) = &mut self.on_challenge {
+                            let context = callback(Context::new(Some(self.socket.clone()), Arc::clone(&self.ids)), challenge.clone());
                             Ok(Some((Messages::from(challenge), Some(context))))
                         } else {
                             Ok(Some((Messages::from(challenge), None)))
@@ -289,7 +552,6 @@ impl Client {
                     Messages::Yield(r#yield) => Err(Error::InvalidFrameReceived(r#yield.into())),
                     Messages::Authenticate(authenticate) => Err(Error::InvalidFrameReceived(authenticate.into())),
                     Messages::Hello(hello) => Err(Error::InvalidFrameReceived(hello.into())),
-                    Messages::Invocation(invocation) => Err(Error::InvalidFrameReceived(invocation.into())),
                     Messages::Publish(publish) => Err(Error::InvalidFrameReceived(publish.into())),
                     Messages::Register(register) => Err(Error::InvalidFrameReceived(register.into())),
                     Messages::Subscribe(subscribe) => Err(Error::InvalidFrameReceived(subscribe.into())),
@@ -301,28 +563,388 @@ impl Client {
         }
     }
 
+    /// Sends the outcome of an invocation handler back to the dealer, binding it to the invocation's request ID.
+    fn reply_invocation(&mut self, invocation: &Invocation, reply: Result<Yield, WampError>) -> Result<(), Error> {
+        match reply {
+            Ok(mut r#yield) => {
+                r#yield.request_id = invocation.request_id;
+                self.send(r#yield)
+            },
+            Err(mut error) => {
+                error.event = WampErrorEvent::Invocation;
+                error.request_id = invocation.request_id;
+                self.send(error)
+            }
+        }
+    }
+
+    /// Reads the next message, reconnecting first when the transport dropped and a reconnect policy is set.
+    /// Returns `None` early when a call's deadline passes, after its callback got `wamp.error.timeout`.
     pub fn read(&mut self) -> Result<Option<Messages>, Error> {
-        let message = self.socket.lock().unwrap().read().unwrap();
-        match message {
-            Message::Text(message) => Ok(Some(from_str(&message)?)),
-            Message::Ping(_) => Ok(None),
-            Message::Close(_) => Ok(None),
-            Message::Binary(_) => Err(Error::Error("Error: Binary frame received\n\nCurrently I have not added support for serialization beyond string json format. Please create an issue if you are interested in contributing. I am planning on implementing support for the msg_pack format as well.")),
-            Message::Pong(_) => Ok(None),
-            Message::Frame(_) => Err(Error::Error("frame received from tungstenite, which their docs say isnt possible\nif this happened, run.")),
+        let now = Instant::now();
+        let timeout = self.context.deadlines.iter().map(|(_, deadline)| deadline.saturating_duration_since(now).max(Duration::from_millis(1))).min();
+        let read = {
+            let mut connection = self.socket.lock().unwrap();
+            connection.set_read_timeout(timeout).and_then(|_| connection.read())
+        };
+        self.expire_calls()?;
+        match read {
+            Err(Error::TungsteniteError(_) | Error::IoError(_)) if self.reconnect_policy.is_some() && self.hello.is_some() => {
+                self.reconnect()?;
+                Ok(None)
+            },
+            read => read
         }
     }
 
+    /// Fails every call whose deadline passed with `wamp.error.timeout`, the dealer is told to drop them too.
+    fn expire_calls(&mut self) -> Result<(), Error> {
+        for (call, mut callback) in self.context.expire_calls(Instant::now()) {
+            let _ = self.send(Cancel::new(call.request_id, CancelMode::KillNoWait));
+            let context = callback(Context::new(Some(Arc::clone(&self.socket)), Arc::clone(&self.ids)), Err(core::error!(call.request_id, WampErrorEvent::Call, "wamp.error.timeout")));
+            self.context.extend(context);
+        }
+        Ok(())
+    }
 
-    pub fn send<T: TryInto<Message>>(&mut self, message: T) -> Result<(), Error> 
-    where
-        Error: From<<T as TryInto<Message>>::Error>
+    fn reconnect(&mut self) -> Result<(), Error> {
+        let policy = self.reconnect_policy.clone().unwrap();
+        let mut attempt = 0;
+        let mut last_error = Error::Close;
+        while let Some(delay) = policy.delay(attempt) {
+            thread::sleep(delay);
+            let rejoined = self.rejoin().and_then(|_| {
+                self.fail_pending();
+                self.replay()
+            });
+            match rejoined {
+                Ok(()) => return Ok(()),
+                // The router refused the session, retrying would be refused again.
+                Err(Error::Abort(abort)) => return Err(Error::Abort(abort)),
+                Err(error) => last_error = error
+            }
+            attempt += 1;
+        }
+        Err(last_error)
+    }
 
-    {
-        let socket = &mut *self.socket.lock().unwrap();
-        Ok(socket.send(message.try_into()?)?)
+    /// Fails the calls and acknowledged publications of the lost session with `wamp.error.network_failure`,
+    /// their callbacks already run against the new session.
+    fn fail_pending(&mut self) {
+        let (calls, publications) = self.context.abandon();
+        for (call, mut callback) in calls {
+            let context = callback(Context::new(Some(Arc::clone(&self.socket)), Arc::clone(&self.ids)), Err(core::error!(call.request_id, WampErrorEvent::Call, "wamp.error.network_failure")));
+            self.context.extend(context);
+        }
+        for (publish, mut callback) in publications {
+            let context = callback(Context::new(Some(Arc::clone(&self.socket)), Arc::clone(&self.ids)), Err(core::error!(publish.request_id, WampErrorEvent::Publish, "wamp.error.network_failure")));
+            self.context.extend(context);
+        }
+    }
+
+    /// Opens a new connection and joins with the stored `Hello`, challenges are answered as usual.
+    fn rejoin(&mut self) -> Result<(), Error> {
+        let connection = (self.connector)()?;
+        *self.socket.lock().unwrap() = connection;
+        self.send(self.hello.clone().unwrap())?;
+        loop {
+            let message = self.socket.lock().unwrap().read()?;
+            match message {
+                Some(Messages::Welcome(_)) => return Ok(()),
+                Some(Messages::Abort(abort)) => return Err(Error::Abort(abort)),
+                Some(challenge @ Messages::Challenge(_)) => {
+                    self.read_contexts(Some(challenge))?;
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// Subscribes and registers again for every handler that is still attached, and points the handlers at the new IDs.
+    /// Subscriptions and registrations the router refuses are dropped along with their handlers.
+    fn replay(&mut self) -> Result<(), Error> {
+        let subscriptions: Vec<(u64, Subscribe)> = self.context.events.iter()
+            .filter(|(subscribed, _)| !self.context.unsubscriptions.iter().any(|(unsubscribe, _)| unsubscribe.subscription == subscribed.subscription))
+            .filter_map(|(subscribed, _)| {
+                let (subscribe, _) = self.context.subscriptions.iter().find(|(subscribe, _)| subscribe.request_id == subscribed.request_id)?;
+                Some((subscribed.subscription, subscribe.clone()))
+            })
+            .collect();
+        for (subscription, mut subscribe) in subscriptions {
+            let previous = subscribe.request_id;
+            subscribe.request_id = self.ids.next();
+            self.send(&subscribe)?;
+            match self.await_reply(WampErrorEvent::Subscribe, subscribe.request_id)? {
+                Messages::Subscribed(subscribed) => {
+                    self.context.events.iter_mut()
+                        .filter(|(entry, _)| entry.subscription == subscription)
+                        .for_each(|(entry, _)| *entry = subscribed.clone());
+                    self.context.subscriptions.iter_mut()
+                        .filter(|(entry, _)| entry.request_id == previous)
+                        .for_each(|(entry, _)| entry.request_id = subscribe.request_id);
+                },
+                _ => self.context.events.retain(|(entry, _)| entry.subscription != subscription)
+            }
+        }
+
+        let registrations: Vec<(u64, Register)> = self.context.invocations.iter()
+            .filter(|(registered, _)| !self.context.unregistrations.iter().any(|(unregister, _)| unregister.registration == registered.registration))
+            .filter_map(|(registered, _)| {
+                let (register, _) = self.context.registrations.iter().find(|(register, _)| register.request_id == registered.request_id)?;
+                Some((registered.registration, register.clone()))
+            })
+            .collect();
+        for (registration, mut register) in registrations {
+            let previous = register.request_id;
+            register.request_id = self.ids.next();
+            self.send(&register)?;
+            match self.await_reply(WampErrorEvent::Register, register.request_id)? {
+                Messages::Registered(registered) => {
+                    self.context.invocations.iter_mut()
+                        .filter(|(entry, _)| entry.registration == registration)
+                        .for_each(|(entry, _)| *entry = registered.clone());
+                    self.context.registrations.iter_mut()
+                        .filter(|(entry, _)| entry.request_id == previous)
+                        .for_each(|(entry, _)| entry.request_id = register.request_id);
+                },
+                _ => self.context.invocations.retain(|(entry, _)| entry.registration != registration)
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads until the router answers the request, dispatching everything else as usual.
+    fn await_reply(&mut self, event: WampErrorEvent, request_id: u64) -> Result<Messages, Error> {
+        loop {
+            let message = self.socket.lock().unwrap().read()?;
+            match message {
+                Some(Messages::Subscribed(subscribed)) if event == WampErrorEvent::Subscribe && subscribed.request_id == request_id => return Ok(subscribed.into()),
+                Some(Messages::Registered(registered)) if event == WampErrorEvent::Register && registered.request_id == request_id => return Ok(registered.into()),
+                Some(Messages::Error(error)) if error.event == event && error.request_id == request_id => return Ok(error.into()),
+                Some(message) => {
+                    self.read_contexts(Some(message))?;
+                },
+                None => {}
+            }
+        }
+    }
+
+
+    pub fn send<T: Serialize>(&mut self, message: T) -> Result<(), Error> {
+        self.socket.lock().unwrap().send(&message)
     }
 
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, net::{TcpListener, TcpStream}, rc::Rc, sync::mpsc, thread, time::Duration};
+    use serde::Serialize;
+    use serde_json::{Value, json};
+    use core::{error::Error, protocol::{Serializer, rawsocket::Handshake}, protocol::messages::{CancelMode, Event, HelloDetails, Invocation, Messages, Registered, Subscribed, WampErrorEvent, Welcome, Yield}};
+    use crate::{rawsocket::RawSocket, ReconnectPolicy, AGENT};
+    use super::{Client, Connection, Transport};
+
+    /// A connection to a stand-in router, whose end of the RawSocket is returned along with it.
+    fn pair() -> (Connection, RawSocket<TcpStream>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let router = thread::spawn(move || RawSocket::connect(listener.accept().unwrap().0, Handshake::new(Serializer::Json)).unwrap());
+        let socket = RawSocket::connect(TcpStream::connect(address).unwrap(), Handshake::new(Serializer::Json)).unwrap();
+        (Connection::new(Transport::RawSocket(socket), Serializer::Json), router.join().unwrap())
+    }
+
+    fn receive(router: &mut RawSocket<TcpStream>) -> Messages {
+        Serializer::Json.decode(&router.read().unwrap().unwrap()).unwrap()
+    }
+
+    fn deliver<T: Serialize>(router: &mut RawSocket<TcpStream>, message: T) {
+        router.send(&Serializer::Json.encode(&message).unwrap()).unwrap();
+    }
+
+    /// Reads and dispatches the next message.
+    fn step(client: &mut Client) {
+        let message = client.read().unwrap();
+        client.read_contexts(message).unwrap();
+    }
+
+    #[test]
+    fn join() {
+        let (connection, mut router) = pair();
+        let mut client = Client::from_connection(connection, Box::new(|| Err(Error::Close)));
+        client.join(core::hello!("realm1", HelloDetails::new().authmethod("ticket"))).unwrap();
+        let Messages::Hello(hello) = receive(&mut router) else {
+            panic!("expected Hello");
+        };
+        assert_eq!(hello.details["authmethods"], json!(["ticket"]));
+        assert_eq!(hello.details["roles"]["caller"]["features"]["call_canceling"], json!(true));
+        assert_eq!(hello.details["roles"]["subscriber"]["features"]["pattern_based_subscription"], json!(true));
+        assert_eq!(hello.details["agent"], json!(AGENT));
+    }
+
+    #[test]
+    fn reconnect() {
+        let (connection, mut router) = pair();
+        let (connected, routers) = mpsc::channel();
+        let mut client = Client::from_connection(connection, Box::new(move || {
+            let (connection, router) = pair();
+            connected.send(router).unwrap();
+            Ok(connection)
+        }));
+        client.reconnect_policy(ReconnectPolicy::new().initial_delay(Duration::ZERO).max_attempts(Some(1)));
+        let outcomes = Rc::new(RefCell::new(vec![]));
+
+        client.join(core::hello!("realm1")).unwrap();
+        assert!(matches!(receive(&mut router), Messages::Hello(_)));
+        deliver(&mut router, Welcome { session: 1, details: json!({}) });
+        step(&mut client);
+
+        let events = Rc::clone(&outcomes);
+        client.subscribe(core::subscribe!(client.ids(), "com.myapp.topic"), Box::new(move |mut ctx, subscribed| {
+            let events = Rc::clone(&events);
+            ctx.event(subscribed.unwrap(), Box::new(move |ctx, event| {
+                events.borrow_mut().push(format!("event {}", event.args[0]));
+                ctx
+            })).unwrap();
+            ctx
+        })).unwrap();
+        let Messages::Subscribe(subscribe) = receive(&mut router) else { panic!("expected Subscribe") };
+        deliver(&mut router, Subscribed { request_id: subscribe.request_id, subscription: 5 });
+        step(&mut client);
+
+        client.register(core::register!(client.ids(), "com.myapp.add"), Box::new(|mut ctx, registered| {
+            ctx.invocation(registered.unwrap(), Box::new(|ctx, invocation| (ctx, Ok(Some(Yield {
+                request_id: invocation.request_id,
+                options: json!({}),
+                args: invocation.args,
+                kwargs: Value::Null
+            }))))).unwrap();
+            ctx
+        })).unwrap();
+        let Messages::Register(register) = receive(&mut router) else { panic!("expected Register") };
+        deliver(&mut router, Registered { request_id: register.request_id, registration: 6 });
+        step(&mut client);
+
+        let calls = Rc::clone(&outcomes);
+        client.call(core::call!(client.ids(), "com.myapp.slow", json!({"timeout": 60000})), Box::new(move |ctx, result| {
+            calls.borrow_mut().push(format!("call {}", result.unwrap_err().error));
+            ctx
+        })).unwrap();
+        let publications = Rc::clone(&outcomes);
+        client.publish(core::publish!(client.ids(), "com.myapp.topic", json!({"acknowledge": true})), Box::new(move |ctx, published| {
+            publications.borrow_mut().push(format!("publish {}", published.unwrap_err().error));
+            ctx
+        })).unwrap();
+        assert!(matches!(receive(&mut router), Messages::Call(_)));
+        assert!(matches!(receive(&mut router), Messages::Publish(_)));
+
+        // The new router welcomes the session again and is asked for the same subscription and registration.
+        let router = thread::spawn(move || {
+            drop(router);
+            let mut router = routers.recv().unwrap();
+            assert!(matches!(receive(&mut router), Messages::Hello(_)));
+            deliver(&mut router, Welcome { session: 2, details: json!({}) });
+            let Messages::Subscribe(resubscribe) = receive(&mut router) else { panic!("expected Subscribe") };
+            assert_eq!(resubscribe.topic, subscribe.topic);
+            assert_ne!(resubscribe.request_id, subscribe.request_id);
+            deliver(&mut router, Subscribed { request_id: resubscribe.request_id, subscription: 15 });
+            let Messages::Register(reregister) = receive(&mut router) else { panic!("expected Register") };
+            assert_eq!(reregister.procedure, register.procedure);
+            deliver(&mut router, Registered { request_id: reregister.request_id, registration: 16 });
+            deliver(&mut router, Event { subscription: 15, publication: 1, details: json!({}), args: json!([1]), kwargs: Value::Null });
+            router
+        });
+        step(&mut client);
+        assert!(client.context.calls.is_empty() && client.context.publications.is_empty() && client.context.deadlines.is_empty());
+        assert_eq!(client.context.events[0].0.subscription, 15);
+        assert_eq!(client.context.invocations[0].0.registration, 16);
+        step(&mut client);
+        let _router = router.join().unwrap();
+        assert_eq!(*outcomes.borrow(), ["call wamp.error.network_failure", "publish wamp.error.network_failure", "event 1"]);
+    }
+
+    #[test]
+    fn deadline() {
+        let (connection, mut router) = pair();
+        let mut client = Client::from_connection(connection, Box::new(|| Err(Error::Close)));
+        let outcomes = Rc::new(RefCell::new(vec![]));
+        let calls = Rc::clone(&outcomes);
+        client.call(core::call!(client.ids(), "com.myapp.slow", json!({"timeout": 20})), Box::new(move |ctx, result| {
+            calls.borrow_mut().push(result.unwrap_err().error.to_string());
+            ctx
+        })).unwrap();
+        let Messages::Call(call) = receive(&mut router) else { panic!("expected Call") };
+
+        // The router never answers, reads give up at the deadline.
+        while outcomes.borrow().is_empty() {
+            assert!(client.read().unwrap().is_none());
+        }
+        assert_eq!(*outcomes.borrow(), ["wamp.error.timeout"]);
+        assert!(client.context.calls.is_empty() && client.context.deadlines.is_empty());
+        let Messages::Cancel(cancel) = receive(&mut router) else { panic!("expected Cancel") };
+        assert_eq!((cancel.request_id, cancel.mode()), (call.request_id, Some(CancelMode::KillNoWait)));
+    }
+
+    #[test]
+    fn cancel() {
+        let (connection, mut router) = pair();
+        let mut client = Client::from_connection(connection, Box::new(|| Err(Error::Close)));
+        let outcomes = Rc::new(RefCell::new(vec![]));
+        let calls = Rc::clone(&outcomes);
+        let handle = client.call(core::call!(client.ids(), "com.myapp.slow"), Box::new(move |ctx, result| {
+            calls.borrow_mut().push(result.unwrap_err().error.to_string());
+            ctx
+        })).unwrap();
+        assert!(matches!(receive(&mut router), Messages::Call(_)));
+
+        handle.cancel(CancelMode::Kill).unwrap();
+        let Messages::Cancel(cancel) = receive(&mut router) else { panic!("expected Cancel") };
+        assert_eq!((cancel.request_id, cancel.mode()), (handle.request_id, Some(CancelMode::Kill)));
+        assert!(outcomes.borrow().is_empty());
+        deliver(&mut router, core::error!(handle.request_id, WampErrorEvent::Call, "wamp.error.canceled"));
+        step(&mut client);
+        assert_eq!(*outcomes.borrow(), ["wamp.error.canceled"]);
+        assert!(client.context.calls.is_empty());
+    }
+
+    #[test]
+    fn progressive_invocation() {
+        let (connection, mut router) = pair();
+        let mut client = Client::from_connection(connection, Box::new(|| Err(Error::Close)));
+        client.register(core::register!(client.ids(), "com.myapp.upload"), Box::new(|mut ctx, registered| {
+            let mut received = vec![];
+            ctx.invocation(registered.unwrap(), Box::new(move |ctx, invocation| {
+                received.push(invocation.args[0].clone());
+                let reply = match (invocation.is_progress(), invocation.args[0].as_str()) {
+                    // Nothing is answered until the last chunk.
+                    (true, Some("chunk")) => None,
+                    // An early answer ends the invocation, later chunks are not waited for.
+                    _ => Some(Yield { request_id: 0, options: json!({}), args: json!([received.len()]), kwargs: Value::Null })
+                };
+                (ctx, Ok(reply))
+            })).unwrap();
+            ctx
+        })).unwrap();
+        let Messages::Register(register) = receive(&mut router) else { panic!("expected Register") };
+        deliver(&mut router, Registered { request_id: register.request_id, registration: 3 });
+        step(&mut client);
+
+        let chunk = |progress: bool, arg: &str| Invocation { request_id: 8, registration: 3, details: json!({"progress": progress}), args: json!([arg]), kwargs: Value::Null };
+        deliver(&mut router, chunk(true, "chunk"));
+        deliver(&mut router, chunk(true, "chunk"));
+        deliver(&mut router, chunk(false, "last"));
+        for _ in 0..3 {
+            step(&mut client);
+        }
+        let Messages::Yield(r#yield) = receive(&mut router) else { panic!("expected Yield") };
+        assert_eq!((r#yield.request_id, r#yield.args.clone()), (8, json!([3])));
+
+        // A final answer to an intermediate chunk is sent rather than dropped.
+        deliver(&mut router, chunk(true, "stop"));
+        step(&mut client);
+        let Messages::Yield(r#yield) = receive(&mut router) else { panic!("expected Yield") };
+        assert_eq!((r#yield.request_id, r#yield.is_progress()), (8, false));
+    }
+}
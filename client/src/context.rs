@@ -1,6 +1,7 @@
-use std::{net::TcpStream, sync::{Arc, Mutex}};
+use std::{net::TcpStream, sync::{Arc, Mutex}, time::Instant};
 use tungstenite::{WebSocket, stream::MaybeTlsStream, Message};
-use core::{protocol::messages::*, error::Error};
+use serde::Serialize;
+use core::{protocol::{messages::*, meta::MetaCall, IdGenerator, Serializer}, error::Error};
 
 use super::client::{self, Socket};
 
@@ -8,12 +9,16 @@ pub(crate) type CallBack<T> = Box<dyn FnMut(Context, T) -> Context>;
 pub(crate) type CallBackResult<T> = CallBack<Result<T, WampError>>;
 pub(crate) type CallBackVec<K, V> = Vec<(K, CallBack<V>)>;
 pub(crate) type CallBackVecResult<K, V> = CallBackVec<K, Result<V, WampError>>;
+/// Callee handler, the returned result is sent back to the dealer as a `Yield` or an `Error`.
+/// `Ok(None)` sends nothing yet, as for the chunks of a progressive invocation before the last one,
+/// or when the handler answers later through its context.
+pub(crate) type InvocationCallBack = Box<dyn FnMut(Context, Invocation) -> (Context, Result<Option<Yield>, WampError>)>;
 
 
 macro_rules! create_push_methods {
     ($method_name: ident, $vec_name: ident, $var_type: ident, $callback: ty) => {
         pub fn $method_name(&mut self, $method_name: $var_type, callback: $callback) -> Result<(), Error> {
-            self.send($method_name.clone())?;
+            self.send(&$method_name)?;
             Ok(self.$vec_name.push(($method_name, callback)))
         }
     };
@@ -36,6 +41,11 @@ macro_rules! create_find_methods {
             self.$vec_name.iter_mut().find(|i| i.0.subscription == $var_name.subscription)
         }
     };
+    (invocation: $method_name: ident, $var_name: ident, $vec_name: ident, $return_type: ident, $var_type: ident) => {
+        pub(crate) fn $method_name(&mut self, $var_name: &$var_type) -> Option<&mut ($return_type, InvocationCallBack)> {
+            self.$vec_name.iter_mut().find(|i| i.0.registration == $var_name.registration)
+        }
+    };
 }
 
 macro_rules! create_find_by_error_method {
@@ -52,6 +62,8 @@ macro_rules! create_find_by_error_method {
 
 pub struct Context {
     pub(crate) socket: Option<client::Socket>,
+    /// Request IDs of the session, shared by the client and every context it hands out.
+    pub(crate) ids: Arc<IdGenerator>,
     pub(crate) registrations: CallBackVecResult<Register, Registered>,
     pub(crate) unregistrations: CallBackVecResult<Unregister, Unregistered>,
     pub(crate) subscriptions: CallBackVecResult<Subscribe, Subscribed>,
@@ -59,16 +71,44 @@ pub struct Context {
     pub(crate) publications: CallBackVecResult<Publish, Published>,
     pub(crate) calls: CallBackVecResult<Call, WampResult>,
     pub(crate) events: CallBackVec<Subscribed, Event>,
-    pub(crate) invocations: CallBackVecResult<Registered, Invocation>,
+    pub(crate) invocations: Vec<(Registered, InvocationCallBack)>,
     pub(crate) errors: CallBackVecResult<Messages, WampError>,
     pub(crate) messages: Vec<Message>,
-    pub(crate) cancelations: CallBackVecResult<Cancel, Interrupt>
+    /// When each call sent with `options.timeout` fails with `wamp.error.timeout`, keyed by its request ID.
+    pub(crate) deadlines: Vec<(u64, Instant)>
+}
+
+/// A call sent to the dealer, its outcome still goes to the callback passed with it.
+pub struct CallHandle {
+    pub request_id: u64,
+    socket: Option<Socket>
+}
+
+impl CallHandle {
+    /// Sends the next chunk of a progressive invocation under this call's request ID,
+    /// the chunk without `options.progress` is the last one.
+    pub fn send(&self, mut call: Call) -> Result<(), Error> {
+        call.request_id = self.request_id;
+        match &self.socket {
+            Some(socket) => socket.lock().unwrap().send(&call),
+            None => Err(Error::Error("Call was queued on a context without a connection, it cannot be continued."))
+        }
+    }
+
+    /// Asks the dealer to cancel the call, the callback then gets `wamp.error.canceled` or the callee's error depending on `mode`.
+    pub fn cancel(&self, mode: CancelMode) -> Result<(), Error> {
+        match &self.socket {
+            Some(socket) => socket.lock().unwrap().send(&Cancel::new(self.request_id, mode)),
+            None => Err(Error::Error("Call was queued on a context without a connection, it cannot be canceled."))
+        }
+    }
 }
 
 impl Context {
-    pub fn new(socket: Option<Socket>) -> Self {
+    pub fn new(socket: Option<Socket>, ids: Arc<IdGenerator>) -> Self {
         Self {
             socket: socket,
+            ids,
             registrations: vec![],
             unregistrations: vec![],
             subscriptions: vec![],
@@ -79,13 +119,14 @@ impl Context {
             invocations: vec![],
             messages: vec![],
             errors: vec![],
-            cancelations: vec![]
+            deadlines: vec![]
         }
     }
 
-    pub fn new_with_capacity(socket: Option<Socket>, capacity: usize) -> Self {
+    pub fn new_with_capacity(socket: Option<Socket>, ids: Arc<IdGenerator>, capacity: usize) -> Self {
         Self {
             socket: socket,
+            ids,
             registrations: Vec::with_capacity(capacity), 
             unregistrations: Vec::with_capacity(capacity), 
             subscriptions: Vec::with_capacity(capacity), 
@@ -96,19 +137,20 @@ impl Context {
             invocations: Vec::with_capacity(capacity),
             messages: Vec::with_capacity(capacity),
             errors: Vec::with_capacity(capacity),
-            cancelations: Vec::with_capacity(capacity)
+            deadlines: vec![]
         }
     }
 
-    pub fn send<T: TryInto<Message>>(&mut self, message: T) -> Result<(), Error>
-    where 
-        Error: From<<T as TryInto<Message>>::Error>
-    {
+    /// Request IDs for this session, pass it to the `core::*!` macros.
+    pub fn ids(&self) -> &IdGenerator {
+        &self.ids
+    }
+
+    pub fn send<T: Serialize>(&mut self, message: T) -> Result<(), Error> {
         if let Some(socket) = &self.socket {
-            let socket = &mut *socket.lock().unwrap();
-           Ok(socket.send(message.try_into()?)?)
+            socket.lock().unwrap().send(&message)
         } else {
-            Ok(self.messages.push(message.try_into()?))
+            Ok(self.messages.push(Serializer::default().to_message(&message)?))
         }
     }
 
@@ -117,7 +159,6 @@ impl Context {
     create_find_by_error_method!(find_by_error_publish, Publish, publications, Published);
     create_find_by_error_method!(find_by_error_register, Register, registrations, Registered);
     create_find_by_error_method!(find_by_error_unregister, Unregister, unregistrations, Unregistered);
-    create_find_by_error_method!(find_by_error_cancel, Cancel, cancelations, Interrupt);
     create_find_by_error_method!(find_by_error_call, Call, calls, WampResult);
 
     create_push_methods!(register, registrations, Register, CallBackResult<Registered>);
@@ -125,10 +166,86 @@ impl Context {
     create_push_methods!(event, events, Subscribed, CallBack<Event>, no_send);
     create_push_methods!(unsubscribe, unsubscriptions, Unsubscribe, CallBackResult<Unsubscribed>);
     create_push_methods!(subscribe, subscriptions, Subscribe, CallBackResult<Subscribed>);
-    create_push_methods!(publish, publications, Publish, CallBackResult<Published>);
-    create_push_methods!(call, calls, Call, CallBackResult<WampResult>);
-    create_push_methods!(invocation, invocations, Registered, CallBackResult<Invocation>);
-    create_push_methods!(cancel, cancelations, Cancel, CallBackResult<Interrupt>);
+    create_push_methods!(invocation, invocations, Registered, InvocationCallBack, no_send);
+
+    /// Sends the publication, the callback is only kept when `options.acknowledge` asks the broker to answer.
+    pub fn publish(&mut self, publish: Publish, callback: CallBackResult<Published>) -> Result<(), Error> {
+        self.send(&publish)?;
+        if publish.acknowledge() {
+            self.publications.push((publish, callback));
+        }
+        Ok(())
+    }
+
+    /// Forgets a publication once the broker acknowledged or refused it.
+    pub(crate) fn finish_publish(&mut self, request_id: u64) -> Option<(Publish, CallBackResult<Published>)> {
+        let index = self.publications.iter().position(|(publish, _)| publish.request_id == request_id)?;
+        Some(self.publications.remove(index))
+    }
+
+    /// Sends the call, when it sets `options.timeout` the callback fails with `wamp.error.timeout` once it runs out.
+    pub fn call(&mut self, call: Call, callback: CallBackResult<WampResult>) -> Result<CallHandle, Error> {
+        self.send(&call)?;
+        if let Some(timeout) = call.timeout() {
+            self.deadlines.push((call.request_id, Instant::now() + timeout));
+        }
+        let handle = CallHandle {
+            request_id: call.request_id,
+            socket: self.socket.clone()
+        };
+        self.calls.push((call, callback));
+        Ok(handle)
+    }
+
+    /// Calls one of the router's meta procedures, built with `core::protocol::meta`, and hands the callback its result read as `T`.
+    /// A result that does not read as `T` fails with `wamp.error.protocol_violation`.
+    pub fn meta<T: 'static>(&mut self, meta: MetaCall<T>, mut callback: CallBackResult<T>) -> Result<CallHandle, Error> {
+        let mut call = meta.call.clone();
+        call.request_id = self.ids.next();
+        self.call(call, Box::new(move |context, result| {
+            let result = result.and_then(|result| meta.read(&result).ok_or_else(|| core::error!(result.request_id, WampErrorEvent::Call, "wamp.error.protocol_violation")));
+            callback(context, result)
+        }))
+    }
+
+    /// Sends a progressive result from inside an invocation handler, the final `Yield` still ends the invocation.
+    pub fn progress(&mut self, invocation: &Invocation, r#yield: Yield) -> Result<(), Error> {
+        if !invocation.receive_progress() {
+            return Err(Error::Error("Dealer did not ask for progressive results for this invocation."));
+        }
+        let mut r#yield = r#yield.progress();
+        r#yield.request_id = invocation.request_id;
+        self.send(r#yield)
+    }
+
+    /// Takes the calls whose deadline passed, they are answered with `wamp.error.timeout` by the caller of this.
+    pub(crate) fn expire_calls(&mut self, now: Instant) -> Vec<(Call, CallBackResult<WampResult>)> {
+        let expired: Vec<u64> = self.deadlines.iter()
+            .filter(|(_, deadline)| *deadline <= now)
+            .map(|(request_id, _)| *request_id)
+            .collect();
+        self.deadlines.retain(|(_, deadline)| *deadline > now);
+        let mut calls = vec![];
+        for request_id in expired {
+            if let Some(index) = self.calls.iter().position(|(call, _)| call.request_id == request_id) {
+                calls.push(self.calls.remove(index));
+            }
+        }
+        calls
+    }
+
+    /// Takes the calls and acknowledged publications still waiting on the router, a session that was lost never answers them.
+    pub(crate) fn abandon(&mut self) -> (CallBackVecResult<Call, WampResult>, CallBackVecResult<Publish, Published>) {
+        self.deadlines.clear();
+        (self.calls.drain(..).collect(), self.publications.drain(..).collect())
+    }
+
+    /// Forgets a call once it got its final answer.
+    pub(crate) fn finish_call(&mut self, request_id: u64) -> Option<(Call, CallBackResult<WampResult>)> {
+        self.deadlines.retain(|(deadline, _)| *deadline != request_id);
+        let index = self.calls.iter().position(|(call, _)| call.request_id == request_id)?;
+        Some(self.calls.remove(index))
+    }
 
     create_find_methods!(find_register, registered, registrations, Register, Registered);
     create_find_methods!(find_unregister, unregister, unregistrations, Unregister, Unregistered);
@@ -137,7 +254,7 @@ impl Context {
     create_find_methods!(find_subscribe, subscribe, subscriptions, Subscribe, Subscribed);
     create_find_methods!(find_publish, publish, publications, Publish, Published);
     create_find_methods!(find_call, call, calls, Call, WampResult);
-    create_find_methods!(find_invocation, invocation, invocations, Registered, Invocation);
+    create_find_methods!(invocation: find_invocation, invocation, invocations, Registered, Invocation);
 
     pub fn extend(&mut self, ctx: Context) {
         self.registrations.extend(ctx.registrations);
@@ -147,6 +264,7 @@ impl Context {
         self.subscriptions.extend(ctx.subscriptions);
         self.publications.extend(ctx.publications);
         self.calls.extend(ctx.calls);
+        self.deadlines.extend(ctx.deadlines);
         self.invocations.extend(ctx.invocations)
     }
 }
@@ -1,6 +1,16 @@
+/// Announced as `Hello.details.agent`.
+pub(crate) const AGENT: &str = concat!("wamp-rs/", env!("CARGO_PKG_VERSION"));
+
 pub mod context;
 mod request;
 pub use request::WampRequest;
 pub use tungstenite::client::IntoClientRequest;
+pub mod auth;
+pub mod rawsocket;
+pub use rawsocket::RawSocket;
+pub mod reconnect;
+pub use reconnect::ReconnectPolicy;
 pub mod client;
-pub use client::Client;
\ No newline at end of file
+pub use client::Client;
+pub mod async_client;
+pub use async_client::AsyncClient;
\ No newline at end of file
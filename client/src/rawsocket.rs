@@ -0,0 +1,94 @@
+use std::io::{self, ErrorKind, Read, Write};
+use core::{error::Error, protocol::rawsocket::{FrameHeader, FrameType, Handshake}};
+
+/// Blocking WAMP RawSocket over any byte stream, such as a `TcpStream` or a `UnixStream`.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-rawsocket
+pub struct RawSocket<S: Read + Write> {
+    stream: S,
+    handshake: Handshake,
+    /// Longest message the router accepts.
+    send_limit: u32,
+    /// Octets read but not yet returned, so a read that times out halfway through a frame can be resumed.
+    buffer: Vec<u8>
+}
+
+impl<S: Read + Write> RawSocket<S> {
+    /// Performs the handshake, failing when the router refuses it or answers with another serializer.
+    pub fn connect(mut stream: S, handshake: Handshake) -> Result<Self, Error> {
+        stream.write_all(&handshake.encode())?;
+        stream.flush()?;
+        let mut reply = [0; 4];
+        stream.read_exact(&mut reply)?;
+        let accepted = Handshake::decode(reply)?;
+        if accepted.serializer != handshake.serializer {
+            return Err(Error::Error("Router accepted the RawSocket handshake with another serializer."));
+        }
+        Ok(Self {
+            stream,
+            handshake,
+            send_limit: accepted.max_length,
+            buffer: vec![]
+        })
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    pub fn handshake(&self) -> Handshake {
+        self.handshake
+    }
+
+    pub fn send(&mut self, data: &[u8]) -> Result<(), Error> {
+        if data.len() > self.send_limit as usize {
+            return Err(Error::Error("Message exceeds the length the router accepts over RawSocket."));
+        }
+        self.write_frame(FrameType::Message, data)
+    }
+
+    pub fn ping(&mut self, payload: &[u8]) -> Result<(), Error> {
+        self.write_frame(FrameType::Ping, payload)
+    }
+
+    /// Reads the next frame, pings are answered with a pong and yield `None` just like pongs do.
+    ///
+    /// When the stream has a read timeout and it runs out, the error is returned and the next call picks up where this one stopped.
+    pub fn read(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        self.fill(4)?;
+        let header = FrameHeader::decode([self.buffer[0], self.buffer[1], self.buffer[2], self.buffer[3]])?;
+        if header.length > self.handshake.max_length {
+            return Err(Error::Error("Router sent a message longer than the RawSocket handshake allows."));
+        }
+        let length = 4 + header.length as usize;
+        self.fill(length)?;
+        let payload = self.buffer[4..length].to_vec();
+        self.buffer.drain(..length);
+        match header.frame_type {
+            FrameType::Message => Ok(Some(payload)),
+            FrameType::Ping => {
+                self.write_frame(FrameType::Pong, &payload)?;
+                Ok(None)
+            },
+            FrameType::Pong => Ok(None)
+        }
+    }
+
+    fn fill(&mut self, length: usize) -> Result<(), Error> {
+        let mut chunk = [0; 4096];
+        while self.buffer.len() < length {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(io::Error::from(ErrorKind::UnexpectedEof).into()),
+                Ok(read) => self.buffer.extend_from_slice(&chunk[..read]),
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into())
+            }
+        }
+        Ok(())
+    }
+
+    fn write_frame(&mut self, frame_type: FrameType, payload: &[u8]) -> Result<(), Error> {
+        self.stream.write_all(&FrameHeader::new(frame_type, payload.len())?.encode())?;
+        self.stream.write_all(payload)?;
+        Ok(self.stream.flush()?)
+    }
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+/// How `Client` retries after its transport drops, exponential backoff with jitter and an optional attempt cap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    /// Fraction of each delay, between 0 and 1, that is randomly taken off so clients do not retry in lockstep.
+    pub jitter: f64,
+    /// Attempts before giving up, None retries forever.
+    pub max_attempts: Option<u32>
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: Some(10)
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn max_attempts(mut self, max_attempts: Option<u32>) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Delay before the zero based `attempt`, None once the attempts are exhausted.
+    pub fn delay(&self, attempt: u32) -> Option<Duration> {
+        if self.max_attempts.is_some_and(|max_attempts| attempt >= max_attempts) {
+            return None;
+        }
+        let backoff = self.initial_delay.as_secs_f64() * self.multiplier.powi(attempt.min(i32::MAX as u32) as i32);
+        let backoff = backoff.min(self.max_delay.as_secs_f64());
+        Some(Duration::from_secs_f64(backoff * (1.0 - self.jitter * rand::random::<f64>())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::ReconnectPolicy;
+
+    #[test]
+    fn backoff() {
+        let policy = ReconnectPolicy::new()
+            .initial_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(1))
+            .jitter(0.5)
+            .max_attempts(Some(6));
+        for (attempt, ceiling) in [100, 200, 400, 800, 1000, 1000].into_iter().enumerate() {
+            let delay = policy.delay(attempt as u32).unwrap();
+            assert!(delay <= Duration::from_millis(ceiling) && delay >= Duration::from_millis(ceiling / 2), "{attempt}: {delay:?}");
+        }
+        assert_eq!(policy.delay(6), None);
+        assert_eq!(policy.jitter(0.0).max_attempts(None).delay(1000), Some(Duration::from_secs(1)));
+    }
+}
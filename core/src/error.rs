@@ -1,4 +1,7 @@
+use std::{fmt::{self, Display}, str::FromStr};
+use serde::{Serialize, Deserialize, de};
 use tungstenite::http::header::{ToStrError, InvalidHeaderValue};
+use crate::protocol::{rawsocket::HandshakeError, Uri};
 use crate::protocol::messages::{Abort, Authenticate, Call, Cancel, Challenge, WampError, WampResult, Event, Goodbye, Hello, Interrupt, Invocation, Publish, Published, Register, Registered, Subscribe, Subscribed, Unregister, Unregistered, Unsubscribe, Unsubscribed, Welcome, Yield, Messages};
 
 #[derive(Debug)]
@@ -7,7 +10,13 @@ pub enum Error {
     ToStrError(ToStrError),
     InvalidHeaderValue(InvalidHeaderValue),
     TungsteniteError(tungstenite::Error),
+    IoError(std::io::Error),
     SerdeJsonError(serde_json::Error),
+    MsgPackEncodeError(rmp_serde::encode::Error),
+    MsgPackDecodeError(rmp_serde::decode::Error),
+    CborEncodeError(ciborium::ser::Error<std::io::Error>),
+    CborDecodeError(ciborium::de::Error<std::io::Error>),
+    RawSocketHandshakeError(HandshakeError),
     InvalidMessageEnumMember,
     Error(&'static str),
     InvalidFrameReceived(Messages),
@@ -79,47 +88,201 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+impl From<rmp_serde::encode::Error> for Error {
+    fn from(value: rmp_serde::encode::Error) -> Self {
+        Self::MsgPackEncodeError(value)
+    }
+}
+
+impl From<rmp_serde::decode::Error> for Error {
+    fn from(value: rmp_serde::decode::Error) -> Self {
+        Self::MsgPackDecodeError(value)
+    }
+}
+
+impl From<ciborium::ser::Error<std::io::Error>> for Error {
+    fn from(value: ciborium::ser::Error<std::io::Error>) -> Self {
+        Self::CborEncodeError(value)
+    }
+}
+
+impl From<ciborium::de::Error<std::io::Error>> for Error {
+    fn from(value: ciborium::de::Error<std::io::Error>) -> Self {
+        Self::CborDecodeError(value)
+    }
+}
+
+impl From<HandshakeError> for Error {
+    fn from(value: HandshakeError) -> Self {
+        Self::RawSocketHandshakeError(value)
+    }
+}
+
 impl From<tungstenite::Error> for Error {
     fn from(value: tungstenite::Error) -> Self {
         Self::TungsteniteError(value)
     }
 }
 
-#[derive(Debug)]
-pub enum WampErrorUri {
-    NotAuthorized,
-    ProcedureAlreadyExists,
-    NoSuchRealm,
-    ProtocolViolation,
-    NoSuchSubscription,
-    NoSuchRegistration,
-    InvalidUri,
-    NoSuchProcedure,
-    InvalidArgument,
-    Canceled,
-    PayloadSizeExceeded,
-    FeatureNotSupported,
-    Timeout,
-    Unavailable,
-    NoAvailableCallee,
-    DiscloseMeNotAllowed,
-    OptionDisallowedDiscloseMe,
-    NoMatchingAuthMethod,
-    NoSuchRole,
-    NoSuchPrincipal,
-    AuthenticationDenied,
-    AuthenticationFailed,
-    AuthenticationRequired,
-    AuthorizationDenied,
-    AuthorizationFailed,
-    AuthorizationRequired,
-    NetworkFailure,
-    OptionNotAllowed
-}
-
-pub enum CloseUri {
-    SystemShutdown,
-    CloseRealm,
-    GoodbyeAndOut,
-    Killed
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::IoError(value)
+    }
+}
+
+/// A URI that is none of the predefined ones of the enum holding it in `Other`, only that enum's `From<Uri>` and `FromStr` make one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomUri(Uri);
+
+impl CustomUri {
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl From<CustomUri> for Uri {
+    fn from(uri: CustomUri) -> Self {
+        uri.0
+    }
+}
+
+/// Declares an enum of predefined URIs from a single table, read both ways, with every other URI kept in `Other`.
+/// Display and serde go through the URI, as the enums go over the wire as plain strings.
+macro_rules! uri_enum {
+    ($(#[$meta:meta])* $typ:ident { $($variant:ident => $uri:literal),* $(,)? }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub enum $typ {
+            $($variant,)*
+            Other(CustomUri)
+        }
+
+        impl $typ {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($typ::$variant => $uri,)*
+                    $typ::Other(uri) => uri.as_str()
+                }
+            }
+        }
+
+        impl From<Uri> for $typ {
+            fn from(uri: Uri) -> Self {
+                match uri.as_str() {
+                    $($uri => $typ::$variant,)*
+                    _ => $typ::Other(CustomUri(uri))
+                }
+            }
+        }
+
+        impl FromStr for $typ {
+            type Err = Error;
+
+            fn from_str(uri: &str) -> Result<Self, Self::Err> {
+                Uri::loose(uri).map(Self::from)
+            }
+        }
+
+        impl From<$typ> for Uri {
+            fn from(uri: $typ) -> Self {
+                match uri {
+                    $($typ::$variant => crate::uri!($uri),)*
+                    $typ::Other(uri) => uri.into()
+                }
+            }
+        }
+
+        impl Display for $typ {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $typ {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $typ {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?.parse().map_err(|_| de::Error::custom("URI must follow the loose URI rules"))
+            }
+        }
+    };
+}
+
+uri_enum! {
+    /// Error URIs predefined by the spec, anything else an application uses is `Other`.
+    /// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-predefined-uris
+    WampErrorUri {
+        NotAuthorized => "wamp.error.not_authorized",
+        ProcedureAlreadyExists => "wamp.error.procedure_already_exists",
+        ProcedureExistsWithDifferentInvocationPolicy => "wamp.error.procedure_exists_with_different_invocation_policy",
+        NoSuchRealm => "wamp.error.no_such_realm",
+        ProtocolViolation => "wamp.error.protocol_violation",
+        NoSuchSubscription => "wamp.error.no_such_subscription",
+        NoSuchRegistration => "wamp.error.no_such_registration",
+        NoSuchSession => "wamp.error.no_such_session",
+        InvalidUri => "wamp.error.invalid_uri",
+        NoSuchProcedure => "wamp.error.no_such_procedure",
+        InvalidArgument => "wamp.error.invalid_argument",
+        Canceled => "wamp.error.canceled",
+        PayloadSizeExceeded => "wamp.error.payload_size_exceeded",
+        FeatureNotSupported => "wamp.error.feature_not_supported",
+        Timeout => "wamp.error.timeout",
+        Unavailable => "wamp.error.unavailable",
+        NoAvailableCallee => "wamp.error.no_available_callee",
+        DiscloseMeNotAllowed => "wamp.error.disclose_me.not_allowed",
+        OptionDisallowedDiscloseMe => "wamp.error.option_disallowed.disclose_me",
+        NoMatchingAuthMethod => "wamp.error.no_matching_auth_method",
+        NoSuchRole => "wamp.error.no_such_role",
+        NoSuchPrincipal => "wamp.error.no_such_principal",
+        AuthenticationDenied => "wamp.error.authentication_denied",
+        AuthenticationFailed => "wamp.error.authentication_failed",
+        AuthenticationRequired => "wamp.error.authentication_required",
+        AuthorizationDenied => "wamp.error.authorization_denied",
+        AuthorizationFailed => "wamp.error.authorization_failed",
+        AuthorizationRequired => "wamp.error.authorization_required",
+        NetworkFailure => "wamp.error.network_failure",
+        OptionNotAllowed => "wamp.error.option_not_allowed"
+    }
+}
+
+uri_enum! {
+    /// Reasons predefined by the spec for closing a session, anything else is `Other`.
+    CloseUri {
+        SystemShutdown => "wamp.close.system_shutdown",
+        CloseRealm => "wamp.close.close_realm",
+        GoodbyeAndOut => "wamp.close.goodbye_and_out",
+        Killed => "wamp.close.killed"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_str, to_string};
+    use crate::protocol::Uri;
+    use super::{CloseUri, WampErrorUri};
+
+    #[test]
+    fn uris() {
+        assert_eq!("wamp.error.no_such_procedure".parse::<WampErrorUri>().unwrap(), WampErrorUri::NoSuchProcedure);
+        assert_eq!("wamp.error.disclose_me.not_allowed".parse::<WampErrorUri>().unwrap(), WampErrorUri::DiscloseMeNotAllowed);
+        let other = "com.myapp.error.object_write_protected".parse::<WampErrorUri>().unwrap();
+        assert!(matches!(&other, WampErrorUri::Other(uri) if uri.as_str() == "com.myapp.error.object_write_protected"));
+        assert_eq!(other.to_string(), "com.myapp.error.object_write_protected");
+        assert_eq!(Uri::from(other), "com.myapp.error.object_write_protected");
+        assert!("com.my app.error".parse::<WampErrorUri>().is_err());
+        assert_eq!(Uri::from(WampErrorUri::Timeout), "wamp.error.timeout");
+
+        // A predefined URI always comes back as its own variant, never as `Other`.
+        assert_eq!(WampErrorUri::from(crate::uri!("wamp.error.timeout")), WampErrorUri::Timeout);
+        assert_eq!(CloseUri::from(Uri::from(CloseUri::CloseRealm)), CloseUri::CloseRealm);
+
+        assert_eq!(to_string(&CloseUri::GoodbyeAndOut).unwrap(), r#""wamp.close.goodbye_and_out""#);
+        assert_eq!(from_str::<CloseUri>(r#""wamp.close.killed""#).unwrap(), CloseUri::Killed);
+        assert_eq!(from_str::<CloseUri>(r#""com.myapp.close.idle""#).unwrap().as_str(), "com.myapp.close.idle");
+        assert!("".parse::<CloseUri>().is_err());
+    }
 }
@@ -1,17 +1,39 @@
-use std::sync::RwLock;
+use std::sync::{RwLock, atomic::{AtomicU64, Ordering}};
 
 use lazy_static::lazy_static;
+use rand::Rng;
 
 lazy_static! {
-    static ref NUMBER: RwLock<u64> = RwLock::new(0);
     static ref TOPICS: RwLock<Vec<String>> = RwLock::new(vec![]);
 }
 
-pub fn increment() -> u64 {
-    let previous = *NUMBER.read().unwrap();
-    let mut num = NUMBER.write().unwrap();
-    *num = previous + 1;
-    *num
+/// Largest ID allowed, IDs go from 1 to 2^53 so they fit in a double for JSON peers.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-ids
+pub const MAX_ID: u64 = 1 << 53;
+
+/// Sequential IDs for the session scope, such as the request IDs of one session, wrapping back to 1 after 2^53.
+#[derive(Debug, Default)]
+pub struct IdGenerator(AtomicU64);
+
+impl IdGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn next(&self) -> u64 {
+        let previous = self.0.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |id| Some(id % MAX_ID + 1)).unwrap();
+        previous % MAX_ID + 1
+    }
+}
+
+/// IDs for the global scope, such as session and publication IDs, drawn at random from 1 to 2^53.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RandomIdGenerator;
+
+impl RandomIdGenerator {
+    pub fn next(&self) -> u64 {
+        rand::thread_rng().gen_range(1..=MAX_ID)
+    }
 }
 
 pub fn subscribe<T: ToString>(topic: T) {
@@ -26,4 +48,30 @@ pub fn unsubscribe<T: ToString>(topic: &T) {
 
 pub fn subscription_contains<T: ToString>(topic: &T) -> bool {
     TOPICS.read().unwrap().contains(&topic.to_string())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::{Arc, atomic::AtomicU64}, thread};
+    use super::{IdGenerator, MAX_ID, RandomIdGenerator};
+
+    #[test]
+    fn session_scope() {
+        let ids = IdGenerator::new();
+        assert_eq!((ids.next(), ids.next()), (1, 2));
+        let ids = IdGenerator(AtomicU64::new(MAX_ID - 1));
+        assert_eq!((ids.next(), ids.next()), (MAX_ID, 1));
+
+        let ids = Arc::new(IdGenerator::new());
+        let threads: Vec<_> = (0..4).map(|_| {
+            let ids = Arc::clone(&ids);
+            thread::spawn(move || (0..1000).map(|_| ids.next()).collect::<Vec<u64>>())
+        }).collect();
+        let drawn: HashSet<u64> = threads.into_iter().flat_map(|thread| thread.join().unwrap()).collect();
+        assert_eq!(drawn.len(), 4000);
+    }
+
+    #[test]
+    fn global_scope() {
+        assert!((0..1000).map(|_| RandomIdGenerator.next()).all(|id| (1..=MAX_ID).contains(&id)));
+    }
+}
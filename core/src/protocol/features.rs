@@ -0,0 +1,78 @@
+use std::{fmt::Display, str::FromStr};
+
+/// Advanced profile features a peer announces under `details.roles.<role>.features`.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-feature-announcement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    ProgressiveCallResults,
+    ProgressiveCallInvocations,
+    CallTimeout,
+    CallCanceling,
+    CallerIdentification,
+    PatternBasedRegistration,
+    SharedRegistration,
+    PublisherIdentification,
+    PublisherExclusion,
+    SubscriberBlackwhiteListing,
+    PatternBasedSubscription,
+    EventHistory,
+    EventRetention,
+    SessionMetaApi,
+    RegistrationMetaApi,
+    SubscriptionMetaApi
+}
+
+impl Feature {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Feature::ProgressiveCallResults => "progressive_call_results",
+            Feature::ProgressiveCallInvocations => "progressive_call_invocations",
+            Feature::CallTimeout => "call_timeout",
+            Feature::CallCanceling => "call_canceling",
+            Feature::CallerIdentification => "caller_identification",
+            Feature::PatternBasedRegistration => "pattern_based_registration",
+            Feature::SharedRegistration => "shared_registration",
+            Feature::PublisherIdentification => "publisher_identification",
+            Feature::PublisherExclusion => "publisher_exclusion",
+            Feature::SubscriberBlackwhiteListing => "subscriber_blackwhite_listing",
+            Feature::PatternBasedSubscription => "pattern_based_subscription",
+            Feature::EventHistory => "event_history",
+            Feature::EventRetention => "event_retention",
+            Feature::SessionMetaApi => "session_meta_api",
+            Feature::RegistrationMetaApi => "registration_meta_api",
+            Feature::SubscriptionMetaApi => "subscription_meta_api"
+        }
+    }
+}
+
+impl Display for Feature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Feature {
+    type Err = ();
+
+    fn from_str(feature: &str) -> Result<Self, Self::Err> {
+        match feature {
+            "progressive_call_results" => Ok(Feature::ProgressiveCallResults),
+            "progressive_call_invocations" => Ok(Feature::ProgressiveCallInvocations),
+            "call_timeout" => Ok(Feature::CallTimeout),
+            "call_canceling" => Ok(Feature::CallCanceling),
+            "caller_identification" => Ok(Feature::CallerIdentification),
+            "pattern_based_registration" => Ok(Feature::PatternBasedRegistration),
+            "shared_registration" => Ok(Feature::SharedRegistration),
+            "publisher_identification" => Ok(Feature::PublisherIdentification),
+            "publisher_exclusion" => Ok(Feature::PublisherExclusion),
+            "subscriber_blackwhite_listing" => Ok(Feature::SubscriberBlackwhiteListing),
+            "pattern_based_subscription" => Ok(Feature::PatternBasedSubscription),
+            "event_history" => Ok(Feature::EventHistory),
+            "event_retention" => Ok(Feature::EventRetention),
+            "session_meta_api" => Ok(Feature::SessionMetaApi),
+            "registration_meta_api" => Ok(Feature::RegistrationMetaApi),
+            "subscription_meta_api" => Ok(Feature::SubscriptionMetaApi),
+            _ => Err(())
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use super::{regex::uri_rules::WampUriRule, uri::{PATTERN, PREFIX, URI}};
+
+/// How a subscription's topic or a registration's procedure is compared against concrete URIs, sent as `options.match`.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-pattern-based-subscription
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MatchPolicy {
+    #[default]
+    Exact,
+    /// Matches every URI starting with the pattern.
+    Prefix,
+    /// Matches every URI with as many components as the pattern, empty components match any component.
+    Wildcard
+}
+
+impl MatchPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchPolicy::Exact => "exact",
+            MatchPolicy::Prefix => "prefix",
+            MatchPolicy::Wildcard => "wildcard"
+        }
+    }
+
+    pub fn from_match(r#match: &str) -> Option<Self> {
+        match r#match {
+            "exact" => Some(MatchPolicy::Exact),
+            "prefix" => Some(MatchPolicy::Prefix),
+            "wildcard" => Some(MatchPolicy::Wildcard),
+            _ => None
+        }
+    }
+
+    /// Whether `pattern` is allowed for the policy, by the loose URI rules.
+    pub fn validate(&self, pattern: &str) -> bool {
+        let rule: &WampUriRule = match self {
+            MatchPolicy::Exact => &URI,
+            MatchPolicy::Prefix => &PREFIX,
+            MatchPolicy::Wildcard => &PATTERN
+        };
+        rule.loose.is_match(pattern)
+    }
+
+    /// Whether the concrete `uri` matches `pattern`.
+    pub fn matches(&self, pattern: &str, uri: &str) -> bool {
+        match self {
+            MatchPolicy::Exact => pattern == uri,
+            MatchPolicy::Prefix => uri.starts_with(pattern),
+            MatchPolicy::Wildcard => {
+                let (pattern, uri): (Vec<&str>, Vec<&str>) = (pattern.split('.').collect(), uri.split('.').collect());
+                pattern.len() == uri.len() && pattern.iter().zip(uri).all(|(pattern, component)| pattern.is_empty() || *pattern == component)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MatchPolicy;
+
+    #[test]
+    fn validate() {
+        assert!(MatchPolicy::Exact.validate("com.myapp.topic"));
+        assert!(!MatchPolicy::Exact.validate("com.myapp."));
+        assert!(MatchPolicy::Prefix.validate("com.myapp."));
+        assert!(!MatchPolicy::Prefix.validate("com..topic"));
+        assert!(MatchPolicy::Wildcard.validate("com..topic"));
+        assert!(!MatchPolicy::Wildcard.validate("com.my app..topic"));
+        assert_eq!(MatchPolicy::from_match("wildcard"), Some(MatchPolicy::Wildcard));
+        assert_eq!(MatchPolicy::from_match("regex"), None);
+    }
+
+    #[test]
+    fn matches() {
+        assert!(MatchPolicy::Prefix.matches("com.myapp.topic.emerg", "com.myapp.topic.emergency.11"));
+        assert!(!MatchPolicy::Prefix.matches("com.myapp.topic.emerg", "com.myapp.topic"));
+        assert!(MatchPolicy::Wildcard.matches("com.myapp..update", "com.myapp.user.update"));
+        assert!(!MatchPolicy::Wildcard.matches("com.myapp..update", "com.myapp.user.profile.update"));
+        assert!(!MatchPolicy::Exact.matches("com.myapp", "com.myapp.topic"));
+    }
+}
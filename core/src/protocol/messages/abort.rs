@@ -1,7 +1,7 @@
 use std::{marker::PhantomData, fmt::Display};
 use serde::{Serialize, de::{self, Visitor}, Deserialize};
 use serde_json::Value;
-use crate::protocol::roles::Roles;
+use crate::{error::WampErrorUri, protocol::roles::Roles};
 use super::{WampMessage, helpers, MessageDirection};
 
 #[macro_export]
@@ -25,6 +25,13 @@ pub struct Abort {
     pub reason: String
 }
 
+impl Abort {
+    /// The reason as an error URI, None when it is not a valid URI.
+    pub fn reason_uri(&self) -> Option<WampErrorUri> {
+        self.reason.parse().ok()
+    }
+}
+
 impl WampMessage<Abort> for Abort {
     const ID: u64 = 3;
     type MESSAGE = Abort;
@@ -89,14 +96,14 @@ impl<'de> Deserialize<'de> for Abort {
             {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message ID must be type u8.")?;
                 helpers::validate_id::<Abort, A, _>(&message_id, "Abort")?;
-                let details: Value = helpers::deser_seq_element(&mut seq, "Details must be a JSON value.")?;
+                let details: Value = helpers::deser_value_element(&mut seq, "Details must be a JSON value.")?;
                 let reason: String = helpers::deser_seq_element(&mut seq, "Reason must be a String.")?;
                 helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
                 Ok(Abort { reason, details })
             }
         }
         
-        deserializer.deserialize_struct("Abort", &["reason", "details"], AbortVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(AbortVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -113,6 +120,7 @@ mod tests {
         let a: Abort = from_str(data).unwrap();
         println!("{:#?}", a);
         assert_eq!(a.reason, "wamp.error.no_such_realm");
+        assert_eq!(a.reason_uri(), Some(WampErrorUri::NoSuchRealm));
     }
 
     #[test]
@@ -125,4 +133,15 @@ mod tests {
         let an = to_string(&a).unwrap();
         assert_eq!(data, an);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Abort {
+            details: json!({"message":"The realm does not exist."}),
+            reason: "wamp.error.no_such_realm".to_string()
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Abort, _>(&d1[..]).unwrap());
+    }
+}
@@ -13,7 +13,7 @@ pub struct Authenticate {
 #[macro_export]
 macro_rules! authenticate {
     ($signature:expr) => {
-        core::authenticate!{$signature, serde_json::json!({})}
+        $crate::authenticate!{$signature, serde_json::json!({})}
     };
 
     ($signature:expr, $details:expr) => {
@@ -93,12 +93,12 @@ impl<'de> Deserialize<'de> for Authenticate {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message ID must be present and type u8.")?;
                 helpers::validate_id::<Authenticate, A, _>(&message_id, "Authenticate")?;
                 let signature: String = helpers::deser_seq_element(&mut seq, "Signature must be type String.")?;
-                let details: Value = helpers::deser_seq_element(&mut seq, "Details must be present and object like.")?;
+                let details: Value = helpers::deser_value_element(&mut seq, "Details must be present and object like.")?;
                 helpers::deser_value_is_object::<A, _>(&details, "Value must be object like")?;
                 Ok(Authenticate { signature, details })
             }
         }
 
-        deserializer.deserialize_struct("Authenticate", &["signature", "details"], AuthenticateVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(AuthenticateVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
\ No newline at end of file
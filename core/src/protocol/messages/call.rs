@@ -1,34 +1,40 @@
 use std::fmt::Formatter;
 use std::marker::PhantomData;
+use std::time::Duration;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde::de::{SeqAccess, Visitor};
 use serde_json::{json, Value};
-use crate::protocol::roles::Roles;
+use crate::protocol::{roles::Roles, Uri};
 use super::{helpers, WampMessage, MessageDirection};
 
+/// Builds a [`Call`](crate::protocol::messages::Call) with the next request ID of `$ids`, the procedure is a [`Uri`](crate::protocol::Uri) or a string literal.
+///
+/// # Panics
+///
+/// When the procedure is a literal breaking the loose URI rules, see [`IntoUri`](crate::protocol::IntoUri).
 #[macro_export]
 macro_rules! call {
-    ($procedure:expr) => {
-        core::call!{$procedure, serde_json::json!({}), serde_json::Value::Null, serde_json::Value::Null}
+    ($ids:expr, $procedure:expr) => {
+        $crate::call!{$ids, $procedure, serde_json::json!({}), serde_json::Value::Null, serde_json::Value::Null}
     };
 
-    ($procedure:expr, $options:expr) => {
-        core::call!{$procedure, $options, serde_json::Value::Null, serde_json::Value::Null}
+    ($ids:expr, $procedure:expr, $options:expr) => {
+        $crate::call!{$ids, $procedure, $options, serde_json::Value::Null, serde_json::Value::Null}
     };
 
-    ($procedure:expr, $options:expr, args: $args:expr) => {
-        core::call!{$procedure, $options, $args, serde_json::Value::Null}
+    ($ids:expr, $procedure:expr, $options:expr, args: $args:expr) => {
+        $crate::call!{$ids, $procedure, $options, $args, serde_json::Value::Null}
     };
 
-    ($procedure:expr, $options:expr, kwargs: $kwargs:expr) => {
-        core::call!{$procedure, $options, serde_json::Value::Null, $kwargs}
+    ($ids:expr, $procedure:expr, $options:expr, kwargs: $kwargs:expr) => {
+        $crate::call!{$ids, $procedure, $options, serde_json::Value::Null, $kwargs}
     };
 
-    ($procedure:expr, $options:expr, $args:expr, $kwargs:expr) => {{
+    ($ids:expr, $procedure:expr, $options:expr, $args:expr, $kwargs:expr) => {{
        $crate::protocol::messages::Call {
-            request_id: $crate::protocol::increment(),
+            request_id: $ids.next(),
             options: $options,
-            procedure: $procedure.to_string(),
+            procedure: $crate::protocol::IntoUri::into_loose($procedure),
             args: $args,
             kwargs: $kwargs
         }
@@ -37,9 +43,10 @@ macro_rules! call {
 
 #[test]
 fn p() {
-    let ca = call!{ "".to_string() };
+    let ids = crate::protocol::IdGenerator::new();
+    let ca = call!{ &ids, Uri::loose("com.myapp.ping".to_string()).unwrap() };
     println!("{:#?}", json!(ca));
-    let ca = call!{ "".to_string() };
+    let ca = call!{ &ids, Uri::loose("com.myapp.ping".to_string()).unwrap() };
     println!("{:#?}", json!(ca))
 }
 
@@ -47,11 +54,95 @@ fn p() {
 pub struct Call {
     pub request_id: u64,
     pub options: Value,
-    pub procedure: String,
+    pub procedure: Uri,
     pub args: Value,
     pub kwargs: Value
 }
 
+impl Call {
+    /// Time the caller is willing to wait, from `options.timeout` in milliseconds where 0 means no timeout.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.options.get("timeout")
+            .and_then(Value::as_u64)
+            .filter(|timeout| *timeout > 0)
+            .map(Duration::from_millis)
+    }
+
+    /// A chunk of a progressive invocation, more calls with the same request ID follow.
+    pub fn is_progress(&self) -> bool {
+        self.options.get("progress") == Some(&json!(true))
+    }
+
+    /// Whether the caller asked for progressive results with `options.receive_progress`.
+    pub fn receive_progress(&self) -> bool {
+        self.options.get("receive_progress") == Some(&json!(true))
+    }
+
+    /// Whether the caller asked the dealer to tell the callee who called, with `options.disclose_me`.
+    pub fn disclose_me(&self) -> bool {
+        self.options.get("disclose_me") == Some(&json!(true))
+    }
+}
+
+/// Typed `Call.options`.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-call-timeouts
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CallOptions {
+    /// Sent as `timeout` in milliseconds, the dealer cancels the call once it runs out and so does the client.
+    pub timeout: Option<Duration>,
+    /// Asks for the callee's progressive results, each one arrives as a `WampResult` with `details.progress`.
+    pub receive_progress: bool,
+    /// Sends the call as the first chunk of a progressive invocation, the chunk without it is the last one.
+    pub progress: bool,
+    /// Asks the dealer to disclose the caller's identity to the callee.
+    pub disclose_me: bool
+}
+
+impl CallOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn receive_progress(mut self) -> Self {
+        self.receive_progress = true;
+        self
+    }
+
+    pub fn progress(mut self) -> Self {
+        self.progress = true;
+        self
+    }
+
+    pub fn disclose_me(mut self) -> Self {
+        self.disclose_me = true;
+        self
+    }
+}
+
+impl From<CallOptions> for Value {
+    fn from(options: CallOptions) -> Self {
+        let mut value = json!({});
+        if let Some(timeout) = options.timeout {
+            value["timeout"] = json!(timeout.as_millis() as u64);
+        }
+        if options.receive_progress {
+            value["receive_progress"] = json!(true);
+        }
+        if options.progress {
+            value["progress"] = json!(true);
+        }
+        if options.disclose_me {
+            value["disclose_me"] = json!(true);
+        }
+        value
+    }
+}
+
 impl WampMessage<Call> for Call {
     const ID: u64 = 48;
 
@@ -111,7 +202,7 @@ impl Serialize for Call {
 
 impl<'de> Deserialize<'de> for Call {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
-        struct CallVisitor(PhantomData<u8>, PhantomData<u64>, PhantomData<Value>, PhantomData<String>, PhantomData<Value>, PhantomData<Value>);
+        struct CallVisitor(PhantomData<u8>, PhantomData<u64>, PhantomData<Value>, PhantomData<Uri>, PhantomData<Value>, PhantomData<Value>);
         
         impl<'vi> Visitor<'vi> for CallVisitor {
             type Value = Call;
@@ -123,9 +214,9 @@ impl<'de> Deserialize<'de> for Call {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message ID must be present and type u8.")?;
                 helpers::validate_id::<Call, A, _>(&message_id, "Call")?;
                 let request_id: u64 = helpers::deser_seq_element(&mut seq, "Request ID must be present and type u64.")?;
-                let options: Value = helpers::deser_seq_element(&mut seq, "Options must be present and object like.")?;
+                let options: Value = helpers::deser_value_element(&mut seq, "Options must be present and object like.")?;
                 helpers::deser_value_is_object::<A, _>(&options, "Options must be object like.")?;
-                let procedure: String = helpers::deser_seq_element(&mut seq, "Procedure must be present and object like.")?;
+                let procedure = helpers::deser_uri_element(&mut seq, Uri::loose, "Procedure must be present and a valid URI.")?;
                 let args: Value = helpers::deser_args_kwargs_element(&mut seq, "Args must be array like or null.")?;
                 let kwargs: Value = helpers::deser_args_kwargs_element(&mut seq, "Kwargs must be object like or null.")?;
                 Ok(Call {
@@ -138,7 +229,7 @@ impl<'de> Deserialize<'de> for Call {
             }
         }
 
-        deserializer.deserialize_struct("Call", &["request_id", "message_id", "options", "procedure", "args", "kwargs"], CallVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(CallVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -152,7 +243,7 @@ mod tests {
         let a = Call {
             request_id: 7814135,
             options: json!({}),
-            procedure: "com.myapp.user.new".to_string(),
+            procedure: crate::protocol::Uri::loose("com.myapp.user.new").unwrap(),
             args: json!(["johnny"]),
             kwargs: json!({"firstname":"John","surname":"Doe"})
         };
@@ -160,22 +251,62 @@ mod tests {
         let an = to_string(&a).unwrap();
         assert_eq!(data, an);
         let data: Call = from_str(data).unwrap();
-        assert_eq!(a, data)
+        assert_eq!(a, data);
+        assert!(from_str::<Call>(r#"[48,7814135,{},"com.myapp..new"]"#).is_err())
     }
 
     #[test]
     fn r#macro() {
         use serde_json::json;
         use super::Call;
-    
-        let call = call!("some.procedure".to_string());
+
+        let ids = crate::protocol::IdGenerator::new();
+        let call = call!(&ids, crate::protocol::Uri::loose("some.procedure".to_string()).unwrap());
         let call2 = Call {
             request_id: 1,
             options: json!({}),
-            procedure: "some.procedure".to_string(),
+            procedure: crate::protocol::Uri::loose("some.procedure").unwrap(),
             args: serde_json::Value::Null,
             kwargs: serde_json::Value::Null
         };
         assert_eq!(call, call2)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn options() {
+        use std::time::Duration;
+        use super::CallOptions;
+
+        let ids = crate::protocol::IdGenerator::new();
+        let call = call!(&ids, "com.myapp.slow", serde_json::Value::from(CallOptions::new().timeout(Duration::from_millis(1500))));
+        assert_eq!(call.options, serde_json::json!({"timeout": 1500}));
+        assert_eq!(call.timeout(), Some(Duration::from_millis(1500)));
+        assert_eq!(call!(&ids, "com.myapp.slow", serde_json::json!({"timeout": 0})).timeout(), None);
+        assert!(!call.receive_progress());
+        let call = call!(&ids, "com.myapp.query", serde_json::Value::from(CallOptions::new().receive_progress()));
+        assert_eq!(call.options, serde_json::json!({"receive_progress": true}));
+        assert!(call.receive_progress());
+        assert!(!call.is_progress());
+        assert!(call!(&ids, "com.myapp.upload", serde_json::Value::from(CallOptions::new().progress())).is_progress());
+        let call = call!(&ids, "com.myapp.add", serde_json::Value::from(CallOptions::new().disclose_me()));
+        assert_eq!(call.options, serde_json::json!({"disclose_me": true}));
+        assert!(call.disclose_me());
+        assert_eq!(call!(&ids, crate::uri!("com.myapp.add")).procedure, "com.myapp.add");
+    }
+
+    #[test]
+    fn cbor() {
+        let m1 = super::Call {
+            request_id: 7814135,
+            options: serde_json::json!({}),
+            procedure: crate::protocol::Uri::loose("com.myapp.user.new").unwrap(),
+            args: serde_json::json!(["\u{0}AQL/", "johnny"]),
+            kwargs: serde_json::json!({"firstname":"John","surname":"Doe"})
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        let raw: ciborium::Value = ciborium::from_reader(&d1[..]).unwrap();
+        assert_eq!(raw.as_array().unwrap()[4].as_array().unwrap()[0], ciborium::Value::Bytes(vec![1, 2, 255]));
+        assert_eq!(m1, ciborium::from_reader::<super::Call, _>(&d1[..]).unwrap());
+    }
+}
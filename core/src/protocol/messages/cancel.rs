@@ -1,6 +1,6 @@
 use std::marker::PhantomData;
 use serde::{Serialize, de::{self, Visitor}, Deserialize};
-use serde_json::Value;
+use serde_json::{Value, json};
 use crate::protocol::roles::Roles;
 use super::{WampMessage, helpers, MessageDirection};
 
@@ -10,14 +10,64 @@ pub struct Cancel {
     pub options: Value
 }
 
+/// How the dealer treats the callee when a call is canceled, carried as `options.mode` of `Cancel` and `Interrupt`.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-call-canceling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CancelMode {
+    /// The caller gets `wamp.error.canceled` right away, the callee is left running and its result is dropped.
+    Skip,
+    /// The callee is interrupted and the caller gets whatever error the callee answers with.
+    #[default]
+    Kill,
+    /// The callee is interrupted and the caller gets `wamp.error.canceled` right away.
+    KillNoWait
+}
+
+impl CancelMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CancelMode::Skip => "skip",
+            CancelMode::Kill => "kill",
+            CancelMode::KillNoWait => "killnowait"
+        }
+    }
+
+    pub fn from_mode(mode: &str) -> Option<Self> {
+        match mode {
+            "skip" => Some(CancelMode::Skip),
+            "kill" => Some(CancelMode::Kill),
+            "killnowait" => Some(CancelMode::KillNoWait),
+            _ => None
+        }
+    }
+}
+
+impl Cancel {
+    /// Cancels the call sent with `request_id`.
+    pub fn new(request_id: u64, mode: CancelMode) -> Self {
+        Self {
+            request_id,
+            options: json!({"mode": mode.as_str()})
+        }
+    }
+
+    /// Requested mode, `kill` when the caller left it out.
+    pub fn mode(&self) -> Option<CancelMode> {
+        match self.options.get("mode") {
+            Some(mode) => mode.as_str().and_then(CancelMode::from_mode),
+            None => Some(CancelMode::default())
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! cancel {
-    () => {
-        cancel!(serde_json::json!({}))
+    ($request_id:expr) => {
+        $crate::cancel!($request_id, serde_json::json!({}))
     };
-    ($options:expr) => {
-        Cancel {
-            request_id: $crate::protocol::increment(),
+    ($request_id:expr, $options:expr) => {
+        $crate::protocol::messages::Cancel {
+            request_id: $request_id,
             options: $options
         }
     };
@@ -87,13 +137,13 @@ impl<'de> Deserialize<'de> for Cancel {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message ID must be type u64.")?;
                 helpers::validate_id::<Cancel, A, _>(&message_id, "Cancel")?;
                 let request_id: u64 = helpers::deser_seq_element(&mut seq, "Request ID must be a u64.")?;
-                let options: Value = helpers::deser_seq_element(&mut seq, "Options must be a JSON value.")?;
+                let options: Value = helpers::deser_value_element(&mut seq, "Options must be a JSON value.")?;
                 helpers::deser_value_is_object::<A, _>(&options, "Options must be object like.")?;
                 Ok(Cancel { request_id, options })
             }
         }
         
-        deserializer.deserialize_struct("Cancel", &["request_id", "options"], CancelVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(CancelVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -113,4 +163,24 @@ mod tests {
         assert_eq!(c1, from_str(d1).unwrap());
         assert_eq!(d1, to_string(&c1).unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Cancel {
+            request_id: 9129132,
+            options: json!({})
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Cancel, _>(&d1[..]).unwrap());
+    }
+
+    #[test]
+    fn mode() {
+        let cancel = Cancel::new(9129132, CancelMode::KillNoWait);
+        assert_eq!(r#"[49,9129132,{"mode":"killnowait"}]"#, to_string(&cancel).unwrap());
+        assert_eq!(cancel.mode(), Some(CancelMode::KillNoWait));
+        assert_eq!(crate::cancel!(9129132).mode(), Some(CancelMode::Kill));
+        assert_eq!(crate::cancel!(9129132, json!({"mode": "abort"})).mode(), None);
+    }
+}
@@ -86,13 +86,13 @@ impl<'de> Deserialize<'de> for Challenge {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message ID must be present and type u8.")?;
                 helpers::validate_id::<Challenge, A, _>(&message_id, "Challenge")?;
                 let authmethod: String = helpers::deser_seq_element(&mut seq, "authmethod must be type String.")?;
-                let details: Value = helpers::deser_seq_element(&mut seq, "Details must be present and object like.")?;
+                let details: Value = helpers::deser_value_element(&mut seq, "Details must be present and object like.")?;
                 helpers::deser_value_is_object::<A, _>(&details, "Value must be object like")?;
                 Ok(Challenge { authmethod, details })
             }
         }
 
-        deserializer.deserialize_struct("Challenge", &["authmethod", "details"], ChallengeVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(ChallengeVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -123,4 +123,15 @@ mod tests {
         let an = to_string(&a).unwrap();
         assert_eq!(data, an)
     }
+
+    #[test]
+    fn cbor() {
+        let m1 = Challenge {
+            authmethod: "wampcra".to_string(),
+            details: json!({"challenge": {"authid":"peter","nonce":"LHRTC9zeOIrt_9U3","session":3251278072152162 as u64}})
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Challenge, _>(&d1[..]).unwrap());
+    }
 }
@@ -2,7 +2,7 @@ use std::marker::PhantomData;
 use serde_json::Value;
 use serde_repr::{Serialize_repr, Deserialize_repr};
 use serde::{Serialize, Deserialize, de::Visitor};
-use crate::{protocol::{messages::helpers, roles::Roles}, error::Error};
+use crate::{protocol::{messages::helpers, roles::Roles, Uri}, error::{Error, WampErrorUri}};
 use super::{WampMessage, MessageDirection, Unsubscribe, Subscribe, Publish, Register, Invocation, Unregister, Messages, Cancel, Call};
 
 #[derive(Debug, Clone, Serialize_repr, Deserialize_repr, PartialEq, Eq)]
@@ -47,21 +47,33 @@ pub struct WampError  {
     pub event: WampErrorEvent,
     pub request_id: u64,
     pub details: Value,
-    pub error: String,
+    pub error: Uri,
 }
 
+impl WampError {
+    /// The error as one of the predefined error URIs, or `Other` for application errors.
+    pub fn error_uri(&self) -> WampErrorUri {
+        WampErrorUri::from(self.error.clone())
+    }
+}
+
+/// Builds a [`WampError`](crate::protocol::messages::WampError) answering `$request_id`, the error URI is a [`Uri`](crate::protocol::Uri) or a string literal.
+///
+/// # Panics
+///
+/// When the error URI is a literal breaking the loose URI rules, see [`IntoUri`](crate::protocol::IntoUri).
 #[macro_export]
 macro_rules! error {
     ($request_id:expr, $event:expr, $error:expr) => {
-        error!{$request_id:expr, $event, $error, serde_json::json!({})}
+        $crate::error!{$request_id, $event, $error, serde_json::json!({})}
     };
 
     ($request_id:expr, $event:expr, $error:expr, $details:expr) => {
-        WampError {
+        $crate::protocol::messages::WampError {
             event: $event,
             request_id: $request_id,
             details: $details,
-            error: $error.to_string()
+            error: $crate::protocol::IntoUri::into_loose($error)
         }
     };
 }
@@ -104,7 +116,7 @@ impl Serialize for WampError {
     where
         S: serde::Serializer 
     {
-        (Self::ID, &self.event, &self.request_id, &self.details, &self.error).serialize(serializer)
+        (Self::ID, &self.event, &self.request_id, helpers::SerValue(&self.details), &self.error).serialize(serializer)
     }
 }
 
@@ -113,7 +125,7 @@ impl<'de> Deserialize<'de> for WampError {
     where
         D: serde::Deserializer<'de> 
     {
-        struct WampErrorVisitor(PhantomData<u64>, PhantomData<WampErrorEvent>, PhantomData<u64>, PhantomData<Value>, PhantomData<String>);
+        struct WampErrorVisitor(PhantomData<u64>, PhantomData<WampErrorEvent>, PhantomData<u64>, PhantomData<Value>, PhantomData<Uri>);
 
         impl<'vi> Visitor<'vi> for WampErrorVisitor {
             type Value = WampError;
@@ -130,9 +142,9 @@ impl<'de> Deserialize<'de> for WampError {
                 helpers::validate_id::<WampError, A, _>(&message_id, "WampError")?;
                 let event: WampErrorEvent = helpers::deser_seq_element(&mut seq, "Message type of error must be present and type u64")?;
                 let request_id: u64 = helpers::deser_seq_element(&mut seq, "Request ID must be present and type u64")?;
-                let details: Value = helpers::deser_seq_element(&mut seq, "Details must be present and object like")?;
+                let details: Value = helpers::deser_value_element(&mut seq, "Details must be present and object like")?;
                 helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
-                let error: String = helpers::deser_seq_element(&mut seq, "Error URI must be present and type String")?;
+                let error = helpers::deser_uri_element(&mut seq, Uri::loose, "Error URI must be present and a valid URI")?;
                 helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
                 Ok(WampError {
                     event,
@@ -143,7 +155,7 @@ impl<'de> Deserialize<'de> for WampError {
             }
         }
 
-        deserializer.deserialize_struct("WampError", &["event", "request_id", "details", "error"], WampErrorVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(WampErrorVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
 
 
     }
@@ -153,20 +165,37 @@ impl<'de> Deserialize<'de> for WampError {
 mod tests {
     use serde_json::{from_str, to_string};
 
+    use crate::protocol::Uri;
     use super::WampError;
 
     #[test]
     fn unsubscribe_test() {
         let data = r#"[8,34,85346237,{},"wamp.error.no_such_subscription"]"#;
         let un_e = WampError {
-            event: super::WampErrorEvent::Unsubsubscribe,
+            event: super::WampErrorEvent::Unsubscribe,
             request_id: 85346237,
             details: serde_json::json!({}),
-            error: "wamp.error.no_such_subscription".to_string()
+            error: Uri::loose("wamp.error.no_such_subscription").unwrap()
         };
         let un_e_2: WampError = from_str(data).unwrap();
         assert_eq!(un_e, un_e_2);
         let data_2 = to_string(&un_e).unwrap();
-        assert_eq!(data, data_2)
+        assert_eq!(data, data_2);
+        assert_eq!(un_e.error_uri(), crate::error::WampErrorUri::NoSuchSubscription);
+        let app: WampError = from_str(r#"[8,48,7814135,{},"com.myapp.error.object_write_protected"]"#).unwrap();
+        assert!(matches!(app.error_uri(), crate::error::WampErrorUri::Other(uri) if uri.as_str() == app.error.as_str()))
+    }
+
+    #[test]
+    fn cbor() {
+        let m1 = WampError {
+            event: super::WampErrorEvent::Unsubscribe,
+            request_id: 85346237,
+            details: serde_json::json!({}),
+            error: Uri::loose("wamp.error.no_such_subscription").unwrap()
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<WampError, _>(&d1[..]).unwrap());
     }
-}
\ No newline at end of file
+}
@@ -5,7 +5,7 @@ use serde::de::{SeqAccess, Visitor};
 use serde_json::{json, Value};
 use crate::protocol::roles::Roles;
 
-use super::{helpers, WampMessage, MessageDirection};
+use super::{helpers, Disclosed, WampMessage, MessageDirection};
 
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -17,35 +17,46 @@ pub struct Event {
     pub kwargs: Value
 }
 
+impl Event {
+    /// Concrete topic the event was published to, brokers set it for pattern based subscriptions.
+    pub fn topic(&self) -> Option<&str> {
+        self.details.get("topic").and_then(Value::as_str)
+    }
+
+    /// Session that published the event, brokers only disclose it when the publisher asked with `disclose_me`.
+    pub fn publisher(&self) -> Option<Disclosed> {
+        Disclosed::from_details(&self.details, "publisher")
+    }
+}
+
 #[macro_export]
 macro_rules! event {
 
     ($subscription:expr, $publication:expr) => {
-        event!{$subscription, $publication, json!({}), serde_json::Value::Null, Value::Null}
+        $crate::event!{$subscription, $publication, serde_json::json!({}), serde_json::Value::Null, serde_json::Value::Null}
     };
 
     ($subscription:expr, $publication:expr, $details:expr) => {
-        event!{$subscription, $publication, $details, serde_json::Value::Null, Value::Null}
+        $crate::event!{$subscription, $publication, $details, serde_json::Value::Null, serde_json::Value::Null}
     };
 
     ($subscription:expr, $publication:expr, $details:expr, args: $args:expr) => {
-        event!{$options, $procedure, $details, $args, serde_json::Value::Null}
+        $crate::event!{$subscription, $publication, $details, $args, serde_json::Value::Null}
     };
 
     ($subscription:expr, $publication:expr, $details:expr, kwargs: $kwargs:expr) => {
-        event!{$options, $procedure, $details, serde_json::Value::Null, $kwargs}
+        $crate::event!{$subscription, $publication, $details, serde_json::Value::Null, $kwargs}
     };
 
-    ($subscription:expr, $publication:expr, $details:expr, $args:expr, $kwargs:expr) => {{
-        let event = Event {
-            subscription: $crate::protocol::increment(),
-            options: $options,
-            procedure: $procedure,
+    ($subscription:expr, $publication:expr, $details:expr, $args:expr, $kwargs:expr) => {
+        $crate::protocol::messages::Event {
+            subscription: $subscription,
+            publication: $publication,
+            details: $details,
             args: $args,
             kwargs: $kwargs
-        };
-        call
-    }};
+        }
+    };
 }
 
 impl WampMessage<Event> for Event {
@@ -102,15 +113,15 @@ impl Serialize for Event {
 
         if args.is_null() {
             if kwargs.is_null() {
-                (Self::ID, &self.subscription, &self.publication, &self.details).serialize(serializer)
+                (Self::ID, &self.subscription, &self.publication, helpers::SerValue(&self.details)).serialize(serializer)
             } else {
-                (Self::ID, &self.subscription, &self.publication, &self.details, json!([]), kwargs).serialize(serializer)
+                (Self::ID, &self.subscription, &self.publication, helpers::SerValue(&self.details), json!([]), kwargs).serialize(serializer)
             }
         } else {
             if kwargs.is_null() {
-                (Self::ID, &self.subscription, &self.publication, &self.details, args).serialize(serializer)
+                (Self::ID, &self.subscription, &self.publication, helpers::SerValue(&self.details), args).serialize(serializer)
             } else {
-                (Self::ID, &self.subscription, &self.publication, &self.details, args, kwargs).serialize(serializer)
+                (Self::ID, &self.subscription, &self.publication, helpers::SerValue(&self.details), args, kwargs).serialize(serializer)
             }
         }
     }
@@ -131,7 +142,7 @@ impl<'de> Deserialize<'de> for Event {
                 helpers::validate_id::<Event, A, _>(&message_id, "Event")?;
                 let subscription: u64 = helpers::deser_seq_element(&mut seq, "Subscription must be present and type u64.")?;
                 let publication: u64 = helpers::deser_seq_element(&mut seq, "Publication must be present and object like.")?;
-                let details: Value = helpers::deser_seq_element(&mut seq, "Details must be present and object like.")?;
+                let details: Value = helpers::deser_value_element(&mut seq, "Details must be present and object like.")?;
                 helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
                 let args: Value = helpers::deser_args_kwargs_element(&mut seq, "Args must be array like or null.")?;
                 let kwargs: Value = helpers::deser_args_kwargs_element(&mut seq, "Kwargs must be object like or null.")?;
@@ -145,7 +156,7 @@ impl<'de> Deserialize<'de> for Event {
             }
         }
 
-        deserializer.deserialize_struct("Event", &["subscription", "publication", "details", "args", "kwargs"], EventVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(EventVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -172,4 +183,20 @@ mod tests {
         assert_eq!(ed, ed2);
         assert_eq!(d, d2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Event {
+            subscription: 5512315355,
+            publication: 4429313566,
+            details: serde_json::json!({}),
+            args: serde_json::json!(["\u{0}AQL/"]),
+            kwargs: serde_json::json!({"color":"orange","sizes":[23,42,7]})
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        let raw: ciborium::Value = ciborium::from_reader(&d1[..]).unwrap();
+        assert_eq!(raw.as_array().unwrap()[4].as_array().unwrap()[0], ciborium::Value::Bytes(vec![1, 2, 255]));
+        assert_eq!(m1, ciborium::from_reader::<Event, _>(&d1[..]).unwrap());
+    }
+}
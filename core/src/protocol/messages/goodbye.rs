@@ -1,7 +1,7 @@
 use std::marker::PhantomData;
 use serde::{Serialize, de::{self, Visitor}, Deserialize};
 use serde_json::Value;
-use crate::protocol::roles::Roles;
+use crate::{error::CloseUri, protocol::roles::Roles};
 use super::{WampMessage, helpers, MessageDirection};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -10,14 +10,21 @@ pub struct Goodbye {
     pub reason: String
 }
 
+impl Goodbye {
+    /// The reason as a close URI, None when it is not a valid URI.
+    pub fn close_uri(&self) -> Option<CloseUri> {
+        self.reason.parse().ok()
+    }
+}
+
 #[macro_export]
 macro_rules! goodbye {
     ($reason:expr) => {
-        goodbye!{$reason, serde_json::json!({})}
+        $crate::goodbye!{$reason, serde_json::json!({})}
     };
 
     ($reason:expr, $details:expr) => {
-        Goodbye {
+        $crate::protocol::messages::Goodbye {
             details: $details,
             reason: $reason.to_string()
         }
@@ -87,14 +94,14 @@ impl<'de> Deserialize<'de> for Goodbye {
             {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message ID must be type u8.")?;
                 helpers::validate_id::<Goodbye, A, _>(&message_id, "Goodbye")?;
-                let details: Value = helpers::deser_seq_element(&mut seq, "Details must be a JSON value.")?;
+                let details: Value = helpers::deser_value_element(&mut seq, "Details must be a JSON value.")?;
                 let reason: String = helpers::deser_seq_element(&mut seq, "Reason must be a String.")?;
                 helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
                 Ok(Goodbye { reason, details })
             }
         }
         
-        deserializer.deserialize_struct("Goodbye", &["reason", "details"], GoodbyeVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(GoodbyeVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -116,4 +123,25 @@ mod tests {
         assert_eq!(d1, d2);
         assert_eq!(g1, g2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Goodbye {
+            details: serde_json::json!({"message":"The host is shutting down now."}),
+            reason: "wamp.close.system_shutdown".to_string()
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Goodbye, _>(&d1[..]).unwrap());
+    }
+
+    #[test]
+    fn close_uri() {
+        use crate::error::CloseUri;
+
+        let goodbye = crate::goodbye!(CloseUri::SystemShutdown.as_str());
+        assert_eq!(goodbye.close_uri(), Some(CloseUri::SystemShutdown));
+        assert!(matches!(crate::goodbye!("com.myapp.maintenance").close_uri(), Some(CloseUri::Other(uri)) if uri.as_str() == "com.myapp.maintenance"));
+        assert_eq!(crate::goodbye!("com.myapp maintenance").close_uri(), None);
+    }
+}
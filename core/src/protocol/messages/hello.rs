@@ -1,7 +1,7 @@
 use std::marker::PhantomData;
 use serde::{Serialize, de::{self, Visitor}, Deserialize};
-use serde_json::{Value, json};
-use crate::protocol::roles::Roles;
+use serde_json::{Map, Value, json};
+use crate::protocol::{features::Feature, roles::Roles};
 use super::{WampMessage, helpers, MessageDirection};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -10,17 +10,127 @@ pub struct Hello {
     pub details: Value
 }
 
+/// Typed `Hello.details`, announcing the session's roles and their features along with how it authenticates.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-hello
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HelloDetails {
+    pub roles: Vec<(Roles, Vec<Feature>)>,
+    pub authmethods: Vec<String>,
+    pub authid: Option<String>,
+    pub authextra: Map<String, Value>,
+    pub agent: Option<String>
+}
+
+impl HelloDetails {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Announces the role, without any features unless they are added with `feature`.
+    pub fn role(mut self, role: Roles) -> Self {
+        if !self.roles.iter().any(|(announced, _)| *announced == role) {
+            self.roles.push((role, vec![]));
+        }
+        self
+    }
+
+    /// Announces the feature for the role, announcing the role too when needed.
+    pub fn feature(mut self, role: Roles, feature: Feature) -> Self {
+        self = self.role(role);
+        let (_, features) = self.roles.iter_mut().find(|(announced, _)| *announced == role).unwrap();
+        if !features.contains(&feature) {
+            features.push(feature);
+        }
+        self
+    }
+
+    pub fn authmethod<T: ToString>(mut self, authmethod: T) -> Self {
+        let authmethod = authmethod.to_string();
+        if !self.authmethods.contains(&authmethod) {
+            self.authmethods.push(authmethod);
+        }
+        self
+    }
+
+    pub fn authid<T: ToString>(mut self, authid: T) -> Self {
+        self.authid = Some(authid.to_string());
+        self
+    }
+
+    pub fn authextra<T: ToString>(mut self, key: T, value: Value) -> Self {
+        self.authextra.insert(key.to_string(), value);
+        self
+    }
+
+    pub fn agent<T: ToString>(mut self, agent: T) -> Self {
+        self.agent = Some(agent.to_string());
+        self
+    }
+
+    /// Adds every key `details` does not set yet, keeping what is already there.
+    /// Roles and their features are merged one by one, so announcing a role does not hide the ones derived here.
+    pub fn merge_into(self, details: &mut Value) {
+        if !details.is_object() {
+            *details = json!({});
+        }
+        merge(details, Value::from(self));
+    }
+}
+
+fn merge(target: &mut Value, derived: Value) {
+    let (Value::Object(target), Value::Object(derived)) = (target, derived) else {
+        return;
+    };
+    for (key, value) in derived {
+        match target.get_mut(&key) {
+            Some(existing) => merge(existing, value),
+            None => {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+impl From<HelloDetails> for Value {
+    fn from(details: HelloDetails) -> Self {
+        let roles: Map<String, Value> = details.roles.into_iter().map(|(role, features)| {
+            let announcement = match features.is_empty() {
+                true => json!({}),
+                false => json!({"features": features.into_iter().map(|feature| (feature.to_string(), json!(true))).collect::<Map<String, Value>>()})
+            };
+            (role.as_str().to_string(), announcement)
+        }).collect();
+        let mut value = json!({});
+        if !roles.is_empty() {
+            value["roles"] = Value::Object(roles);
+        }
+        if !details.authmethods.is_empty() {
+            value["authmethods"] = json!(details.authmethods);
+        }
+        if let Some(authid) = details.authid {
+            value["authid"] = json!(authid);
+        }
+        if !details.authextra.is_empty() {
+            value["authextra"] = Value::Object(details.authextra);
+        }
+        if let Some(agent) = details.agent {
+            value["agent"] = json!(agent);
+        }
+        value
+    }
+}
+
 #[macro_export]
 macro_rules! hello {
     
     ($realm:expr) => {
-        hello!{$realm, {serde_json::json!({})}}
+        $crate::hello!{$realm, {serde_json::json!({})}}
     };
 
     ($realm:expr, $details:expr) => {
-        core::protocol::messages::Hello {
+        $crate::protocol::messages::Hello {
             realm: $realm.to_string(),
-            details: $details
+            details: serde_json::Value::from($details)
         }
     };
 }
@@ -89,13 +199,13 @@ impl<'de> Deserialize<'de> for Hello {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message ID must be type u8.")?;
                 helpers::validate_id::<Hello, A, _>(&message_id, "Hello")?;
                 let realm: String = helpers::deser_seq_element(&mut seq, "realm must be a String.")?;
-                let details: Value = helpers::deser_seq_element(&mut seq, "Details must be a JSON value.")?;
+                let details: Value = helpers::deser_value_element(&mut seq, "Details must be a JSON value.")?;
                 helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
                 Ok(Hello { realm, details })
             }
         }
         
-        deserializer.deserialize_struct("Hello", &["realm", "details"], HelloVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(HelloVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -117,4 +227,63 @@ mod tests {
         assert_eq!(d1, d2);
         assert_eq!(g1, g2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Hello {
+            details: serde_json::json!({"roles":{"publisher":{},"subscriber":{}}}),
+            realm: "somerealm".to_string()
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Hello, _>(&d1[..]).unwrap());
+    }
+
+    #[test]
+    fn details() {
+        use serde_json::json;
+        use crate::protocol::{Feature, roles::Roles};
+        use super::HelloDetails;
+
+        let details = HelloDetails::new()
+            .role(Roles::Subscriber)
+            .feature(Roles::Caller, Feature::CallCanceling)
+            .feature(Roles::Caller, Feature::CallCanceling)
+            .feature(Roles::Caller, Feature::ProgressiveCallResults)
+            .authmethod("ticket")
+            .authid("joe")
+            .authextra("device", json!(1))
+            .agent("wamp-rs/0.1.0");
+        let hello = crate::hello!("somerealm", details);
+        assert_eq!(hello.details, json!({
+            "roles": {
+                "subscriber": {},
+                "caller": {"features": {"call_canceling": true, "progressive_call_results": true}}
+            },
+            "authmethods": ["ticket"],
+            "authid": "joe",
+            "authextra": {"device": 1},
+            "agent": "wamp-rs/0.1.0"
+        }));
+        assert_eq!(serde_json::Value::from(HelloDetails::new()), json!({}));
+        assert_eq!(serde_json::Value::from(HelloDetails::new().authmethod("ticket")), json!({"authmethods": ["ticket"]}));
+
+        let mut details = json!({"roles": {"callee": {}}, "agent": "myapp"});
+        HelloDetails::new()
+            .role(Roles::Caller)
+            .feature(Roles::Callee, Feature::CallCanceling)
+            .agent("wamp-rs/0.1.0")
+            .merge_into(&mut details);
+        assert_eq!(details, json!({
+            "roles": {
+                "callee": {"features": {"call_canceling": true}},
+                "caller": {}
+            },
+            "agent": "myapp"
+        }));
+
+        let mut details = HelloDetails::new().authmethod("ticket").into();
+        HelloDetails::new().role(Roles::Subscriber).authmethod("wampcra").merge_into(&mut details);
+        assert_eq!(details, json!({"roles": {"subscriber": {}}, "authmethods": ["ticket"]}));
+    }
+}
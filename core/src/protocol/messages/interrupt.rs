@@ -1,8 +1,8 @@
 use std::marker::PhantomData;
 use serde::{Serialize, de::{self, Visitor}, Deserialize};
-use serde_json::Value;
+use serde_json::{Value, json};
 use crate::protocol::roles::Roles;
-use super::{WampMessage, helpers, MessageDirection};
+use super::{CancelMode, WampMessage, helpers, MessageDirection};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Interrupt {
@@ -10,13 +10,27 @@ pub struct Interrupt {
     pub options: Value
 }
 
+impl Interrupt {
+    /// Interrupts the invocation sent with `request_id`.
+    pub fn new(request_id: u64, mode: CancelMode) -> Self {
+        Self {
+            request_id,
+            options: json!({"mode": mode.as_str()})
+        }
+    }
+
+    pub fn mode(&self) -> Option<CancelMode> {
+        self.options.get("mode").and_then(Value::as_str).and_then(CancelMode::from_mode)
+    }
+}
+
 #[macro_export]
 macro_rules! interrupt {
     ($request_id:expr) => {
-        interrupt!{$request_id, serde_json::json({})}
+        $crate::interrupt!{$request_id, serde_json::json!({})}
     };
     ($request_id:expr, $options:expr) => {
-        Interrupt {
+        $crate::protocol::messages::Interrupt {
             request_id: $request_id,
             options: $options
         }
@@ -87,13 +101,13 @@ impl<'de> Deserialize<'de> for Interrupt {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message ID must be type u64.")?;
                 helpers::validate_id::<Interrupt, A, _>(&message_id, "Interrupt")?;
                 let request_id: u64 = helpers::deser_seq_element(&mut seq, "Request ID must be a u64.")?;
-                let options: Value = helpers::deser_seq_element(&mut seq, "Options must be a JSON value.")?;
+                let options: Value = helpers::deser_value_element(&mut seq, "Options must be a JSON value.")?;
                 helpers::deser_value_is_object::<A, _>(&options, "Options must be object like.")?;
                 Ok(Interrupt { request_id, options })
             }
         }
         
-        deserializer.deserialize_struct("Interrupt", &["request_id", "options"], InterruptVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(InterruptVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 /* 
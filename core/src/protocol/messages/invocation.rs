@@ -15,27 +15,80 @@ pub struct Invocation {
     pub kwargs: Value
 }
 
+impl Invocation {
+    /// Whether the dealer accepts progressive `Yield`s for this invocation, from `details.receive_progress`.
+    pub fn receive_progress(&self) -> bool {
+        self.details.get("receive_progress") == Some(&json!(true))
+    }
+
+    /// Procedure the caller called, dealers set it when the invocation matched a pattern based registration.
+    pub fn procedure(&self) -> Option<&str> {
+        self.details.get("procedure").and_then(Value::as_str)
+    }
+
+    /// A chunk of a progressive invocation, more invocations with the same request ID follow.
+    pub fn is_progress(&self) -> bool {
+        self.details.get("progress") == Some(&json!(true))
+    }
+
+    /// Session that made the call, dealers only disclose it when the caller asked with `disclose_me`.
+    pub fn caller(&self) -> Option<Disclosed> {
+        Disclosed::from_details(&self.details, "caller")
+    }
+}
+
+/// Identity of a caller or publisher disclosed by the router, read from `<role>`, `<role>_authid` and `<role>_authrole`.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-caller-identification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disclosed {
+    pub session: u64,
+    pub authid: Option<String>,
+    pub authrole: Option<String>
+}
+
+impl Disclosed {
+    pub(crate) fn from_details(details: &Value, role: &str) -> Option<Self> {
+        let string = |key: String| details.get(key).and_then(Value::as_str).map(str::to_string);
+        Some(Self {
+            session: details.get(role)?.as_u64()?,
+            authid: string(format!("{role}_authid")),
+            authrole: string(format!("{role}_authrole"))
+        })
+    }
+
+    /// Writes the identity into `details` under the role's keys.
+    pub fn disclose(&self, details: &mut Value, role: &str) {
+        details[role] = json!(self.session);
+        if let Some(authid) = &self.authid {
+            details[format!("{role}_authid")] = json!(authid);
+        }
+        if let Some(authrole) = &self.authrole {
+            details[format!("{role}_authrole")] = json!(authrole);
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! invocation {
-    ($registration:expr) => {
-        invocation!{$registration, serde_json::json!({}), serde_json::Value::Null, serde_json::Value::Null}
+    ($ids:expr, $registration:expr) => {
+        invocation!{$ids, $registration, serde_json::json!({}), serde_json::Value::Null, serde_json::Value::Null}
     };
 
-    ($registration:expr, $details:expr) => {
-        invocation!{$registration, $details, serde_json::Value::Null, serde_json::Value::Null}
+    ($ids:expr, $registration:expr, $details:expr) => {
+        invocation!{$ids, $registration, $details, serde_json::Value::Null, serde_json::Value::Null}
     };
 
-    ($registration:expr, $details:expr, args: $args:expr) => {
-        invocation!{$registration, $details, $args, serde_json::Value::Null}
+    ($ids:expr, $registration:expr, $details:expr, args: $args:expr) => {
+        invocation!{$ids, $registration, $details, $args, serde_json::Value::Null}
     };
 
-    ($registration:expr, $details:expr, kwargs: $kwargs:expr) => {
-        invocation!{$registration, $details, serde_json::Value::Null, $kwargs}
+    ($ids:expr, $registration:expr, $details:expr, kwargs: $kwargs:expr) => {
+        invocation!{$ids, $registration, $details, serde_json::Value::Null, $kwargs}
     };
 
-    ($registration:expr, $details:expr, $args:expr, $kwargs:expr) => {{
+    ($ids:expr, $registration:expr, $details:expr, $args:expr, $kwargs:expr) => {{
         Invocation {
-            request_id: $crate::protocol::increment(),
+            request_id: $ids.next(),
             details: $details,
             registration: $registration,
             args: $args,
@@ -89,15 +142,15 @@ impl Serialize for Invocation {
 
         if args.is_null() {
             if kwargs.is_null() {
-                (Self::ID, &self.request_id, &self.registration, &self.details).serialize(serializer)
+                (Self::ID, &self.request_id, &self.registration, helpers::SerValue(&self.details)).serialize(serializer)
             } else {
-                (Self::ID, &self.request_id, &self.registration, &self.details, json!([]), kwargs).serialize(serializer)
+                (Self::ID, &self.request_id, &self.registration, helpers::SerValue(&self.details), json!([]), kwargs).serialize(serializer)
             }
         } else {
             if kwargs.is_null() {
-                (Self::ID, &self.request_id, &self.registration, &self.details, args).serialize(serializer)
+                (Self::ID, &self.request_id, &self.registration, helpers::SerValue(&self.details), args).serialize(serializer)
             } else {
-                (Self::ID, &self.request_id, &self.registration, &self.details, args, kwargs).serialize(serializer)
+                (Self::ID, &self.request_id, &self.registration, helpers::SerValue(&self.details), args, kwargs).serialize(serializer)
             }
         }
     }
@@ -118,7 +171,7 @@ impl<'de> Deserialize<'de> for Invocation {
                 helpers::validate_id::<Invocation, A, _>(&message_id, "Invocation")?;
                 let request_id: u64 = helpers::deser_seq_element(&mut seq, "request_id must be present and type u64.")?;
                 let registration: u64 = helpers::deser_seq_element(&mut seq, "registration must be present and object like.")?;
-                let details: Value = helpers::deser_seq_element(&mut seq, "Details must be present and object like.")?;
+                let details: Value = helpers::deser_value_element(&mut seq, "Details must be present and object like.")?;
                 helpers::deser_value_is_object::<A, _>(&details, "Details must be object like.")?;
                 let args: Value = helpers::deser_args_kwargs_element(&mut seq, "Args must be array like or null.")?;
                 let kwargs: Value = helpers::deser_args_kwargs_element(&mut seq, "Kwargs must be object like or null.")?;
@@ -132,7 +185,7 @@ impl<'de> Deserialize<'de> for Invocation {
             }
         }
 
-        deserializer.deserialize_struct("Invocation", &["request_id", "registration", "details", "args", "kwargs"], InvocationVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(InvocationVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -159,4 +212,35 @@ mod tests {
         let d2 = to_string(&ed).unwrap();
         assert_eq!(d, d2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn caller() {
+        use serde_json::json;
+        use super::Disclosed;
+
+        let d = r#"[68,6131533,9823529,{"caller":3335656,"caller_authrole":"anonymous"},[]]"#;
+        let invocation: Invocation = from_str(d).unwrap();
+        let caller = invocation.caller().unwrap();
+        assert_eq!(caller, Disclosed { session: 3335656, authid: None, authrole: Some("anonymous".to_string()) });
+        let mut details = json!({});
+        caller.disclose(&mut details, "caller");
+        assert_eq!(details, invocation.details);
+        assert_eq!(from_str::<Invocation>(r#"[68,6131533,9823529,{}]"#).unwrap().caller(), None);
+    }
+
+    #[test]
+    fn cbor() {
+        let m1 = Invocation {
+            request_id: 6131533,
+            registration: 9823529,
+            details: serde_json::json!({}),
+            args: serde_json::json!(["\u{0}AQL/"]),
+            kwargs: serde_json::json!({"firstname":"John","surname":"Doe"})
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        let raw: ciborium::Value = ciborium::from_reader(&d1[..]).unwrap();
+        assert_eq!(raw.as_array().unwrap()[4].as_array().unwrap()[0], ciborium::Value::Bytes(vec![1, 2, 255]));
+        assert_eq!(m1, ciborium::from_reader::<Invocation, _>(&d1[..]).unwrap());
+    }
+}
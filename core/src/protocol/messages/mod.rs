@@ -24,34 +24,34 @@ pub mod welcome;
 pub mod r#yield;
 
 pub use abort::Abort;
-pub use call::Call;
+pub use call::{Call, CallOptions};
 pub use authenticate::Authenticate;
-pub use cancel::Cancel;
+pub use cancel::{Cancel, CancelMode};
 pub use challenge::Challenge;
 pub use error::{WampError, WampErrorEvent};
 pub use event::Event;
 pub use goodbye::Goodbye;
-pub use hello::Hello;
+pub use hello::{Hello, HelloDetails};
 pub use interrupt::Interrupt;
-pub use invocation::Invocation;
-pub use publish::Publish;
+pub use invocation::{Disclosed, Invocation};
+pub use publish::{Publish, PublishOptions};
 pub use published::Published;
-pub use register::Register;
+pub use register::{InvokePolicy, Register, RegisterOptions};
 pub use registered::Registered;
 pub use result::WampResult;
-pub use subscribe::Subscribe;
+pub use subscribe::{Subscribe, SubscribeOptions};
 pub use subscribed::Subscribed;
 use tungstenite::Message;
 pub use unregister::Unregister;
 pub use unregistered::Unregistered;
 pub use unsubscribe::Unsubscribe;
 pub use unsubscribed::Unsubscribed;
-pub use welcome::Welcome;
+pub use welcome::{RouterRoles, Welcome, WelcomeDetails};
 pub use r#yield::Yield;
 
 
 
-use serde::{Deserialize, de, Deserializer};
+use serde::{Deserialize, de, Deserializer, Serialize, Serializer};
 use serde_json::{Value, json, from_value, from_str};
 
 
@@ -61,11 +61,13 @@ use crate::protocol::roles::Roles;
 
 pub(crate) mod helpers {
 
-    use std::fmt::Display;
-    use serde::{de::{SeqAccess, self}, Deserialize, Serializer, ser::Error};
-    use serde_json::Value;
+    use std::{fmt::{self, Display}, ops::Deref};
+    use base64::{Engine, engine::general_purpose::STANDARD};
+    use serde::{de::{SeqAccess, MapAccess, Visitor, self}, Deserialize, Deserializer, Serialize, Serializer, ser::Error};
+    use serde_json::{Map, Number, Value};
     
 
+    use crate::protocol::Uri;
     use super::WampMessage;
 
     pub(crate) fn deser_seq_element<'de, T: PartialEq + Deserialize<'de>, E: Display, A: SeqAccess<'de>>(seq: &mut A, error: E) -> Result<T, <A as SeqAccess<'de>>::Error> {
@@ -77,8 +79,17 @@ pub(crate) mod helpers {
         }
     }
 
+    /// Reads a payload value, binary formats may carry byte strings anywhere inside it.
+    pub(crate) fn deser_value_element<'de, E: Display, A: SeqAccess<'de>>(seq: &mut A, error: E) -> Result<Value, <A as SeqAccess<'de>>::Error> {
+        let element: Option<DeValue> = seq.next_element()?;
+        match element {
+            Some(element) => Ok(element.0),
+            None => Err(serde::de::Error::custom(error))
+        }
+    }
+
     pub(crate) fn deser_args_kwargs_element<'de, E: Display, A: SeqAccess<'de>>(seq: &mut A, error: E) -> Result<Value, <A as SeqAccess<'de>>::Error> {
-        let element: Option<Value> = seq.next_element()?;
+        let element: Option<Value> = seq.next_element::<DeValue>()?.map(|element| element.0);
         if let Some(element) = element {
             if element.is_object() || element.is_array() {
                 Ok(element)
@@ -90,6 +101,12 @@ pub(crate) mod helpers {
         }
     }
 
+    /// Reads a URI element, `uri` picks the rules it is checked against.
+    pub(crate) fn deser_uri_element<'de, E: Display, A: SeqAccess<'de>>(seq: &mut A, uri: fn(String) -> Result<Uri, crate::error::Error>, error: E) -> Result<Uri, <A as SeqAccess<'de>>::Error> {
+        let element: String = deser_seq_element(seq, &error)?;
+        uri(element).map_err(|_| de::Error::custom(error))
+    }
+
     pub(crate) fn validate_id<'de, M: WampMessage<M>, A: SeqAccess<'de>, E: Display>(id: &u64, name: E) -> Result<(), A::Error> {
         if &M::ID == id {
             Ok(())
@@ -106,30 +123,137 @@ pub(crate) mod helpers {
         }
     }
 
-    pub(crate) fn ser_value_is_object<S: Serializer, T: Display>(v: &Value, e: T) -> Result<&Value, S::Error> {
+    pub(crate) fn ser_value_is_object<S: Serializer, T: Display>(v: &Value, e: T) -> Result<SerValue<'_>, S::Error> {
         if v.is_object() {
-            Ok(v)
+            Ok(SerValue(v))
         } else {
             Err(S::Error::custom(e))
         }
     }
 
-    pub(crate) fn ser_value_is_args<S: Serializer, T: Display>(v: &Value, e: T) -> Result<&Value, S::Error> {
+    pub(crate) fn ser_value_is_args<S: Serializer, T: Display>(v: &Value, e: T) -> Result<SerValue<'_>, S::Error> {
         if v.is_array() || v.is_null() {
-            Ok(v)
+            Ok(SerValue(v))
         } else {
             Err(S::Error::custom(e))
         }
     }
 
-    pub(crate) fn ser_value_is_kwargs<S: Serializer, T: Display>(v: &Value, e: T) -> Result<&Value, S::Error> {
+    pub(crate) fn ser_value_is_kwargs<S: Serializer, T: Display>(v: &Value, e: T) -> Result<SerValue<'_>, S::Error> {
         if v.is_object() || v.is_null() {
-            Ok(v)
+            Ok(SerValue(v))
         } else {
             Err(S::Error::custom(e))
         }
     }
 
+    /// Binary payloads are kept in a `Value` as a string of `\0` followed by base64, as the spec mandates for JSON.
+    /// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-binary-support-in-json
+    pub(crate) const BINARY_PREFIX: char = '\0';
+
+    /// Serializes a payload value, formats that are not human readable get binary strings back as byte strings.
+    pub(crate) struct SerValue<'a>(pub(crate) &'a Value);
+
+    impl Deref for SerValue<'_> {
+        type Target = Value;
+
+        fn deref(&self) -> &Value {
+            self.0
+        }
+    }
+
+    impl Serialize for SerValue<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                return self.0.serialize(serializer);
+            }
+            match self.0 {
+                Value::String(v) if v.starts_with(BINARY_PREFIX) => {
+                    let bytes = STANDARD.decode(&v[1..]).map_err(S::Error::custom)?;
+                    serializer.serialize_bytes(&bytes)
+                },
+                Value::Array(v) => serializer.collect_seq(v.iter().map(SerValue)),
+                Value::Object(v) => serializer.collect_map(v.iter().map(|(k, v)| (k, SerValue(v)))),
+                v => v.serialize(serializer)
+            }
+        }
+    }
+
+    /// Deserializes a payload value, byte strings become binary strings so they survive in a `Value`.
+    pub(crate) struct DeValue(pub(crate) Value);
+
+    impl<'de> Deserialize<'de> for DeValue {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(DeValueVisitor).map(DeValue)
+        }
+    }
+
+    struct DeValueVisitor;
+
+    impl<'vi> Visitor<'vi> for DeValueVisitor {
+        type Value = Value;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("Any WAMP payload value.")
+        }
+
+        fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+            Ok(Value::Bool(v))
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+            Ok(Value::Number(v.into()))
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+            Ok(Value::Number(v.into()))
+        }
+
+        fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+            Ok(Number::from_f64(v).map_or(Value::Null, Value::Number))
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+            Ok(Value::String(v.to_string()))
+        }
+
+        fn visit_string<E: de::Error>(self, v: String) -> Result<Value, E> {
+            Ok(Value::String(v))
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Value, E> {
+            Ok(Value::String(format!("{BINARY_PREFIX}{}", STANDARD.encode(v))))
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+            Ok(Value::Null)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+            Ok(Value::Null)
+        }
+
+        fn visit_some<D: Deserializer<'vi>>(self, deserializer: D) -> Result<Value, D::Error> {
+            DeValue::deserialize(deserializer).map(|v| v.0)
+        }
+
+        fn visit_seq<A: SeqAccess<'vi>>(self, mut seq: A) -> Result<Value, A::Error> {
+            let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(DeValue(v)) = seq.next_element()? {
+                values.push(v);
+            }
+            Ok(Value::Array(values))
+        }
+
+        fn visit_map<A: MapAccess<'vi>>(self, mut map: A) -> Result<Value, A::Error> {
+            let mut values = Map::new();
+            while let Some((k, DeValue(v))) = map.next_entry::<String, DeValue>()? {
+                values.insert(k, v);
+            }
+            Ok(Value::Object(values))
+        }
+    }
+
 
 }
 
@@ -291,12 +415,47 @@ try_from_messages!(Unsubscribed);
 try_from_messages!(Welcome);
 try_from_messages!(Yield);
 
+impl Serialize for Messages {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        match self {
+            Messages::Abort(v) => v.serialize(serializer),
+            Messages::Authenticate(v) => v.serialize(serializer),
+            Messages::Call(v) => v.serialize(serializer),
+            Messages::Cancel(v) => v.serialize(serializer),
+            Messages::Challenge(v) => v.serialize(serializer),
+            Messages::Error(v) => v.serialize(serializer),
+            Messages::Event(v) => v.serialize(serializer),
+            Messages::Goodbye(v) => v.serialize(serializer),
+            Messages::Hello(v) => v.serialize(serializer),
+            Messages::Interrupt(v) => v.serialize(serializer),
+            Messages::Invocation(v) => v.serialize(serializer),
+            Messages::Publish(v) => v.serialize(serializer),
+            Messages::Published(v) => v.serialize(serializer),
+            Messages::Register(v) => v.serialize(serializer),
+            Messages::Registered(v) => v.serialize(serializer),
+            Messages::Result(v) => v.serialize(serializer),
+            Messages::Subscribe(v) => v.serialize(serializer),
+            Messages::Subscribed(v) => v.serialize(serializer),
+            Messages::Unregister(v) => v.serialize(serializer),
+            Messages::Unregistered(v) => v.serialize(serializer),
+            Messages::Unsubscribe(v) => v.serialize(serializer),
+            Messages::Unsubscribed(v) => v.serialize(serializer),
+            Messages::Welcome(v) => v.serialize(serializer),
+            Messages::Yield(v) => v.serialize(serializer),
+            Messages::Extension(v) => v.serialize(serializer)
+        }
+    }
+}
+
 impl<'de> Deserialize<'de> for Messages {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de> 
     {
-        let wamp_components: Vec<Value> = Deserialize::deserialize(deserializer)?;
+        let wamp_components: Vec<Value> = Vec::<helpers::DeValue>::deserialize(deserializer)?.into_iter().map(|v| v.0).collect();
         let wamp_message_id = match wamp_components.first() {
             Some(v) => { 
                 match v.as_u64() {
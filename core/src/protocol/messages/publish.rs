@@ -3,7 +3,7 @@ use std::marker::PhantomData;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde::de::{SeqAccess, Visitor};
 use serde_json::{json, Value};
-use crate::protocol::roles::Roles;
+use crate::protocol::{roles::Roles, Uri};
 
 use super::{helpers, WampMessage, MessageDirection};
 
@@ -11,35 +11,211 @@ use super::{helpers, WampMessage, MessageDirection};
 pub struct Publish {
     pub request_id: u64,
     pub options: Value,
-    pub topic: String,
+    pub topic: Uri,
     pub args: Value,
     pub kwargs: Value
 }
 
+impl Publish {
+    pub fn typed_options(&self) -> PublishOptions {
+        PublishOptions::from(&self.options)
+    }
+
+    /// The broker answers with `Published` only when this is set.
+    pub fn acknowledge(&self) -> bool {
+        self.options.get("acknowledge") == Some(&json!(true))
+    }
+}
+
+/// Typed `Publish.options`, which subscribers receive the event and whether the broker acknowledges or retains it.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-subscriber-black-and-whitelisting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishOptions {
+    pub acknowledge: bool,
+    /// The publisher does not receive its own event even when subscribed, unless this is turned off.
+    pub exclude_me: bool,
+    pub exclude: Vec<u64>,
+    pub exclude_authid: Vec<String>,
+    pub exclude_authrole: Vec<String>,
+    /// Only these sessions may receive the event, None lets every subscriber receive it.
+    pub eligible: Option<Vec<u64>>,
+    pub eligible_authid: Option<Vec<String>>,
+    pub eligible_authrole: Option<Vec<String>>,
+    /// The broker keeps the event and hands it to later subscribers that ask for it.
+    pub retain: bool,
+    /// Asks the broker to disclose the publisher's identity to subscribers.
+    pub disclose_me: bool
+}
+
+impl Default for PublishOptions {
+    fn default() -> Self {
+        Self {
+            acknowledge: false,
+            exclude_me: true,
+            exclude: vec![],
+            exclude_authid: vec![],
+            exclude_authrole: vec![],
+            eligible: None,
+            eligible_authid: None,
+            eligible_authrole: None,
+            retain: false,
+            disclose_me: false
+        }
+    }
+}
+
+impl PublishOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn acknowledge(mut self) -> Self {
+        self.acknowledge = true;
+        self
+    }
+
+    pub fn include_me(mut self) -> Self {
+        self.exclude_me = false;
+        self
+    }
+
+    pub fn exclude(mut self, sessions: Vec<u64>) -> Self {
+        self.exclude = sessions;
+        self
+    }
+
+    pub fn exclude_authid(mut self, authids: Vec<String>) -> Self {
+        self.exclude_authid = authids;
+        self
+    }
+
+    pub fn exclude_authrole(mut self, authroles: Vec<String>) -> Self {
+        self.exclude_authrole = authroles;
+        self
+    }
+
+    pub fn eligible(mut self, sessions: Vec<u64>) -> Self {
+        self.eligible = Some(sessions);
+        self
+    }
+
+    pub fn eligible_authid(mut self, authids: Vec<String>) -> Self {
+        self.eligible_authid = Some(authids);
+        self
+    }
+
+    pub fn eligible_authrole(mut self, authroles: Vec<String>) -> Self {
+        self.eligible_authrole = Some(authroles);
+        self
+    }
+
+    pub fn retain(mut self) -> Self {
+        self.retain = true;
+        self
+    }
+
+    pub fn disclose_me(mut self) -> Self {
+        self.disclose_me = true;
+        self
+    }
+
+    /// Whether a subscriber passes the exclusion and eligibility lists, the publisher itself is checked with `exclude_me` by the broker.
+    pub fn admits(&self, session: u64, authid: Option<&str>, authrole: Option<&str>) -> bool {
+        let listed = |list: &Vec<String>, value: Option<&str>| value.is_some_and(|value| list.iter().any(|entry| entry == value));
+        !self.exclude.contains(&session)
+            && !listed(&self.exclude_authid, authid)
+            && !listed(&self.exclude_authrole, authrole)
+            && self.eligible.as_ref().is_none_or(|eligible| eligible.contains(&session))
+            && self.eligible_authid.as_ref().is_none_or(|eligible| listed(eligible, authid))
+            && self.eligible_authrole.as_ref().is_none_or(|eligible| listed(eligible, authrole))
+    }
+}
+
+impl From<&Value> for PublishOptions {
+    fn from(options: &Value) -> Self {
+        let flag = |key: &str| options[key].as_bool();
+        let ids = |key: &str| options[key].as_array().map(|ids| ids.iter().filter_map(Value::as_u64).collect::<Vec<u64>>());
+        let strings = |key: &str| options[key].as_array().map(|values| values.iter().filter_map(Value::as_str).map(str::to_string).collect::<Vec<String>>());
+        Self {
+            acknowledge: flag("acknowledge").unwrap_or(false),
+            exclude_me: flag("exclude_me").unwrap_or(true),
+            exclude: ids("exclude").unwrap_or_default(),
+            exclude_authid: strings("exclude_authid").unwrap_or_default(),
+            exclude_authrole: strings("exclude_authrole").unwrap_or_default(),
+            eligible: ids("eligible"),
+            eligible_authid: strings("eligible_authid"),
+            eligible_authrole: strings("eligible_authrole"),
+            retain: flag("retain").unwrap_or(false),
+            disclose_me: flag("disclose_me").unwrap_or(false)
+        }
+    }
+}
 
+impl From<PublishOptions> for Value {
+    fn from(options: PublishOptions) -> Self {
+        let mut value = json!({});
+        if options.acknowledge {
+            value["acknowledge"] = json!(true);
+        }
+        if !options.exclude_me {
+            value["exclude_me"] = json!(false);
+        }
+        if !options.exclude.is_empty() {
+            value["exclude"] = json!(options.exclude);
+        }
+        if !options.exclude_authid.is_empty() {
+            value["exclude_authid"] = json!(options.exclude_authid);
+        }
+        if !options.exclude_authrole.is_empty() {
+            value["exclude_authrole"] = json!(options.exclude_authrole);
+        }
+        if let Some(eligible) = options.eligible {
+            value["eligible"] = json!(eligible);
+        }
+        if let Some(eligible) = options.eligible_authid {
+            value["eligible_authid"] = json!(eligible);
+        }
+        if let Some(eligible) = options.eligible_authrole {
+            value["eligible_authrole"] = json!(eligible);
+        }
+        if options.retain {
+            value["retain"] = json!(true);
+        }
+        if options.disclose_me {
+            value["disclose_me"] = json!(true);
+        }
+        value
+    }
+}
+
+/// Builds a [`Publish`](crate::protocol::messages::Publish) with the next request ID of `$ids`, the topic is a [`Uri`](crate::protocol::Uri) or a string literal.
+///
+/// # Panics
+///
+/// When the topic is a literal breaking the loose URI rules, see [`IntoUri`](crate::protocol::IntoUri).
 #[macro_export]
 macro_rules! publish {
-    ($topic:expr) => {
-        publish!{$topic, serde_json::json!({}), serde_json::Value::Null, serde_json::Value::Null}
+    ($ids:expr, $topic:expr) => {
+        $crate::publish!{$ids, $topic, serde_json::json!({}), serde_json::Value::Null, serde_json::Value::Null}
     };
 
-    ($topic:expr, $options:expr) => {
-        publish!{$topic, $options, serde_json::Value::Null, serde_json::Value::Null}
+    ($ids:expr, $topic:expr, $options:expr) => {
+        $crate::publish!{$ids, $topic, $options, serde_json::Value::Null, serde_json::Value::Null}
     };
 
-    ($topic:expr, $options:expr, args: $args:expr) => {
-        publish!{$topic, $options, $args, serde_json::Value::Null}
+    ($ids:expr, $topic:expr, $options:expr, args: $args:expr) => {
+        $crate::publish!{$ids, $topic, $options, $args, serde_json::Value::Null}
     };
 
-    ($topic:expr, $options:expr, kwargs: $kwargs:expr) => {
-        publish!{$topic, $options, serde_json::Value::Null, kwargs}
+    ($ids:expr, $topic:expr, $options:expr, kwargs: $kwargs:expr) => {
+        $crate::publish!{$ids, $topic, $options, serde_json::Value::Null, $kwargs}
     };
 
-    ($topic:expr, $options:expr, $args:expr, $kwargs:expr) => {{
-        publish {
-            request_id: $crate::protocol::increment(),
+    ($ids:expr, $topic:expr, $options:expr, $args:expr, $kwargs:expr) => {{
+        $crate::protocol::messages::Publish {
+            request_id: $ids.next(),
             options: $options,
-            topic: $topic.to_string(),
+            topic: $crate::protocol::IntoUri::into_loose($topic),
             args: $args,
             kwargs: $kwargs
         }
@@ -108,7 +284,7 @@ impl Serialize for Publish {
 
 impl<'de> Deserialize<'de> for Publish {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
-        struct PublishVisitor(PhantomData<u8>, PhantomData<u64>, PhantomData<Value>, PhantomData<String>, PhantomData<Value>, PhantomData<Value>);
+        struct PublishVisitor(PhantomData<u8>, PhantomData<u64>, PhantomData<Value>, PhantomData<Uri>, PhantomData<Value>, PhantomData<Value>);
         
         impl<'vi> Visitor<'vi> for PublishVisitor {
             type Value = Publish;
@@ -120,9 +296,9 @@ impl<'de> Deserialize<'de> for Publish {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message ID must be present and type u8.")?;
                 helpers::validate_id::<Publish, A, _>(&message_id, "Publish")?;
                 let request_id: u64 = helpers::deser_seq_element(&mut seq, "Request ID must be present and type u64.")?;
-                let options: Value = helpers::deser_seq_element(&mut seq, "Options must be present and object like.")?;
+                let options: Value = helpers::deser_value_element(&mut seq, "Options must be present and object like.")?;
                 helpers::deser_value_is_object::<A, _>(&options, "Options must be object like.")?;
-                let topic: String = helpers::deser_seq_element(&mut seq, "topic must be present and object like.")?;
+                let topic = helpers::deser_uri_element(&mut seq, Uri::loose, "topic must be present and a valid URI.")?;
                 let args: Value = helpers::deser_args_kwargs_element(&mut seq, "Args must be array like or null.")?;
                 let kwargs: Value = helpers::deser_args_kwargs_element(&mut seq, "Kwargs must be object like or null.")?;
                 Ok(Publish {
@@ -135,7 +311,7 @@ impl<'de> Deserialize<'de> for Publish {
             }
         }
 
-        deserializer.deserialize_struct("Publish", &["request_id", "options", "topic", "args", "kwargs"], PublishVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(PublishVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -143,16 +319,17 @@ impl<'de> Deserialize<'de> for Publish {
 mod tests {
     use serde_json::{json, from_str, to_string};
 
+    use crate::protocol::Uri;
     use super::Publish;
 
 
     #[test]
     fn raw_str() {
-        let d1 = r#"[48,7814135,{},"com.myapp.user.new",["johnny"],{"firstname":"John","surname":"Doe"}]"#;
+        let d1 = r#"[16,7814135,{},"com.myapp.user.new",["johnny"],{"firstname":"John","surname":"Doe"}]"#;
         let mut p1 = Publish {
             request_id: 7814135,
             options: json!({}),
-            topic: "com.myapp.user.new".to_string(),
+            topic: Uri::loose("com.myapp.user.new").unwrap(),
             args: serde_json::Value::Null,
             kwargs: json!({"firstname":"John","surname":"Doe"})
         };
@@ -165,5 +342,36 @@ mod tests {
         assert_eq!(d1, d2);
     }
 
+    #[test]
+    fn options() {
+        use super::PublishOptions;
+
+        let ids = crate::protocol::IdGenerator::new();
+        let publish = crate::publish!(&ids, "com.myapp.topic", serde_json::Value::from(PublishOptions::new().acknowledge().include_me().exclude(vec![3]).eligible_authrole(vec!["admin".to_string()])));
+        assert_eq!(publish.options, json!({"acknowledge": true, "exclude_me": false, "exclude": [3], "eligible_authrole": ["admin"]}));
+        assert!(publish.acknowledge());
+        let options = publish.typed_options();
+        assert!(!options.exclude_me);
+        assert!(options.admits(2, None, Some("admin")));
+        assert!(!options.admits(3, None, Some("admin")));
+        assert!(!options.admits(2, Some("joe"), Some("user")));
+        assert_eq!(crate::publish!(&ids, "com.myapp.topic").typed_options(), PublishOptions::new());
+        assert!(!PublishOptions::new().eligible(vec![]).admits(1, None, None));
+    }
 
-}
\ No newline at end of file
+    #[test]
+    fn cbor() {
+        let m1 = Publish {
+            request_id: 7814135,
+            options: json!({}),
+            topic: Uri::loose("com.myapp.user.new").unwrap(),
+            args: json!(["\u{0}AQL/"]),
+            kwargs: json!({"firstname":"John","surname":"Doe"})
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        let raw: ciborium::Value = ciborium::from_reader(&d1[..]).unwrap();
+        assert_eq!(raw.as_array().unwrap()[4].as_array().unwrap()[0], ciborium::Value::Bytes(vec![1, 2, 255]));
+        assert_eq!(m1, ciborium::from_reader::<Publish, _>(&d1[..]).unwrap());
+    }
+}
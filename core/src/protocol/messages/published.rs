@@ -87,7 +87,7 @@ impl<'de> Deserialize<'de> for Published {
             }
         }
 
-        deserializer.deserialize_struct("Published", &["request_id", "publication"], PublishedVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(PublishedVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -107,4 +107,15 @@ mod tests {
         assert_eq!(d1, to_string(&p1).unwrap());
         assert_eq!(from_str::<Published>(d1).unwrap(), p1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Published {
+            request_id: 239714735,
+            publication: 4429313566
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Published, _>(&d1[..]).unwrap());
+    }
+}
@@ -1,9 +1,9 @@
 use std::marker::PhantomData;
 
-use serde_json::Value;
+use serde_json::{Value, json};
 use serde::{Serialize, Deserialize, de::Visitor};
 
-use crate::protocol::{messages::helpers, roles::Roles};
+use crate::protocol::{matching::MatchPolicy, messages::helpers, roles::Roles, Uri};
 
 use super::{WampMessage, MessageDirection};
 
@@ -12,19 +12,118 @@ use super::{WampMessage, MessageDirection};
 pub struct Register  {
     pub request_id: u64,
     pub options: Value,
-    pub procedure: String,
+    pub procedure: Uri,
 }
 
+impl Register {
+    /// Policy from `options.match`, None when it is unknown or the procedure is not a valid pattern for it.
+    pub fn match_policy(&self) -> Option<MatchPolicy> {
+        let policy = match self.options.get("match") {
+            Some(policy) => MatchPolicy::from_match(policy.as_str()?)?,
+            None => MatchPolicy::Exact
+        };
+        policy.validate(&self.procedure).then_some(policy)
+    }
+
+    /// Policy from `options.invoke`, None when it is unknown.
+    pub fn invoke_policy(&self) -> Option<InvokePolicy> {
+        match self.options.get("invoke") {
+            Some(policy) => InvokePolicy::from_invoke(policy.as_str()?),
+            None => Some(InvokePolicy::Single)
+        }
+    }
+}
+
+/// How the dealer picks one of the callees sharing a registration.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-shared-registration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum InvokePolicy {
+    /// Only one callee may register the procedure.
+    #[default]
+    Single,
+    RoundRobin,
+    Random,
+    /// The callee that registered first, until it leaves.
+    First,
+    /// The callee that registered last.
+    Last
+}
+
+impl InvokePolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvokePolicy::Single => "single",
+            InvokePolicy::RoundRobin => "roundrobin",
+            InvokePolicy::Random => "random",
+            InvokePolicy::First => "first",
+            InvokePolicy::Last => "last"
+        }
+    }
+
+    pub fn from_invoke(invoke: &str) -> Option<Self> {
+        match invoke {
+            "single" => Some(InvokePolicy::Single),
+            "roundrobin" => Some(InvokePolicy::RoundRobin),
+            "random" => Some(InvokePolicy::Random),
+            "first" => Some(InvokePolicy::First),
+            "last" => Some(InvokePolicy::Last),
+            _ => None
+        }
+    }
+}
+
+/// Typed `Register.options`.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-pattern-based-registration
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RegisterOptions {
+    pub match_policy: MatchPolicy,
+    pub invoke: InvokePolicy
+}
+
+impl RegisterOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn match_policy(mut self, match_policy: MatchPolicy) -> Self {
+        self.match_policy = match_policy;
+        self
+    }
+
+    pub fn invoke(mut self, invoke: InvokePolicy) -> Self {
+        self.invoke = invoke;
+        self
+    }
+}
+
+impl From<RegisterOptions> for Value {
+    fn from(options: RegisterOptions) -> Self {
+        let mut value = json!({});
+        if options.match_policy != MatchPolicy::Exact {
+            value["match"] = json!(options.match_policy.as_str());
+        }
+        if options.invoke != InvokePolicy::Single {
+            value["invoke"] = json!(options.invoke.as_str());
+        }
+        value
+    }
+}
+
+/// Builds a [`Register`](crate::protocol::messages::Register) with the next request ID of `$ids`, the procedure is a [`Uri`](crate::protocol::Uri) or a string literal.
+///
+/// # Panics
+///
+/// When the procedure is a literal breaking the URI pattern rules, see [`IntoUri`](crate::protocol::IntoUri).
 #[macro_export]
 macro_rules! register {
-    ($procedure:expr) => {
-        register!{$procedure, serde_json::json!({})}
+    ($ids:expr, $procedure:expr) => {
+        $crate::register!{$ids, $procedure, serde_json::json!({})}
     };
-    ($procedure:expr, $options:expr) => {
-        Register {
-            procedure: $procedure.to_string(),
+    ($ids:expr, $procedure:expr, $options:expr) => {
+        $crate::protocol::messages::Register {
+            procedure: $crate::protocol::IntoUri::into_pattern($procedure),
             options: $options,
-            request_id: $crate::protocol::increment()
+            request_id: $ids.next()
         }
     };
 }
@@ -67,7 +166,7 @@ impl Serialize for Register {
     where
         S: serde::Serializer 
     {
-        (Self::ID, &self.request_id, &self.options, &self.procedure).serialize(serializer)
+        (Self::ID, &self.request_id, helpers::SerValue(&self.options), &self.procedure).serialize(serializer)
     }
 }
 
@@ -76,7 +175,7 @@ impl<'de> Deserialize<'de> for Register {
     where
         D: serde::Deserializer<'de> 
     {
-        struct RegisterVisitor(PhantomData<u64>, PhantomData<u64>, PhantomData<Value>, PhantomData<String>);
+        struct RegisterVisitor(PhantomData<u64>, PhantomData<u64>, PhantomData<Value>, PhantomData<Uri>);
 
         impl<'vi> Visitor<'vi> for RegisterVisitor {
             type Value = Register;
@@ -92,9 +191,9 @@ impl<'de> Deserialize<'de> for Register {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message id must be present and type u64.")?;
                 helpers::validate_id::<Register, A, _>(&message_id, "Register")?;
                 let request_id: u64 = helpers::deser_seq_element(&mut seq, "Request ID must be present and type u64")?;
-                let options: Value = helpers::deser_seq_element(&mut seq, "options must be present and object like")?;
+                let options: Value = helpers::deser_value_element(&mut seq, "options must be present and object like")?;
                 helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
-                let procedure: String = helpers::deser_seq_element(&mut seq, "procedure URI must be present and type String")?;
+                let procedure = helpers::deser_uri_element(&mut seq, Uri::pattern, "procedure URI must be present and a valid URI pattern")?;
                 helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
                 Ok(Register {
                     request_id,
@@ -104,7 +203,7 @@ impl<'de> Deserialize<'de> for Register {
             }
         }
 
-        deserializer.deserialize_struct("Register", &["request_id", "options", "procedure"], RegisterVisitor(PhantomData, PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(RegisterVisitor(PhantomData, PhantomData, PhantomData, PhantomData))
 
 
     }
@@ -114,6 +213,7 @@ impl<'de> Deserialize<'de> for Register {
 mod tests {
     use serde_json::{from_str, to_string, json};
 
+    use crate::protocol::Uri;
     use super::Register;
 
     #[test]
@@ -122,9 +222,37 @@ mod tests {
         let r1 = Register {
             request_id: 25349185,
             options: json!({}),
-            procedure: "com.myapp.myprocedure1".to_string()
+            procedure: Uri::loose("com.myapp.myprocedure1").unwrap()
         };
         assert_eq!(d1, to_string(&r1).unwrap());
         assert_eq!(r1, from_str::<Register>(d1).unwrap())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn options() {
+        use crate::protocol::MatchPolicy;
+        use super::{InvokePolicy, RegisterOptions};
+
+        let ids = crate::protocol::IdGenerator::new();
+        let register = crate::register!(&ids, "com.myapp.", serde_json::Value::from(RegisterOptions::new().match_policy(MatchPolicy::Prefix).invoke(InvokePolicy::RoundRobin)));
+        assert_eq!(register.options, json!({"match": "prefix", "invoke": "roundrobin"}));
+        assert_eq!(register.match_policy(), Some(MatchPolicy::Prefix));
+        assert_eq!(register.invoke_policy(), Some(InvokePolicy::RoundRobin));
+        let register = crate::register!(&ids, "com.myapp.add");
+        assert_eq!(register.options, json!({}));
+        assert_eq!(register.invoke_policy(), Some(InvokePolicy::Single));
+        assert_eq!(crate::register!(&ids, "com.myapp.add", json!({"invoke": "fastest"})).invoke_policy(), None);
+    }
+
+    #[test]
+    fn cbor() {
+        let m1 = Register {
+            request_id: 25349185,
+            options: json!({}),
+            procedure: Uri::loose("com.myapp.myprocedure1").unwrap()
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Register, _>(&d1[..]).unwrap());
+    }
+}
@@ -86,7 +86,7 @@ impl<'de> Deserialize<'de> for Registered {
             }
         }
 
-        deserializer.deserialize_struct("Registered", &["request_id", "registration"], RegisteredVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(RegisteredVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -106,4 +106,15 @@ mod tests {
         assert_eq!(d1, to_string(&p1).unwrap());
         assert_eq!(from_str::<Registered>(d1).unwrap(), p1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Registered {
+            request_id: 25349185,
+            registration: 2103333224
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Registered, _>(&d1[..]).unwrap());
+    }
+}
@@ -14,6 +14,13 @@ pub struct WampResult {
     pub kwargs: Value
 }
 
+impl WampResult {
+    /// A progressive result, more results for the same call follow.
+    pub fn is_progress(&self) -> bool {
+        self.details.get("progress") == Some(&json!(true))
+    }
+}
+
 #[macro_export]
 macro_rules! result {
     ($request_id:expr) => {
@@ -110,7 +117,7 @@ impl<'de> Deserialize<'de> for WampResult {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message ID must be present and type u8.")?;
                 helpers::validate_id::<WampResult, A, _>(&message_id, "WampResult")?;
                 let request_id: u64 = helpers::deser_seq_element(&mut seq, "Request ID must be present and type u64.")?;
-                let details: Value = helpers::deser_seq_element(&mut seq, "details must be present and object like.")?;
+                let details: Value = helpers::deser_value_element(&mut seq, "details must be present and object like.")?;
                 helpers::deser_value_is_object::<A, _>(&details, "details must be object like.")?;
                 let args: Value = helpers::deser_args_kwargs_element(&mut seq, "Args must be array like or null.")?;
                 let kwargs: Value = helpers::deser_args_kwargs_element(&mut seq, "Kwargs must be object like or null.")?;
@@ -123,7 +130,7 @@ impl<'de> Deserialize<'de> for WampResult {
             }
         }
 
-        deserializer.deserialize_struct("WampResult", &["request_id", "details", "args", "kwargs"], WampResultVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(WampResultVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -147,4 +154,19 @@ mod tests {
         assert_eq!(from_str::<WampResult>(d1).unwrap(), w1);
         assert_eq!(to_string(&w1).unwrap(), d1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = WampResult {
+            request_id: 7814135,
+            details: json!({}),
+            args: json!(["\u{0}AQL/"]),
+            kwargs: json!({"userid":123,"karma":10})
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        let raw: ciborium::Value = ciborium::from_reader(&d1[..]).unwrap();
+        assert_eq!(raw.as_array().unwrap()[3].as_array().unwrap()[0], ciborium::Value::Bytes(vec![1, 2, 255]));
+        assert_eq!(m1, ciborium::from_reader::<WampResult, _>(&d1[..]).unwrap());
+    }
+}
@@ -1,26 +1,85 @@
 use std::marker::PhantomData;
-use serde_json::Value;
+use serde_json::{Value, json};
 use serde::{Serialize, Deserialize, de::Visitor};
-use crate::protocol::{messages::helpers, roles::Roles};
+use crate::protocol::{matching::MatchPolicy, messages::helpers, roles::Roles, Uri};
 use super::{WampMessage, MessageDirection};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Subscribe  {
     pub request_id: u64,
     pub options: Value,
-    pub topic: String,
+    pub topic: Uri,
 }
 
+impl Subscribe {
+    /// Policy from `options.match`, None when it is unknown or the topic is not a valid pattern for it.
+    pub fn match_policy(&self) -> Option<MatchPolicy> {
+        let policy = match self.options.get("match") {
+            Some(policy) => MatchPolicy::from_match(policy.as_str()?)?,
+            None => MatchPolicy::Exact
+        };
+        policy.validate(&self.topic).then_some(policy)
+    }
+
+    /// Whether the subscriber wants the last retained event of each matching topic right away.
+    pub fn get_retained(&self) -> bool {
+        self.options.get("get_retained") == Some(&json!(true))
+    }
+}
+
+/// Typed `Subscribe.options`.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-pattern-based-subscription
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SubscribeOptions {
+    pub match_policy: MatchPolicy,
+    /// Asks the broker for the events retained on the matching topics, sent right after `Subscribed`.
+    pub get_retained: bool
+}
+
+impl SubscribeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn match_policy(mut self, match_policy: MatchPolicy) -> Self {
+        self.match_policy = match_policy;
+        self
+    }
+
+    pub fn get_retained(mut self) -> Self {
+        self.get_retained = true;
+        self
+    }
+}
+
+impl From<SubscribeOptions> for Value {
+    fn from(options: SubscribeOptions) -> Self {
+        let mut value = json!({});
+        if options.match_policy != MatchPolicy::Exact {
+            value["match"] = json!(options.match_policy.as_str());
+        }
+        if options.get_retained {
+            value["get_retained"] = json!(true);
+        }
+        value
+    }
+}
+
+/// Builds a [`Subscribe`](crate::protocol::messages::Subscribe) with the next request ID of `$ids`, the topic is a [`Uri`](crate::protocol::Uri) or a string literal.
+///
+/// # Panics
+///
+/// When the topic is a literal breaking the URI pattern rules, see [`IntoUri`](crate::protocol::IntoUri).
 #[macro_export]
 macro_rules! subscribe {
-    ($topic:expr) => {
-        core::subscribe!{$topic, serde_json::json!({})}
+    ($ids:expr, $topic:expr) => {
+        $crate::subscribe!{$ids, $topic, serde_json::json!({})}
     };
-    ($topic:expr, $options:expr) => {
+    ($ids:expr, $topic:expr, $options:expr) => {
         $crate::protocol::messages::Subscribe {
-            topic: $topic.to_string(),
+            topic: $crate::protocol::IntoUri::into_pattern($topic),
             options: $options,
-            request_id: $crate::protocol::increment()
+            request_id: $ids.next()
         }
     };
 }
@@ -63,7 +122,7 @@ impl Serialize for Subscribe {
     where
         S: serde::Serializer 
     {
-        (Self::ID, &self.request_id, &self.options, &self.topic).serialize(serializer)
+        (Self::ID, &self.request_id, helpers::SerValue(&self.options), &self.topic).serialize(serializer)
     }
 }
 
@@ -72,7 +131,7 @@ impl<'de> Deserialize<'de> for Subscribe {
     where
         D: serde::Deserializer<'de> 
     {
-        struct SubscribeVisitor(PhantomData<u64>, PhantomData<u64>, PhantomData<Value>, PhantomData<String>);
+        struct SubscribeVisitor(PhantomData<u64>, PhantomData<u64>, PhantomData<Value>, PhantomData<Uri>);
 
         impl<'vi> Visitor<'vi> for SubscribeVisitor {
             type Value = Subscribe;
@@ -88,9 +147,9 @@ impl<'de> Deserialize<'de> for Subscribe {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message id must be present and type u64.")?;
                 helpers::validate_id::<Subscribe, A, _>(&message_id, "Subscribe")?;
                 let request_id: u64 = helpers::deser_seq_element(&mut seq, "Request ID must be present and type u64")?;
-                let options: Value = helpers::deser_seq_element(&mut seq, "options must be present and object like")?;
+                let options: Value = helpers::deser_value_element(&mut seq, "options must be present and object like")?;
                 helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
-                let topic: String = helpers::deser_seq_element(&mut seq, "topic URI must be present and type String")?;
+                let topic = helpers::deser_uri_element(&mut seq, Uri::pattern, "topic URI must be present and a valid URI pattern")?;
                 helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
                 Ok(Subscribe {
                     request_id,
@@ -100,7 +159,7 @@ impl<'de> Deserialize<'de> for Subscribe {
             }
         }
 
-        deserializer.deserialize_struct("Subscribe", &["request_id", "options", "topic"], SubscribeVisitor(PhantomData, PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(SubscribeVisitor(PhantomData, PhantomData, PhantomData, PhantomData))
 
 
     }
@@ -110,6 +169,7 @@ impl<'de> Deserialize<'de> for Subscribe {
 mod tests {
     use serde_json::{from_str, to_string, json};
 
+    use crate::protocol::Uri;
     use super::Subscribe;
 
     #[test]
@@ -118,9 +178,41 @@ mod tests {
         let r1 = Subscribe {
             request_id: 713845233,
             options: json!({}),
-            topic: "com.myapp.mytopic1".to_string()
+            topic: Uri::loose("com.myapp.mytopic1").unwrap()
         };
         assert_eq!(d1, to_string(&r1).unwrap());
-        assert_eq!(r1, from_str::<Subscribe>(d1).unwrap())
+        assert_eq!(r1, from_str::<Subscribe>(d1).unwrap());
+        assert_eq!(from_str::<Subscribe>(r#"[32,1,{"match":"wildcard"},"com.myapp..update"]"#).unwrap().topic, "com.myapp..update");
+        assert!(from_str::<Subscribe>(r#"[32,1,{},"com.my app.topic"]"#).is_err())
+    }
+
+    #[test]
+    fn match_policy() {
+        use crate::protocol::MatchPolicy;
+        use super::SubscribeOptions;
+
+        let ids = crate::protocol::IdGenerator::new();
+        let subscribe = crate::subscribe!(&ids, "com.myapp..update", serde_json::Value::from(SubscribeOptions::new().match_policy(MatchPolicy::Wildcard)));
+        assert_eq!(subscribe.options, json!({"match": "wildcard"}));
+        assert_eq!(subscribe.match_policy(), Some(MatchPolicy::Wildcard));
+        assert_eq!(crate::subscribe!(&ids, "com.myapp..update").match_policy(), None);
+        assert_eq!(crate::subscribe!(&ids, "com.myapp", json!({"match": "regex"})).match_policy(), None);
+        assert_eq!(crate::subscribe!(&ids, "com.myapp.").options, json!({}));
+        let subscribe = crate::subscribe!(&ids, "com.myapp.status", serde_json::Value::from(SubscribeOptions::new().get_retained()));
+        assert_eq!(subscribe.options, json!({"get_retained": true}));
+        assert!(subscribe.get_retained());
+        assert_eq!(crate::subscribe!(&ids, crate::uri!("com.myapp.status")).topic, "com.myapp.status");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Subscribe {
+            request_id: 713845233,
+            options: json!({}),
+            topic: Uri::loose("com.myapp.mytopic1").unwrap()
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Subscribe, _>(&d1[..]).unwrap());
+    }
+}
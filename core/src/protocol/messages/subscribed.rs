@@ -86,7 +86,7 @@ impl<'de> Deserialize<'de> for Subscribed {
             }
         }
 
-        deserializer.deserialize_struct("Subscribed", &["request_id", "subscription"], SubscribedVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(SubscribedVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -106,4 +106,15 @@ mod tests {
         assert_eq!(d1, to_string(&p1).unwrap());
         assert_eq!(from_str::<Subscribed>(d1).unwrap(), p1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Subscribed {
+            request_id: 713845233,
+            subscription: 5512315355
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Subscribed, _>(&d1[..]).unwrap());
+    }
+}
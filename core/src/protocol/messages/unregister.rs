@@ -14,9 +14,9 @@ pub struct Unregister {
 
 #[macro_export]
 macro_rules! unregister {
-    ($registration:expr) => {
+    ($ids:expr, $registration:expr) => {
         Unregister {
-            request_id: $crate::protocol::increment(),
+            request_id: $ids.next(),
             registration: $registration
         }
     };
@@ -86,7 +86,7 @@ impl<'de> Deserialize<'de> for Unregister {
             }
         }
 
-        deserializer.deserialize_struct("Unregister", &["request_id", "registration"], UnregisterVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(UnregisterVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -106,4 +106,15 @@ mod tests {
         assert_eq!(d1, to_string(&p1).unwrap());
         assert_eq!(from_str::<Unregister>(d1).unwrap(), p1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Unregister {
+            request_id: 788923562,
+            registration: 2103333224
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Unregister, _>(&d1[..]).unwrap());
+    }
+}
@@ -82,7 +82,7 @@ impl<'de> Deserialize<'de> for Unregistered {
             }
         }
 
-        deserializer.deserialize_struct("Unregistered", &["request_id", "registration"], UnregisteredVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(UnregisteredVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -101,4 +101,14 @@ mod tests {
         assert_eq!(d1, to_string(&p1).unwrap());
         assert_eq!(from_str::<Unregistered>(d1).unwrap(), p1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Unregistered {
+            request_id: 788923562
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Unregistered, _>(&d1[..]).unwrap());
+    }
+}
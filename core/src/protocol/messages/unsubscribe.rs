@@ -13,9 +13,9 @@ pub struct Unsubscribe {
 
 #[macro_export]
 macro_rules! unsubscribe {
-    ($subscription:expr) => {
+    ($ids:expr, $subscription:expr) => {
         Unsubscribe {
-            request_id: $crate::protocol::increment(),
+            request_id: $ids.next(),
             subscription: $subscription
         }
     };
@@ -85,7 +85,7 @@ impl<'de> Deserialize<'de> for Unsubscribe {
             }
         }
 
-        deserializer.deserialize_struct("Unsubscribe", &["request_id", "subscription"], UnsubscribeVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(UnsubscribeVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -105,4 +105,15 @@ mod tests {
         assert_eq!(d1, to_string(&p1).unwrap());
         assert_eq!(from_str::<Unsubscribe>(d1).unwrap(), p1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Unsubscribe {
+            request_id: 85346237,
+            subscription: 5512315355
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Unsubscribe, _>(&d1[..]).unwrap());
+    }
+}
@@ -84,7 +84,7 @@ impl<'de> Deserialize<'de> for Unsubscribed {
             }
         }
 
-        deserializer.deserialize_struct("Unsubscribed", &["request_id", "registration"], UnsubscribedVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(UnsubscribedVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -103,4 +103,14 @@ mod tests {
         assert_eq!(d1, to_string(&p1).unwrap());
         assert_eq!(from_str::<Unsubscribed>(d1).unwrap(), p1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Unsubscribed {
+            request_id: 85346237
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Unsubscribed, _>(&d1[..]).unwrap());
+    }
+}
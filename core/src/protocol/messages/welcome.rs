@@ -1,7 +1,7 @@
 use std::marker::PhantomData;
 use serde::{Serialize, de::{self, Visitor}, Deserialize};
-use serde_json::Value;
-use crate::protocol::roles::Roles;
+use serde_json::{Map, Value, json};
+use crate::protocol::{features::Feature, roles::Roles};
 use super::{WampMessage, helpers, MessageDirection};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -10,15 +10,135 @@ pub struct Welcome {
     pub details: Value
 }
 
+impl Welcome {
+    /// Parses `details`, keys that are missing or malformed are left empty.
+    pub fn typed_details(&self) -> WelcomeDetails {
+        WelcomeDetails::from(&self.details)
+    }
+}
+
+/// Roles the router plays for the session, each with the features it advertises.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RouterRoles {
+    pub broker: Option<Vec<Feature>>,
+    pub dealer: Option<Vec<Feature>>
+}
+
+impl RouterRoles {
+    /// Announces the role, without any features unless they are added with `feature`.
+    pub fn role(mut self, role: Roles) -> Self {
+        if let Some(features @ None) = self.features_mut(role) {
+            *features = Some(vec![]);
+        }
+        self
+    }
+
+    /// Announces the feature for the role, announcing the role too when needed.
+    /// Roles other than broker and dealer are ignored.
+    pub fn feature(mut self, role: Roles, feature: Feature) -> Self {
+        if let Some(features) = self.features_mut(role) {
+            let features = features.get_or_insert_with(Vec::new);
+            if !features.contains(&feature) {
+                features.push(feature);
+            }
+        }
+        self
+    }
+
+    pub fn supports(&self, feature: Feature) -> bool {
+        [&self.broker, &self.dealer].into_iter().flatten().any(|features| features.contains(&feature))
+    }
+
+    fn features_mut(&mut self, role: Roles) -> Option<&mut Option<Vec<Feature>>> {
+        match role {
+            Roles::Broker => Some(&mut self.broker),
+            Roles::Dealer => Some(&mut self.dealer),
+            _ => None
+        }
+    }
+}
+
+/// Typed `Welcome.details`, the router's roles and the identity the session was authenticated as.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-welcome
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WelcomeDetails {
+    pub roles: RouterRoles,
+    pub authid: Option<String>,
+    pub authrole: Option<String>,
+    pub authmethod: Option<String>,
+    pub authprovider: Option<String>,
+    pub authextra: Map<String, Value>
+}
+
+impl WelcomeDetails {
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.roles.supports(feature)
+    }
+}
+
+fn features(announcement: &Value) -> Option<Vec<Feature>> {
+    let features = match announcement["features"].as_object() {
+        Some(features) => features.iter()
+            .filter(|(_, enabled)| **enabled == json!(true))
+            .filter_map(|(feature, _)| feature.parse().ok())
+            .collect(),
+        None => vec![]
+    };
+    announcement.is_object().then_some(features)
+}
+
+impl From<&Value> for WelcomeDetails {
+    fn from(details: &Value) -> Self {
+        let string = |key: &str| details[key].as_str().map(str::to_string);
+        Self {
+            roles: RouterRoles {
+                broker: features(&details["roles"]["broker"]),
+                dealer: features(&details["roles"]["dealer"])
+            },
+            authid: string("authid"),
+            authrole: string("authrole"),
+            authmethod: string("authmethod"),
+            authprovider: string("authprovider"),
+            authextra: details["authextra"].as_object().cloned().unwrap_or_default()
+        }
+    }
+}
+
+impl From<WelcomeDetails> for Value {
+    fn from(details: WelcomeDetails) -> Self {
+        let announce = |features: Vec<Feature>| match features.is_empty() {
+            true => json!({}),
+            false => json!({"features": features.into_iter().map(|feature| (feature.to_string(), json!(true))).collect::<Map<String, Value>>()})
+        };
+        let mut roles = Map::new();
+        if let Some(broker) = details.roles.broker {
+            roles.insert(Roles::Broker.as_str().to_string(), announce(broker));
+        }
+        if let Some(dealer) = details.roles.dealer {
+            roles.insert(Roles::Dealer.as_str().to_string(), announce(dealer));
+        }
+        let mut value = json!({"roles": roles});
+        for (key, field) in [("authid", details.authid), ("authrole", details.authrole), ("authmethod", details.authmethod), ("authprovider", details.authprovider)] {
+            if let Some(field) = field {
+                value[key] = json!(field);
+            }
+        }
+        if !details.authextra.is_empty() {
+            value["authextra"] = Value::Object(details.authextra);
+        }
+        value
+    }
+}
+
 #[macro_export]
 macro_rules! welcome {
     ($session:expr) => {
-        welcome!($session, Value::Null)
+        $crate::welcome!($session, serde_json::json!({}))
     };
     ($session:expr, $details:expr) => {
-        Welcome {
+        $crate::protocol::messages::Welcome {
             session: $session,
-            details: $details
+            details: serde_json::Value::from($details)
         }
     }
 }
@@ -87,13 +207,13 @@ impl<'de> Deserialize<'de> for Welcome {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message ID must be type u64.")?;
                 helpers::validate_id::<Welcome, A, _>(&message_id, "Welcome")?;
                 let session: u64 = helpers::deser_seq_element(&mut seq, "Request ID must be a u64.")?;
-                let details: Value = helpers::deser_seq_element(&mut seq, "details must be a JSON value.")?;
+                let details: Value = helpers::deser_value_element(&mut seq, "details must be a JSON value.")?;
                 helpers::deser_value_is_object::<A, _>(&details, "details must be object like.")?;
                 Ok(Welcome { session, details })
             }
         }
         
-        deserializer.deserialize_struct("Welcome", &["session", "details"], WelcomeVisitor(PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(WelcomeVisitor(PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -116,4 +236,39 @@ mod tests {
         assert_eq!(d1, to_string(&w1).unwrap());
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn cbor() {
+        let m1 = Welcome {
+            session: 9129137332,
+            details: json!({"roles": {"broker": {}}})
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        assert_eq!(m1, ciborium::from_reader::<Welcome, _>(&d1[..]).unwrap());
+    }
+
+    #[test]
+    fn details() {
+        let welcome = Welcome {
+            session: 9129137332,
+            details: json!({
+                "roles": {
+                    "broker": {"features": {"publisher_exclusion": true, "event_history": false, "some_extension": true}},
+                    "dealer": {}
+                },
+                "authid": "joe",
+                "authrole": "user",
+                "authextra": {"device": 1}
+            })
+        };
+        let details = welcome.typed_details();
+        assert_eq!(details.roles, RouterRoles::default().role(Roles::Dealer).feature(Roles::Broker, Feature::PublisherExclusion));
+        assert!(details.supports(Feature::PublisherExclusion));
+        assert!(!details.supports(Feature::EventHistory));
+        assert_eq!(details.authid.as_deref(), Some("joe"));
+        assert_eq!(details.authmethod, None);
+        assert_eq!(details.authextra["device"], json!(1));
+        assert_eq!(WelcomeDetails::from(&Value::from(details.clone())), details);
+        assert_eq!(Welcome { session: 1, details: json!(null) }.typed_details(), WelcomeDetails::default());
+    }
+}
@@ -14,25 +14,40 @@ pub struct Yield {
     pub kwargs: Value
 }
 
+impl Yield {
+    /// Marks the yield as a progressive result, the invocation stays open for more.
+    pub fn progress(mut self) -> Self {
+        if !self.options.is_object() {
+            self.options = json!({});
+        }
+        self.options["progress"] = json!(true);
+        self
+    }
+
+    pub fn is_progress(&self) -> bool {
+        self.options.get("progress") == Some(&json!(true))
+    }
+}
+
 #[macro_export]
 macro_rules! r#yield {
     ($request_id:expr) => {
-        r#yield!{$request_id:expr, json!({}), Value::Null, Value::Null}
+        $crate::r#yield!{$request_id, serde_json::json!({}), serde_json::Value::Null, serde_json::Value::Null}
     };
-    ($request_id:expr, $details:expr) => {
-        r#yield!{$request_id:expr, $details, Value::Null, Value::Null}
+    ($request_id:expr, $options:expr) => {
+        $crate::r#yield!{$request_id, $options, serde_json::Value::Null, serde_json::Value::Null}
     };
-    ($request_id:expr, $details:expr, args: $args:expr) => {
-        r#yield!{$request_id:expr, $details, $args, Value::Null}
+    ($request_id:expr, $options:expr, args: $args:expr) => {
+        $crate::r#yield!{$request_id, $options, $args, serde_json::Value::Null}
     };
-    ($request_id:expr, $details:expr, kwargs: $kwargs:expr) => {
-        r#yield!$request_id:expr, {$details, Value::Null, $kwargs}
+    ($request_id:expr, $options:expr, kwargs: $kwargs:expr) => {
+        $crate::r#yield!{$request_id, $options, serde_json::Value::Null, $kwargs}
     };
-    ($request_id:expr, $details:expr, $args:expr, $kwargs:expr) => {
-        Yield {
+    ($request_id:expr, $options:expr, $args:expr, $kwargs:expr) => {
+        $crate::protocol::messages::Yield {
             args: $args,
-            request_id: $request_id:expr,
-            details: $details,
+            request_id: $request_id,
+            options: $options,
             kwargs: $kwargs
         }
     };
@@ -110,7 +125,7 @@ impl<'de> Deserialize<'de> for Yield {
                 let message_id: u64 = helpers::deser_seq_element(&mut seq, "Message ID must be present and type u8.")?;
                 helpers::validate_id::<Yield, A, _>(&message_id, "Yield")?;
                 let request_id: u64 = helpers::deser_seq_element(&mut seq, "Request ID must be present and type u64.")?;
-                let options: Value = helpers::deser_seq_element(&mut seq, "options must be present and object like.")?;
+                let options: Value = helpers::deser_value_element(&mut seq, "options must be present and object like.")?;
                 helpers::deser_value_is_object::<A, _>(&options, "options must be object like.")?;
                 let args: Value = helpers::deser_args_kwargs_element(&mut seq, "Args must be array like or null.")?;
                 let kwargs: Value = helpers::deser_args_kwargs_element(&mut seq, "Kwargs must be object like or null.")?;
@@ -123,7 +138,7 @@ impl<'de> Deserialize<'de> for Yield {
             }
         }
 
-        deserializer.deserialize_struct("Yield", &["request_id", "options", "args", "kwargs"], YieldVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
+        deserializer.deserialize_seq(YieldVisitor(PhantomData, PhantomData, PhantomData, PhantomData, PhantomData))
     }
 }
 
@@ -147,4 +162,27 @@ mod tests {
         assert_eq!(from_str::<Yield>(d1).unwrap(), w1);
         assert_eq!(to_string(&w1).unwrap(), d1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn cbor() {
+        let m1 = Yield {
+            request_id: 6131533,
+            options: json!({}),
+            args: json!(["\u{0}AQL/"]),
+            kwargs: json!({"userid":123,"karma":10})
+        };
+        let mut d1 = vec![];
+        ciborium::into_writer(&m1, &mut d1).unwrap();
+        let raw: ciborium::Value = ciborium::from_reader(&d1[..]).unwrap();
+        assert_eq!(raw.as_array().unwrap()[3].as_array().unwrap()[0], ciborium::Value::Bytes(vec![1, 2, 255]));
+        assert_eq!(m1, ciborium::from_reader::<Yield, _>(&d1[..]).unwrap());
+    }
+
+    #[test]
+    fn progress() {
+        let r#yield = crate::r#yield!(6131533).progress();
+        assert_eq!(to_string(&r#yield).unwrap(), r#"[70,6131533,{"progress":true}]"#);
+        assert!(r#yield.is_progress());
+        assert!(!crate::r#yield!(6131533).is_progress());
+    }
+}
@@ -0,0 +1,267 @@
+use serde_json::{Value, json};
+use super::{MatchPolicy, Uri, messages::{Call, InvokePolicy, WampResult}};
+
+pub const SESSION_ON_JOIN: &str = "wamp.session.on_join";
+pub const SESSION_ON_LEAVE: &str = "wamp.session.on_leave";
+pub const SUBSCRIPTION_ON_CREATE: &str = "wamp.subscription.on_create";
+pub const SUBSCRIPTION_ON_SUBSCRIBE: &str = "wamp.subscription.on_subscribe";
+pub const SUBSCRIPTION_ON_UNSUBSCRIBE: &str = "wamp.subscription.on_unsubscribe";
+pub const SUBSCRIPTION_ON_DELETE: &str = "wamp.subscription.on_delete";
+pub const REGISTRATION_ON_CREATE: &str = "wamp.registration.on_create";
+pub const REGISTRATION_ON_REGISTER: &str = "wamp.registration.on_register";
+pub const REGISTRATION_ON_UNREGISTER: &str = "wamp.registration.on_unregister";
+pub const REGISTRATION_ON_DELETE: &str = "wamp.registration.on_delete";
+
+/// A session as `wamp.session.get` and `wamp.session.on_join` describe it.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-session-meta-api
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionDetails {
+    pub session: u64,
+    pub authid: Option<String>,
+    pub authrole: Option<String>,
+    pub authmethod: Option<String>
+}
+
+impl SessionDetails {
+    pub fn from_value(details: &Value) -> Option<Self> {
+        let string = |key: &str| details[key].as_str().map(str::to_string);
+        Some(Self {
+            session: details["session"].as_u64()?,
+            authid: string("authid"),
+            authrole: string("authrole"),
+            authmethod: string("authmethod")
+        })
+    }
+}
+
+impl From<SessionDetails> for Value {
+    fn from(details: SessionDetails) -> Self {
+        let mut value = json!({"session": details.session});
+        for (key, field) in [("authid", details.authid), ("authrole", details.authrole), ("authmethod", details.authmethod)] {
+            if let Some(field) = field {
+                value[key] = json!(field);
+            }
+        }
+        value
+    }
+}
+
+/// A subscription as `wamp.subscription.get` and `wamp.subscription.on_create` describe it.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-subscription-meta-api
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubscriptionDetails {
+    pub id: u64,
+    pub uri: String,
+    pub match_policy: MatchPolicy
+}
+
+impl SubscriptionDetails {
+    pub fn from_value(details: &Value) -> Option<Self> {
+        Some(Self {
+            id: details["id"].as_u64()?,
+            uri: details["uri"].as_str()?.to_string(),
+            match_policy: MatchPolicy::from_match(details["match"].as_str()?)?
+        })
+    }
+}
+
+impl From<SubscriptionDetails> for Value {
+    fn from(details: SubscriptionDetails) -> Self {
+        json!({"id": details.id, "uri": details.uri, "match": details.match_policy.as_str()})
+    }
+}
+
+/// A registration as `wamp.registration.get` and `wamp.registration.on_create` describe it.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-registration-meta-api
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistrationDetails {
+    pub id: u64,
+    pub uri: String,
+    pub match_policy: MatchPolicy,
+    pub invoke: InvokePolicy
+}
+
+impl RegistrationDetails {
+    pub fn from_value(details: &Value) -> Option<Self> {
+        Some(Self {
+            id: details["id"].as_u64()?,
+            uri: details["uri"].as_str()?.to_string(),
+            match_policy: MatchPolicy::from_match(details["match"].as_str()?)?,
+            invoke: InvokePolicy::from_invoke(details["invoke"].as_str()?)?
+        })
+    }
+}
+
+impl From<RegistrationDetails> for Value {
+    fn from(details: RegistrationDetails) -> Self {
+        json!({"id": details.id, "uri": details.uri, "match": details.match_policy.as_str(), "invoke": details.invoke.as_str()})
+    }
+}
+
+/// IDs of every subscription or registration grouped by match policy, the result of `wamp.subscription.list` and `wamp.registration.list`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Listing {
+    pub exact: Vec<u64>,
+    pub prefix: Vec<u64>,
+    pub wildcard: Vec<u64>
+}
+
+impl Listing {
+    pub fn push(&mut self, policy: MatchPolicy, id: u64) {
+        match policy {
+            MatchPolicy::Exact => self.exact.push(id),
+            MatchPolicy::Prefix => self.prefix.push(id),
+            MatchPolicy::Wildcard => self.wildcard.push(id)
+        }
+    }
+
+    pub fn from_value(listing: &Value) -> Option<Self> {
+        Some(Self {
+            exact: ids(&listing["exact"])?,
+            prefix: ids(&listing["prefix"])?,
+            wildcard: ids(&listing["wildcard"])?
+        })
+    }
+}
+
+impl From<Listing> for Value {
+    fn from(listing: Listing) -> Self {
+        json!({"exact": listing.exact, "prefix": listing.prefix, "wildcard": listing.wildcard})
+    }
+}
+
+/// Call to one of the router's meta procedures, along with how to read its result as `T`.
+/// The call's request ID is left at 0, clients assign one from the session's generator when sending it.
+pub struct MetaCall<T> {
+    pub call: Call,
+    read: fn(&Value) -> Option<T>
+}
+
+impl<T> MetaCall<T> {
+    fn new(procedure: &str, args: Value, read: fn(&Value) -> Option<T>) -> Self {
+        Self {
+            call: Call {
+                request_id: 0,
+                options: json!({}),
+                procedure: Uri::loose(procedure).expect("meta procedures are valid URIs"),
+                args,
+                kwargs: Value::Null
+            },
+            read
+        }
+    }
+
+    /// Reads the first positional result, None when the router answered with something else.
+    pub fn read(&self, result: &WampResult) -> Option<T> {
+        (self.read)(&result.args[0])
+    }
+}
+
+fn ids(ids: &Value) -> Option<Vec<u64>> {
+    ids.as_array()?.iter().map(Value::as_u64).collect()
+}
+
+fn id_or_null(id: &Value) -> Option<Option<u64>> {
+    match id {
+        Value::Null => Some(None),
+        id => id.as_u64().map(Some)
+    }
+}
+
+fn filter(authroles: Option<Vec<String>>) -> Value {
+    match authroles {
+        Some(authroles) => json!([authroles]),
+        None => json!([])
+    }
+}
+
+/// Number of sessions in the realm, only those with one of `authroles` when given.
+pub fn session_count(authroles: Option<Vec<String>>) -> MetaCall<u64> {
+    MetaCall::new("wamp.session.count", filter(authroles), Value::as_u64)
+}
+
+/// IDs of the sessions in the realm, only those with one of `authroles` when given.
+pub fn session_list(authroles: Option<Vec<String>>) -> MetaCall<Vec<u64>> {
+    MetaCall::new("wamp.session.list", filter(authroles), ids)
+}
+
+pub fn session_get(session: u64) -> MetaCall<SessionDetails> {
+    MetaCall::new("wamp.session.get", json!([session]), SessionDetails::from_value)
+}
+
+pub fn subscription_list() -> MetaCall<Listing> {
+    MetaCall::new("wamp.subscription.list", json!([]), Listing::from_value)
+}
+
+/// The subscription to exactly this topic pattern and policy, None when there is none.
+pub fn subscription_lookup(topic: &str, policy: MatchPolicy) -> MetaCall<Option<u64>> {
+    MetaCall::new("wamp.subscription.lookup", json!([topic, {"match": policy.as_str()}]), id_or_null)
+}
+
+/// Every subscription an event published to `topic` would go to.
+pub fn subscription_match(topic: &str) -> MetaCall<Vec<u64>> {
+    MetaCall::new("wamp.subscription.match", json!([topic]), ids)
+}
+
+pub fn subscription_get(subscription: u64) -> MetaCall<SubscriptionDetails> {
+    MetaCall::new("wamp.subscription.get", json!([subscription]), SubscriptionDetails::from_value)
+}
+
+pub fn subscription_list_subscribers(subscription: u64) -> MetaCall<Vec<u64>> {
+    MetaCall::new("wamp.subscription.list_subscribers", json!([subscription]), ids)
+}
+
+pub fn registration_list() -> MetaCall<Listing> {
+    MetaCall::new("wamp.registration.list", json!([]), Listing::from_value)
+}
+
+/// The registration of exactly this procedure pattern and policy, None when there is none.
+pub fn registration_lookup(procedure: &str, policy: MatchPolicy) -> MetaCall<Option<u64>> {
+    MetaCall::new("wamp.registration.lookup", json!([procedure, {"match": policy.as_str()}]), id_or_null)
+}
+
+/// The registration a call to `procedure` would be routed to, None when there is none.
+pub fn registration_match(procedure: &str) -> MetaCall<Option<u64>> {
+    MetaCall::new("wamp.registration.match", json!([procedure]), id_or_null)
+}
+
+pub fn registration_get(registration: u64) -> MetaCall<RegistrationDetails> {
+    MetaCall::new("wamp.registration.get", json!([registration]), RegistrationDetails::from_value)
+}
+
+pub fn registration_list_callees(registration: u64) -> MetaCall<Vec<u64>> {
+    MetaCall::new("wamp.registration.list_callees", json!([registration]), ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+    use crate::protocol::{MatchPolicy, messages::{InvokePolicy, WampResult}};
+    use super::{Listing, RegistrationDetails, registration_get, registration_lookup, session_count};
+
+    fn result(args: Value) -> WampResult {
+        WampResult { request_id: 1, details: json!({}), args, kwargs: Value::Null }
+    }
+
+    #[test]
+    fn meta_calls() {
+        let count = session_count(Some(vec!["admin".to_string()]));
+        assert_eq!(count.call.procedure, "wamp.session.count");
+        assert_eq!(count.call.args, json!([["admin"]]));
+        assert_eq!(count.read(&result(json!([3]))), Some(3));
+        assert_eq!(count.read(&result(json!(["3"]))), None);
+
+        let lookup = registration_lookup("com.myapp.", MatchPolicy::Prefix);
+        assert_eq!(lookup.call.args, json!(["com.myapp.", {"match": "prefix"}]));
+        assert_eq!(lookup.read(&result(json!([null]))), Some(None));
+        assert_eq!(lookup.read(&result(json!([7]))), Some(Some(7)));
+
+        let details = RegistrationDetails { id: 7, uri: "com.myapp.".to_string(), match_policy: MatchPolicy::Prefix, invoke: InvokePolicy::RoundRobin };
+        assert_eq!(registration_get(7).read(&result(json!([Value::from(details.clone())]))), Some(details));
+
+        let mut listing = Listing::default();
+        listing.push(MatchPolicy::Wildcard, 4);
+        assert_eq!(Value::from(listing.clone()), json!({"exact": [], "prefix": [], "wildcard": [4]}));
+        assert_eq!(Listing::from_value(&Value::from(listing.clone())), Some(listing));
+    }
+}
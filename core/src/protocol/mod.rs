@@ -1,6 +1,15 @@
 pub mod messages;
 pub mod regex;
 pub mod roles;
+pub mod features;
+pub mod matching;
+pub mod meta;
 pub mod uri;
 pub mod factories;
-pub use factories::increment;
\ No newline at end of file
+pub mod serializer;
+pub mod rawsocket;
+pub use factories::{IdGenerator, RandomIdGenerator};
+pub use serializer::Serializer;
+pub use features::Feature;
+pub use matching::MatchPolicy;
+pub use uri::{IntoUri, Uri};
\ No newline at end of file
@@ -0,0 +1,194 @@
+use crate::error::Error;
+use super::Serializer;
+
+/// First octet of every RawSocket handshake.
+pub const MAGIC: u8 = 0x7F;
+/// Largest message length that can be announced, `2^24` octets.
+pub const MAX_LENGTH: u32 = 1 << 24;
+/// Smallest message length that can be announced, `2^9` octets.
+pub const MIN_LENGTH: u32 = 1 << 9;
+
+/// Kind of a RawSocket frame, carried in the low 3 bits of the frame header.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-transport-format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameType {
+    Message,
+    Ping,
+    Pong
+}
+
+/// Reasons a router refuses a RawSocket handshake.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakeError {
+    /// The peer did not start with the RawSocket magic octet, it is not speaking RawSocket at all.
+    InvalidMagic,
+    SerializerUnsupported,
+    MaxLengthUnacceptable,
+    ReservedBitsUsed,
+    MaxConnectionCountReached
+}
+
+impl HandshakeError {
+    fn code(&self) -> u8 {
+        match self {
+            HandshakeError::InvalidMagic => 0,
+            HandshakeError::SerializerUnsupported => 1,
+            HandshakeError::MaxLengthUnacceptable => 2,
+            HandshakeError::ReservedBitsUsed => 3,
+            HandshakeError::MaxConnectionCountReached => 4
+        }
+    }
+
+    fn from_code(code: u8) -> Self {
+        match code {
+            1 => HandshakeError::SerializerUnsupported,
+            2 => HandshakeError::MaxLengthUnacceptable,
+            3 => HandshakeError::ReservedBitsUsed,
+            4 => HandshakeError::MaxConnectionCountReached,
+            _ => HandshakeError::InvalidMagic
+        }
+    }
+
+    /// The router's reply refusing the connection.
+    pub fn encode(&self) -> [u8; 4] {
+        [MAGIC, self.code() << 4, 0, 0]
+    }
+}
+
+/// Serializer and receive limit announced by either peer when a RawSocket connection opens.
+///
+/// `max_length` is the longest message the announcing peer accepts, the other side must never send anything longer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Handshake {
+    pub serializer: Serializer,
+    pub max_length: u32
+}
+
+impl Handshake {
+    pub fn new(serializer: Serializer) -> Self {
+        Self { serializer, max_length: MAX_LENGTH }
+    }
+
+    /// Sets the receive limit, rounded up to the next length the handshake can express.
+    pub fn max_length(mut self, max_length: u32) -> Self {
+        self.max_length = max_length.clamp(MIN_LENGTH, MAX_LENGTH).next_power_of_two();
+        self
+    }
+
+    pub fn encode(&self) -> [u8; 4] {
+        let exponent = (self.max_length.trailing_zeros() - MIN_LENGTH.trailing_zeros()) as u8;
+        [MAGIC, exponent << 4 | serializer_code(self.serializer), 0, 0]
+    }
+
+    /// Reads a client's request or a router's reply, a router refusal is returned as its `HandshakeError`.
+    pub fn decode(data: [u8; 4]) -> Result<Self, HandshakeError> {
+        if data[0] != MAGIC {
+            return Err(HandshakeError::InvalidMagic);
+        }
+        let exponent = data[1] >> 4;
+        let code = data[1] & 0x0F;
+        if code == 0 {
+            return Err(HandshakeError::from_code(exponent));
+        }
+        if data[2] != 0 || data[3] != 0 {
+            return Err(HandshakeError::ReservedBitsUsed);
+        }
+        let serializer = serializer_from_code(code).ok_or(HandshakeError::SerializerUnsupported)?;
+        Ok(Self {
+            serializer,
+            max_length: MIN_LENGTH << exponent
+        })
+    }
+}
+
+fn serializer_code(serializer: Serializer) -> u8 {
+    match serializer {
+        Serializer::Json => 1,
+        Serializer::MsgPack => 2,
+        Serializer::Cbor => 3
+    }
+}
+
+fn serializer_from_code(code: u8) -> Option<Serializer> {
+    match code {
+        1 => Some(Serializer::Json),
+        2 => Some(Serializer::MsgPack),
+        3 => Some(Serializer::Cbor),
+        _ => None
+    }
+}
+
+/// Prefix of every frame after the handshake, its type and the 24 bit length of the payload that follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub frame_type: FrameType,
+    pub length: u32
+}
+
+impl FrameHeader {
+    pub fn new(frame_type: FrameType, length: usize) -> Result<Self, Error> {
+        if length >= MAX_LENGTH as usize {
+            return Err(Error::Error("RawSocket payload does not fit in a single frame."));
+        }
+        Ok(Self { frame_type, length: length as u32 })
+    }
+
+    pub fn encode(&self) -> [u8; 4] {
+        let length = self.length.to_be_bytes();
+        let frame_type = match self.frame_type {
+            FrameType::Message => 0,
+            FrameType::Ping => 1,
+            FrameType::Pong => 2
+        };
+        [frame_type, length[1], length[2], length[3]]
+    }
+
+    pub fn decode(data: [u8; 4]) -> Result<Self, Error> {
+        let frame_type = match data[0] {
+            0 => FrameType::Message,
+            1 => FrameType::Ping,
+            2 => FrameType::Pong,
+            _ => return Err(Error::Error("RawSocket frame uses a reserved frame type."))
+        };
+        Ok(Self {
+            frame_type,
+            length: u32::from_be_bytes([0, data[1], data[2], data[3]])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocol::Serializer;
+    use super::{FrameHeader, FrameType, Handshake, HandshakeError, MAX_LENGTH, MIN_LENGTH};
+
+    #[test]
+    fn handshake() {
+        let handshake = Handshake::new(Serializer::MsgPack);
+        assert_eq!(handshake.encode(), [0x7F, 0xF2, 0, 0]);
+        assert_eq!(Handshake::decode(handshake.encode()), Ok(handshake));
+        let handshake = Handshake::new(Serializer::Cbor).max_length(1000);
+        assert_eq!(handshake.max_length, 1024);
+        assert_eq!(handshake.encode(), [0x7F, 0x13, 0, 0]);
+        assert_eq!(Handshake::new(Serializer::Json).max_length(1).max_length, MIN_LENGTH);
+        assert_eq!(Handshake::new(Serializer::Json).max_length(u32::MAX).max_length, MAX_LENGTH);
+    }
+
+    #[test]
+    fn handshake_errors() {
+        assert_eq!(Handshake::decode(HandshakeError::MaxConnectionCountReached.encode()), Err(HandshakeError::MaxConnectionCountReached));
+        assert_eq!(Handshake::decode([0x7F, 0xF7, 0, 0]), Err(HandshakeError::SerializerUnsupported));
+        assert_eq!(Handshake::decode([0x7F, 0xF1, 0, 1]), Err(HandshakeError::ReservedBitsUsed));
+        assert_eq!(Handshake::decode(*b"GET "), Err(HandshakeError::InvalidMagic));
+    }
+
+    #[test]
+    fn frame_header() {
+        let header = FrameHeader::new(FrameType::Pong, 0x012345).unwrap();
+        assert_eq!(header.encode(), [2, 0x01, 0x23, 0x45]);
+        assert_eq!(FrameHeader::decode(header.encode()).unwrap(), header);
+        assert!(FrameHeader::new(FrameType::Message, MAX_LENGTH as usize).is_err());
+        assert!(FrameHeader::decode([8, 0, 0, 0]).is_err());
+    }
+}
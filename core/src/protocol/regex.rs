@@ -79,13 +79,3 @@ pub mod uri_rules {
     }
     
 }
-
-//pub struct URI(String);
-//
-//impl URI {
-//    pub fn validate<R: uri_rules::Rule>(r: R, v: String) -> Self {
-//        todo!();
-//        let rule = r.rule().loose;
-//        //let values = rule.capture
-//    }
-//}
@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Roles {
     Callee,
     Caller,
@@ -5,4 +6,18 @@ pub enum Roles {
     Subscriber,
     Dealer,
     Broker
-}
\ No newline at end of file
+}
+
+impl Roles {
+    /// Key the role is announced under in `details.roles`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Roles::Callee => "callee",
+            Roles::Caller => "caller",
+            Roles::Publisher => "publisher",
+            Roles::Subscriber => "subscriber",
+            Roles::Dealer => "dealer",
+            Roles::Broker => "broker"
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use tungstenite::Message;
+use crate::{error::Error, protocol::messages::Messages};
+
+/// Wire formats a session can be established with, picked through the WebSocket subprotocol.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-serializations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Serializer {
+    #[default]
+    Json,
+    MsgPack,
+    Cbor
+}
+
+impl Serializer {
+    /// Resolves the serializer from a subprotocol such as `wamp.2.json`, `wamp.2.msgpack` or `wamp.2.cbor`.
+    pub fn from_protocol<T: ToString>(protocol: T) -> Option<Self> {
+        match protocol.to_string().as_str() {
+            "wamp.2.json" => Some(Self::Json),
+            "wamp.2.msgpack" => Some(Self::MsgPack),
+            "wamp.2.cbor" => Some(Self::Cbor),
+            _ => None
+        }
+    }
+
+    pub fn protocol(&self) -> &'static str {
+        match self {
+            Serializer::Json => "wamp.2.json",
+            Serializer::MsgPack => "wamp.2.msgpack",
+            Serializer::Cbor => "wamp.2.cbor"
+        }
+    }
+
+    pub fn encode<T: Serialize>(&self, message: &T) -> Result<Vec<u8>, Error> {
+        match self {
+            Serializer::Json => Ok(serde_json::to_vec(message)?),
+            Serializer::MsgPack => Ok(rmp_serde::to_vec(message)?),
+            Serializer::Cbor => {
+                let mut data = vec![];
+                ciborium::into_writer(message, &mut data)?;
+                Ok(data)
+            }
+        }
+    }
+
+    pub fn decode(&self, data: &[u8]) -> Result<Messages, Error> {
+        self.deserialize(data)
+    }
+
+    /// Decodes into a plain value, to look into a frame that is not a valid message.
+    pub fn decode_value(&self, data: &[u8]) -> Result<Value, Error> {
+        self.deserialize(data)
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T, Error> {
+        match self {
+            Serializer::Json => Ok(serde_json::from_slice(data)?),
+            Serializer::MsgPack => Ok(rmp_serde::from_slice(data)?),
+            Serializer::Cbor => Ok(ciborium::from_reader(data)?)
+        }
+    }
+
+    /// Encodes into a WebSocket frame, text for JSON and binary for every other format.
+    pub fn to_message<T: Serialize>(&self, message: &T) -> Result<Message, Error> {
+        match self {
+            Serializer::Json => Ok(Message::Text(serde_json::to_string(message)?)),
+            _ => Ok(Message::Binary(self.encode(message)?))
+        }
+    }
+
+    pub fn from_message(&self, message: Message) -> Result<Messages, Error> {
+        self.decode(&message.into_data())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use crate::protocol::{messages::*, Uri};
+    use super::Serializer;
+
+    fn messages() -> Vec<Messages> {
+        vec![
+            Abort { details: json!({"message": "The realm does not exist."}), reason: "wamp.error.no_such_realm".to_string() }.into(),
+            Authenticate { signature: "secret".to_string(), details: json!({}) }.into(),
+            Call { request_id: 7814135, options: json!({}), procedure: Uri::loose("com.myapp.user.new").unwrap(), args: json!(["johnny"]), kwargs: json!({"firstname": "John"}) }.into(),
+            Cancel { request_id: 7814135, options: json!({"mode": "kill"}) }.into(),
+            Challenge { authmethod: "wampcra".to_string(), details: json!({"challenge": "{}"}) }.into(),
+            WampError { event: WampErrorEvent::Call, request_id: 7814135, details: json!({}), error: Uri::loose("wamp.error.no_such_procedure").unwrap() }.into(),
+            Event { subscription: 5512315355, publication: 4429313566, details: json!({}), args: json!(["Hello, world!"]), kwargs: Value::Null }.into(),
+            Goodbye { details: json!({}), reason: "wamp.close.goodbye_and_out".to_string() }.into(),
+            Hello { realm: "somerealm".to_string(), details: json!({"roles": {"publisher": {}}}) }.into(),
+            Interrupt { request_id: 7814135, options: json!({}) }.into(),
+            Invocation { request_id: 6131533, registration: 9823529, details: json!({}), args: json!([]), kwargs: json!({"karma": 10}) }.into(),
+            Publish { request_id: 239714735, options: json!({}), topic: Uri::loose("com.myapp.mytopic1").unwrap(), args: json!([1, 2.5, "three", null, true]), kwargs: Value::Null }.into(),
+            Published { request_id: 239714735, publication: 4429313566 }.into(),
+            Register { request_id: 25349185, options: json!({}), procedure: Uri::loose("com.myapp.myprocedure1").unwrap() }.into(),
+            Registered { request_id: 25349185, registration: 2103333224 }.into(),
+            WampResult { request_id: 7814135, details: json!({}), args: json!([30]), kwargs: Value::Null }.into(),
+            Subscribe { request_id: 713845233, options: json!({}), topic: Uri::loose("com.myapp.mytopic1").unwrap() }.into(),
+            Subscribed { request_id: 713845233, subscription: 5512315355 }.into(),
+            Unregister { request_id: 788923562, registration: 2103333224 }.into(),
+            Unregistered { request_id: 788923562 }.into(),
+            Unsubscribe { request_id: 85346237, subscription: 5512315355 }.into(),
+            Unsubscribed { request_id: 85346237 }.into(),
+            Welcome { session: 9129137332, details: json!({"roles": {"broker": {}}}) }.into(),
+            Yield { request_id: 6131533, options: json!({}), args: json!([]), kwargs: json!({"userid": 123}) }.into()
+        ]
+    }
+
+    #[test]
+    fn protocol() {
+        assert_eq!(Serializer::from_protocol("wamp.2.json"), Some(Serializer::Json));
+        assert_eq!(Serializer::from_protocol("wamp.2.msgpack"), Some(Serializer::MsgPack));
+        assert_eq!(Serializer::from_protocol("wamp.2.cbor"), Some(Serializer::Cbor));
+        assert_eq!(Serializer::from_protocol("wamp.2.xml"), None);
+        assert_eq!(Serializer::from_protocol("wamp.3.json"), None);
+        assert_eq!(Serializer::from_protocol("foo.json"), None);
+        assert_eq!(Serializer::from_protocol("json"), None);
+    }
+
+    #[test]
+    fn msgpack() {
+        for message in messages() {
+            let data = Serializer::MsgPack.encode(&message).unwrap();
+            assert_eq!(Serializer::MsgPack.decode(&data).unwrap(), message);
+            assert!(matches!(Serializer::MsgPack.to_message(&message).unwrap(), tungstenite::Message::Binary(_)));
+        }
+    }
+
+    #[test]
+    fn cbor() {
+        for message in messages() {
+            let data = Serializer::Cbor.encode(&message).unwrap();
+            assert_eq!(Serializer::Cbor.decode(&data).unwrap(), message);
+            assert!(matches!(Serializer::Cbor.to_message(&message).unwrap(), tungstenite::Message::Binary(_)));
+        }
+    }
+
+    #[test]
+    fn json() {
+        for message in messages() {
+            let data = Serializer::Json.encode(&message).unwrap();
+            assert_eq!(Serializer::Json.decode(&data).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn decode_value() {
+        let frame = json!([32, 713845233, {}, "com.my app.topic"]);
+        for serializer in [Serializer::Json, Serializer::MsgPack, Serializer::Cbor] {
+            let data = serializer.encode(&frame).unwrap();
+            assert!(serializer.decode(&data).is_err());
+            assert_eq!(serializer.decode_value(&data).unwrap(), frame);
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use std::{borrow::{Borrow, Cow}, fmt::{self, Display}, ops::Deref, str::FromStr};
+use lazy_static::lazy_static;
+use serde::{Serialize, Deserialize, de};
+use crate::error::Error;
+use super::regex::uri_rules::{Regex, Rule, WampRules, WampUriRule};
+
+lazy_static! {
+    pub(crate) static ref URI: WampUriRule = WampRules::URI.rule();
+    pub(crate) static ref PREFIX: WampUriRule = WampRules::Prefix.rule();
+    pub(crate) static ref PATTERN: WampUriRule = WampRules::PrefixOrWildcard.rule();
+}
+
+/// A WAMP URI checked against `uri_rules` when built, so it can not hold an invalid topic, procedure or error.
+/// Read More: https://wamp-proto.org/wamp_latest_ietf.html#name-uris
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Uri(Cow<'static, str>);
+
+/// Builds a [`Uri`] from a string literal checked against the strict URI rule at compile time, a bad literal fails the build.
+#[macro_export]
+macro_rules! uri {
+    ($uri:literal) => {{
+        const URI: $crate::protocol::Uri = $crate::protocol::Uri::from_static($uri);
+        URI
+    }};
+}
+
+impl Uri {
+    /// Checks `uri` against `rule`, by its strict regex when `strict` is set and by its loose one otherwise.
+    pub fn validate<R: Rule>(rule: R, strict: bool, uri: impl Into<String>) -> Result<Self, Error> {
+        let rule = rule.rule();
+        Self::checked(if strict { &rule.strict } else { &rule.loose }, uri.into())
+    }
+
+    /// A concrete URI made only of lowercase letters, digits and underscores.
+    pub fn strict(uri: impl Into<String>) -> Result<Self, Error> {
+        Self::checked(&URI.strict, uri.into())
+    }
+
+    /// A strict URI usable in constants, panics when `uri` breaks the strict rule which fails the build in a const context.
+    pub const fn from_static(uri: &'static str) -> Self {
+        assert!(is_strict(uri), "URI must follow the strict URI rules");
+        Self(Cow::Borrowed(uri))
+    }
+
+    /// A concrete URI whose components hold no whitespace, `.` or `#`.
+    pub fn loose(uri: impl Into<String>) -> Result<Self, Error> {
+        Self::checked(&URI.loose, uri.into())
+    }
+
+    /// A loose URI that may also have empty components, as prefix and wildcard subscriptions and registrations use.
+    pub fn pattern(uri: impl Into<String>) -> Result<Self, Error> {
+        Self::checked(&PATTERN.loose, uri.into())
+    }
+
+    fn checked(rule: &Regex, uri: String) -> Result<Self, Error> {
+        if rule.is_match(&uri) {
+            Ok(Self(Cow::Owned(uri)))
+        } else {
+            Err(Error::InvalidURI)
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Uri {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Uri {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Uri {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for Uri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl PartialEq<str> for Uri {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Uri {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl From<Uri> for String {
+    fn from(uri: Uri) -> Self {
+        uri.0.into_owned()
+    }
+}
+
+impl FromStr for Uri {
+    type Err = Error;
+
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        Self::loose(uri)
+    }
+}
+
+impl TryFrom<&str> for Uri {
+    type Error = Error;
+
+    fn try_from(uri: &str) -> Result<Self, Self::Error> {
+        Self::loose(uri)
+    }
+}
+
+impl TryFrom<String> for Uri {
+    type Error = Error;
+
+    fn try_from(uri: String) -> Result<Self, Self::Error> {
+        Self::loose(uri)
+    }
+}
+
+/// What the message macros take as a URI, a [`Uri`] as it is or a string literal checked as the message is built.
+/// URIs made at runtime go through [`Uri::loose`] or [`Uri::pattern`] so the error can be handled.
+///
+/// # Panics
+///
+/// A literal is only checked when the message is built, unlike [`uri!`](crate::uri) which fails the build,
+/// so an invalid one panics at runtime. Pass `uri!("...")` instead for a strict URI checked at compile time.
+pub trait IntoUri {
+    fn into_loose(self) -> Uri;
+    fn into_pattern(self) -> Uri;
+}
+
+impl IntoUri for Uri {
+    fn into_loose(self) -> Uri {
+        self
+    }
+
+    fn into_pattern(self) -> Uri {
+        self
+    }
+}
+
+impl IntoUri for &'static str {
+    fn into_loose(self) -> Uri {
+        Uri::loose(self).expect("URI literals must follow the loose URI rules")
+    }
+
+    fn into_pattern(self) -> Uri {
+        Uri::pattern(self).expect("URI pattern literals must follow the loose URI rules")
+    }
+}
+
+/// `WampRules::URI` strict, `^([\da-z_]+\.)*([\da-z_]+)$`, written out so it can run at compile time.
+const fn is_strict(uri: &str) -> bool {
+    let bytes = uri.as_bytes();
+    let mut component = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'.' if component > 0 => component = 0,
+            b'0'..=b'9' | b'a'..=b'z' | b'_' => component += 1,
+            _ => return false
+        }
+        i += 1;
+    }
+    component > 0
+}
+
+impl Serialize for Uri {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer
+    {
+        serializer.serialize_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Uri {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>
+    {
+        let uri = String::deserialize(deserializer)?;
+        Self::loose(uri).map_err(|_| de::Error::custom("URI must follow the loose URI rules"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{from_str, to_string};
+    use crate::protocol::regex::uri_rules::WampRules;
+    use super::{IntoUri, Uri};
+
+    #[test]
+    fn validate() {
+        assert_eq!(Uri::strict("com.myapp.topic1").unwrap(), "com.myapp.topic1");
+        assert!(Uri::strict("com.myApp.topic").is_err());
+        assert!(Uri::loose("com.myApp.topic").is_ok());
+        assert!(Uri::loose("com.my app.topic").is_err());
+        assert!(Uri::loose("com..topic").is_err());
+        assert!(Uri::loose("").is_err());
+        assert!(Uri::pattern("com..topic").is_ok());
+        assert!(Uri::pattern("com.myapp.").is_ok());
+        assert!(Uri::pattern("com.#.topic").is_err());
+        assert!(Uri::validate(WampRules::Name, true, "topic").is_ok());
+        assert!(Uri::validate(WampRules::Name, false, "com.topic").is_err());
+        assert_eq!("com.myapp.topic".parse::<Uri>().unwrap().as_str(), "com.myapp.topic");
+    }
+
+    #[test]
+    fn compile_time() {
+        const TOPIC: Uri = crate::uri!("com.myapp.topic_1");
+        assert_eq!(TOPIC, Uri::strict("com.myapp.topic_1").unwrap());
+        for uri in ["com.myapp.topic", "a", "com.my_app.v2", "", ".", "com.", ".com", "com..topic", "com.myApp", "com.my app", "com.#"] {
+            assert_eq!(super::is_strict(uri), Uri::strict(uri).is_ok(), "{uri}");
+        }
+    }
+
+    #[test]
+    fn into_uri() {
+        assert_eq!("com.myapp.".into_pattern(), "com.myapp.");
+        let uri = Uri::loose(format!("com.myapp.{}", "topic")).unwrap();
+        assert_eq!(uri.clone().into_loose(), uri);
+    }
+
+    #[test]
+    #[should_panic(expected = "URI literals must follow the loose URI rules")]
+    fn invalid_literal() {
+        "com.myapp.".into_loose();
+    }
+
+    #[test]
+    fn serde() {
+        let uri = Uri::loose("com.myapp.topic").unwrap();
+        assert_eq!(to_string(&uri).unwrap(), r#""com.myapp.topic""#);
+        assert_eq!(from_str::<Uri>(r#""com.myapp.topic""#).unwrap(), uri);
+        assert!(from_str::<Uri>(r#""com.my app""#).is_err());
+    }
+}
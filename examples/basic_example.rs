@@ -2,30 +2,24 @@
 use dotenv;
 use serde_json::{json, Value};
 use client::{Client, WampRequest};
-use core::protocol::factories;
+use core::protocol::{factories, messages::HelloDetails, Uri};
 use std::time::SystemTime;
 
 
 fn main() {
     dotenv::from_filename("examples/.env").unwrap();
     let time = SystemTime::now();
-    let (mut client, _) = Client::connect(WampRequest { uri: dotenv::var("URL").unwrap(), protocol: "wamp.json" }).unwrap();
+    let (mut client, _) = Client::connect(WampRequest { uri: dotenv::var("URL").unwrap(), protocol: "wamp.2.json" }).unwrap();
 
     // Hello message, this is required to be sent first per wamp spec.
-    client.send(core::hello!{
+    // `join` announces the roles and features the client implements, only the auth method is added here.
+    client.join(core::hello!{
         "co.fun.chat.ifunny".to_string(),
-        json!({
-            "roles": {
-                "subscriber": {},
-                "caller": {},
-                "callee": {},
-                "publisher": {}
-            },
-            "authmethods": ["ticket"]
-        })
+        HelloDetails::new().authmethod("ticket")
     }).unwrap();
 
     // Authentication handling, here we use a ticket.
+    // For WAMP-CRA register an authenticator instead: `client.authenticator(client::auth::WampCra::new(secret));`
     client.on_challenge(Box::new(|mut ctx, _| {
         // Note that we use helpful macros to construct the messages.
         ctx.send(core::authenticate!(dotenv::var("BEARER").unwrap())).unwrap();
@@ -33,18 +27,26 @@ fn main() {
     }));
 
     client.on_welcome(Box::new(move |mut ctx, welcome| {
-        let authid = &welcome.details["authid"].as_str().unwrap();
+        let authid = welcome.typed_details().authid.unwrap_or_default();
         let dur = SystemTime::now().duration_since(time);
         println!("{:#?} {:#?}", dur.unwrap(), welcome);
+        // Topics built at runtime are checked first, the authid could make an invalid URI.
+        let (Ok(chats), Ok(invites)) = (Uri::loose(format!("co.fun.chat.user.{authid}.chats")), Uri::loose(format!("co.fun.chat.user.{authid}.invites"))) else {
+            println!("authid {authid} does not make valid topics");
+            return ctx;
+        };
         // Subscribe to listen for the chats the user is in
-        let _ = ctx.subscribe(core::subscribe!(format!("co.fun.chat.user.{authid}.chats")), Box::new(move |mut ctx, subscribed| {
+        let _ = ctx.subscribe(core::subscribe!(ctx.ids(), chats), Box::new(move |mut ctx, subscribed| {
             let subscribed = subscribed.unwrap();
 
             // Attatch event listener for the subscription, which simply lists chats that the given user is in
             ctx.event(subscribed, Box::new(move |mut ctx, event| {
                 let _: &Vec<&Value> = &event.kwargs.get("chats").unwrap().as_array().unwrap().iter().map(|i| {
     
-                    let topic = format!("co.fun.chat.chat.{}", i.get("name").unwrap().as_str().unwrap());
+                    // Chat names that do not make a valid topic are skipped
+                    let Ok(topic) = Uri::loose(format!("co.fun.chat.chat.{}", i.get("name").unwrap().as_str().unwrap())) else {
+                        return i;
+                    };
 
                     // Check if you are subscribed to the given topic
                     if !factories::subscription_contains(&topic) {
@@ -53,7 +55,7 @@ fn main() {
                         factories::subscribe(topic.clone());
 
                         // Subscribe to the topic
-                        ctx.subscribe(core::subscribe!(topic), Box::new(|mut ctx, subscribed| {
+                        ctx.subscribe(core::subscribe!(ctx.ids(), topic), Box::new(|mut ctx, subscribed| {
                         
                             println!("Subscription success: {:#?}", subscribed);
                             // Attatch event listener for the subscription
@@ -73,7 +75,7 @@ fn main() {
             ctx
         })).unwrap();
 
-        let _ = ctx.subscribe(core::subscribe!(format!("co.fun.chat.user.{authid}.invites")), Box::new(|mut ctx, subscribed| {
+        let _ = ctx.subscribe(core::subscribe!(ctx.ids(), invites), Box::new(|mut ctx, subscribed| {
             let subscribed = subscribed.unwrap();
             ctx.event(subscribed, Box::new(|mut ctx, event| {
                 println!("{:#?}", event);
@@ -84,7 +86,7 @@ fn main() {
                         i["name"].as_str().unwrap()
                     }).collect::<Vec<&str>>();
                     if !ids.is_empty() {
-                        ctx.call(core::call!("co.fun.chat.invite.accept", json!({}), args: json!(ids)), Box::new(|ctx, result| {
+                        ctx.call(core::call!(ctx.ids(), core::uri!("co.fun.chat.invite.accept"), json!({}), args: json!(ids)), Box::new(|ctx, result| {
                             let result = result.unwrap();
                             println!("{:#?}", result);
                             ctx
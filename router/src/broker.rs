@@ -0,0 +1,245 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use serde_json::Value;
+use core::protocol::{MatchPolicy, messages::{Disclosed, PublishOptions}, meta::Listing};
+
+use super::trie::UriTrie;
+
+pub(crate) struct Subscription {
+    pub(crate) id: u64,
+    pub(crate) topic: String,
+    pub(crate) policy: MatchPolicy,
+    pub(crate) subscribers: HashSet<u64>
+}
+
+/// A publication the broker keeps, as the topic's retained event or in its history.
+#[derive(Clone)]
+pub(crate) struct Publication {
+    pub(crate) publication: u64,
+    /// Position among every publication the broker recorded, set by `record` as publication IDs are random.
+    pub(crate) sequence: u64,
+    /// The publisher's identity when it asked to be disclosed.
+    pub(crate) disclosed: Option<Disclosed>,
+    /// Exclusion and eligibility still apply to sessions that ask for it later.
+    pub(crate) options: PublishOptions,
+    pub(crate) args: Value,
+    pub(crate) kwargs: Value
+}
+
+/// Topic subscriptions of a realm, one subscription is shared by every session subscribed to the same topic and match policy.
+#[derive(Default)]
+pub(crate) struct Broker {
+    subscriptions: HashMap<u64, Subscription>,
+    topics: HashMap<(String, MatchPolicy), u64>,
+    trie: UriTrie,
+    /// Last publication to each topic that asked to be retained, handed to later subscribers with `get_retained`.
+    retained: HashMap<String, Publication>,
+    /// Most recent publications to each topic, oldest first.
+    history: HashMap<String, VecDeque<Publication>>,
+    /// Publications kept per topic for `wamp.subscription.get_events`, 0 keeps none.
+    pub(crate) history_limit: usize,
+    /// Publications recorded so far, the next one's sequence.
+    recorded: u64
+}
+
+impl Broker {
+    /// Adds the session to the pattern's subscription, creating it with `id` when it does not exist yet.
+    pub(crate) fn subscribe(&mut self, session: u64, topic: &str, policy: MatchPolicy, id: impl FnOnce() -> u64) -> u64 {
+        let subscription = match self.topics.get(&(topic.to_string(), policy)) {
+            Some(subscription) => *subscription,
+            None => {
+                let subscription = id();
+                self.topics.insert((topic.to_string(), policy), subscription);
+                self.trie.insert(topic, policy, subscription);
+                self.subscriptions.insert(subscription, Subscription {
+                    id: subscription,
+                    topic: topic.to_string(),
+                    policy,
+                    subscribers: HashSet::new()
+                });
+                subscription
+            }
+        };
+        self.subscriptions.get_mut(&subscription).unwrap().subscribers.insert(session);
+        subscription
+    }
+
+    /// Removes the session from the subscription, returns false when the session was not subscribed.
+    pub(crate) fn unsubscribe(&mut self, session: u64, subscription: u64) -> bool {
+        let removed = match self.subscriptions.get_mut(&subscription) {
+            Some(entry) => entry.subscribers.remove(&session),
+            None => false
+        };
+        self.prune(subscription);
+        removed
+    }
+
+    /// Every subscription an event published to `topic` goes to.
+    pub(crate) fn matches(&self, topic: &str) -> Vec<&Subscription> {
+        self.trie.matches(topic).iter().filter_map(|subscription| self.subscriptions.get(subscription)).collect()
+    }
+
+    pub(crate) fn subscription(&self, subscription: u64) -> Option<&Subscription> {
+        self.subscriptions.get(&subscription)
+    }
+
+    /// The subscription to exactly this topic pattern and policy.
+    pub(crate) fn lookup(&self, topic: &str, policy: MatchPolicy) -> Option<u64> {
+        self.topics.get(&(topic.to_string(), policy)).copied()
+    }
+
+    pub(crate) fn list(&self) -> Listing {
+        let mut listing = Listing::default();
+        for subscription in self.subscriptions.values() {
+            listing.push(subscription.policy, subscription.id);
+        }
+        listing
+    }
+
+    /// Subscriptions the session is one of the subscribers of.
+    pub(crate) fn subscriptions_of(&self, session: u64) -> Vec<u64> {
+        self.subscriptions.values()
+            .filter(|subscription| subscription.subscribers.contains(&session))
+            .map(|subscription| subscription.id)
+            .collect()
+    }
+
+    /// Adds the publication to the topic's history, dropping the oldest one once the limit is reached,
+    /// and keeps it as the topic's retained event when the publisher asked for it.
+    pub(crate) fn record(&mut self, topic: &str, mut publication: Publication) {
+        publication.sequence = self.recorded;
+        self.recorded += 1;
+        if self.history_limit > 0 {
+            let history = self.history.entry(topic.to_string()).or_default();
+            if history.len() == self.history_limit {
+                history.pop_front();
+            }
+            history.push_back(publication.clone());
+        }
+        if publication.options.retain {
+            self.retained.insert(topic.to_string(), publication);
+        }
+    }
+
+    /// Retained publications of every topic the pattern matches, with the topic they went to.
+    pub(crate) fn retained(&self, pattern: &str, policy: MatchPolicy) -> Vec<(&str, &Publication)> {
+        self.retained.iter()
+            .filter(|(topic, _)| policy.matches(pattern, topic))
+            .map(|(topic, retained)| (topic.as_str(), retained))
+            .collect()
+    }
+
+    /// Kept publications to topics the subscription matches, oldest first.
+    pub(crate) fn history(&self, subscription: u64) -> Option<Vec<(&str, &Publication)>> {
+        let subscription = self.subscriptions.get(&subscription)?;
+        let mut history: Vec<(&str, &Publication)> = self.history.iter()
+            .filter(|(topic, _)| subscription.policy.matches(&subscription.topic, topic))
+            .flat_map(|(topic, publications)| publications.iter().map(|publication| (topic.as_str(), publication)))
+            .collect();
+        history.sort_by_key(|(_, publication)| publication.sequence);
+        Some(history)
+    }
+
+    pub(crate) fn remove_session(&mut self, session: u64) {
+        let subscriptions: Vec<u64> = self.subscriptions.values_mut()
+            .filter_map(|subscription| subscription.subscribers.remove(&session).then_some(subscription.id))
+            .collect();
+        for subscription in subscriptions {
+            self.prune(subscription);
+        }
+    }
+
+    fn prune(&mut self, subscription: u64) {
+        if let Some(entry) = self.subscriptions.get(&subscription) {
+            if entry.subscribers.is_empty() {
+                self.topics.remove(&(entry.topic.clone(), entry.policy));
+                self.trie.remove(&entry.topic, entry.policy, subscription);
+                self.subscriptions.remove(&subscription);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+    use core::protocol::{MatchPolicy, messages::PublishOptions};
+    use super::{Broker, Publication};
+
+    #[test]
+    fn shared_subscription() {
+        let mut broker = Broker::default();
+        let first = broker.subscribe(1, "com.myapp.topic", MatchPolicy::Exact, || 10);
+        let second = broker.subscribe(2, "com.myapp.topic", MatchPolicy::Exact, || 11);
+        assert_eq!(first, second);
+        assert_eq!(broker.matches("com.myapp.topic")[0].subscribers.len(), 2);
+        assert!(broker.unsubscribe(1, first));
+        assert!(!broker.unsubscribe(1, first));
+        broker.remove_session(2);
+        assert!(broker.matches("com.myapp.topic").is_empty());
+    }
+
+    #[test]
+    fn pattern_subscriptions() {
+        let mut broker = Broker::default();
+        let exact = broker.subscribe(1, "com.myapp.topic", MatchPolicy::Exact, || 10);
+        let prefix = broker.subscribe(1, "com.myapp.topic", MatchPolicy::Prefix, || 11);
+        let wildcard = broker.subscribe(2, "com..topic", MatchPolicy::Wildcard, || 12);
+        assert_ne!(exact, prefix);
+        let matched: Vec<u64> = broker.matches("com.myapp.topic").iter().map(|subscription| subscription.id).collect();
+        assert_eq!(matched, vec![exact, prefix, wildcard]);
+        assert_eq!(broker.lookup("com.myapp.topic", MatchPolicy::Prefix), Some(prefix));
+        assert_eq!(broker.list().wildcard, [wildcard]);
+        let mut subscriptions = broker.subscriptions_of(1);
+        subscriptions.sort_unstable();
+        assert_eq!(subscriptions, [exact, prefix]);
+        broker.remove_session(1);
+        let matched: Vec<u64> = broker.matches("com.myapp.topic").iter().map(|subscription| subscription.id).collect();
+        assert_eq!(matched, vec![wildcard]);
+    }
+
+    #[test]
+    fn retained() {
+        let mut broker = Broker::default();
+        broker.record("com.myapp.topic", publication(1, true));
+        broker.record("com.myapp.topic", publication(2, true));
+        broker.record("com.myapp.topic", publication(3, false));
+        broker.record("com.other.topic", publication(4, true));
+        let exact = broker.retained("com.myapp.topic", MatchPolicy::Exact);
+        assert_eq!(exact.len(), 1);
+        assert_eq!(exact[0].1.publication, 2);
+        assert_eq!(broker.retained("com..topic", MatchPolicy::Wildcard).len(), 2);
+        assert!(broker.retained("org.", MatchPolicy::Prefix).is_empty());
+    }
+
+    #[test]
+    fn history() {
+        let mut broker = Broker { history_limit: 2, ..Default::default() };
+        let subscription = broker.subscribe(1, "com.myapp.", MatchPolicy::Prefix, || 10);
+        for id in 1..=3 {
+            broker.record("com.myapp.topic", publication(id, false));
+        }
+        broker.record("com.myapp.other", publication(4, false));
+        let history: Vec<u64> = broker.history(subscription).unwrap().iter().map(|(_, publication)| publication.publication).collect();
+        assert_eq!(history, [2, 3, 4]);
+        assert!(broker.history(11).is_none());
+    }
+
+    #[test]
+    fn history_order() {
+        let mut broker = Broker { history_limit: 5, ..Default::default() };
+        let subscription = broker.subscribe(1, "com.myapp.", MatchPolicy::Prefix, || 10);
+        broker.record("com.myapp.topic", publication(30, false));
+        broker.record("com.myapp.other", publication(10, false));
+        broker.record("com.myapp.topic", publication(20, false));
+        let history: Vec<u64> = broker.history(subscription).unwrap().iter().map(|(_, publication)| publication.publication).collect();
+        assert_eq!(history, [30, 10, 20]);
+    }
+
+    fn publication(publication: u64, retain: bool) -> Publication {
+        let options = match retain {
+            true => PublishOptions::new().retain(),
+            false => PublishOptions::new()
+        };
+        Publication { publication, sequence: 0, disclosed: None, options, args: json!([publication]), kwargs: Value::Null }
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use rand::Rng;
+use core::{error::WampErrorUri, protocol::{MatchPolicy, messages::InvokePolicy, meta::Listing}};
+
+use super::trie::UriTrie;
+
+pub(crate) struct Registration {
+    pub(crate) id: u64,
+    pub(crate) procedure: String,
+    pub(crate) policy: MatchPolicy,
+    pub(crate) invoke: InvokePolicy,
+    /// Sessions sharing the registration, in the order they registered.
+    pub(crate) callees: Vec<u64>,
+    /// Index of the callee a round robin registration invokes next.
+    next: usize
+}
+
+impl Registration {
+    /// Picks the callee the next invocation goes to.
+    fn select(&mut self) -> u64 {
+        match self.invoke {
+            InvokePolicy::Single | InvokePolicy::First => self.callees[0],
+            InvokePolicy::Last => self.callees[self.callees.len() - 1],
+            InvokePolicy::Random => self.callees[rand::thread_rng().gen_range(0..self.callees.len())],
+            InvokePolicy::RoundRobin => {
+                let callee = self.callees[self.next % self.callees.len()];
+                self.next = (self.next + 1) % self.callees.len();
+                callee
+            }
+        }
+    }
+}
+
+/// A call forwarded to a callee, keyed by the invocation's request ID.
+pub(crate) struct PendingCall {
+    pub(crate) caller: u64,
+    pub(crate) request_id: u64,
+    pub(crate) registration: u64,
+    pub(crate) callee: u64,
+    /// The caller asked for progressive results and the callee can produce them.
+    pub(crate) progress: bool
+}
+
+/// Procedure registrations of a realm and the calls that are waiting on a callee.
+#[derive(Default)]
+pub(crate) struct Dealer {
+    registrations: HashMap<u64, Registration>,
+    procedures: HashMap<(String, MatchPolicy), u64>,
+    trie: UriTrie,
+    pub(crate) invocations: HashMap<u64, PendingCall>
+}
+
+impl Dealer {
+    /// Registers the procedure pattern for the callee, sharing the registration when both sides allow it.
+    /// Fails with the error URI to send back when the registration can not be shared with this callee.
+    pub(crate) fn register(&mut self, callee: u64, procedure: &str, policy: MatchPolicy, invoke: InvokePolicy, id: impl FnOnce() -> u64) -> Result<u64, WampErrorUri> {
+        if let Some(registration) = self.procedures.get(&(procedure.to_string(), policy)) {
+            let entry = self.registrations.get_mut(registration).unwrap();
+            if entry.invoke == InvokePolicy::Single || invoke == InvokePolicy::Single || entry.callees.contains(&callee) {
+                return Err(WampErrorUri::ProcedureAlreadyExists);
+            }
+            if entry.invoke != invoke {
+                return Err(WampErrorUri::ProcedureExistsWithDifferentInvocationPolicy);
+            }
+            entry.callees.push(callee);
+            return Ok(entry.id);
+        }
+        let registration = id();
+        self.procedures.insert((procedure.to_string(), policy), registration);
+        self.trie.insert(procedure, policy, registration);
+        self.registrations.insert(registration, Registration {
+            id: registration,
+            procedure: procedure.to_string(),
+            policy,
+            invoke,
+            callees: vec![callee],
+            next: 0
+        });
+        Ok(registration)
+    }
+
+    /// Removes the callee from the registration, returns false when the callee is not part of it.
+    pub(crate) fn unregister(&mut self, callee: u64, registration: u64) -> bool {
+        let Some(entry) = self.registrations.get_mut(&registration) else {
+            return false;
+        };
+        let Some(index) = entry.callees.iter().position(|entry| *entry == callee) else {
+            return false;
+        };
+        entry.callees.remove(index);
+        if entry.callees.is_empty() {
+            self.procedures.remove(&(entry.procedure.clone(), entry.policy));
+            self.trie.remove(&entry.procedure, entry.policy, registration);
+            self.registrations.remove(&registration);
+        } else if index < entry.next {
+            entry.next -= 1;
+        }
+        true
+    }
+
+    pub(crate) fn registration(&self, registration: u64) -> Option<&Registration> {
+        self.registrations.get(&registration)
+    }
+
+    /// The registration of exactly this procedure pattern and policy.
+    pub(crate) fn lookup(&self, procedure: &str, policy: MatchPolicy) -> Option<u64> {
+        self.procedures.get(&(procedure.to_string(), policy)).copied()
+    }
+
+    pub(crate) fn list(&self) -> Listing {
+        let mut listing = Listing::default();
+        for registration in self.registrations.values() {
+            listing.push(registration.policy, registration.id);
+        }
+        listing
+    }
+
+    /// Registrations the session is one of the callees of.
+    pub(crate) fn registrations_of(&self, session: u64) -> Vec<u64> {
+        self.registrations.values()
+            .filter(|registration| registration.callees.contains(&session))
+            .map(|registration| registration.id)
+            .collect()
+    }
+
+    /// The registration a call to `procedure` goes to.
+    /// An exact registration wins over a prefix one, which wins over a wildcard one.
+    pub(crate) fn matched(&self, procedure: &str) -> Option<u64> {
+        self.trie.matches(procedure).first().copied()
+    }
+
+    /// The registration a call to `procedure` goes to and the callee it is invoked on.
+    pub(crate) fn route(&mut self, procedure: &str) -> Option<(&Registration, u64)> {
+        let registration = self.matched(procedure)?;
+        let entry = self.registrations.get_mut(&registration)?;
+        let callee = entry.select();
+        Some((entry, callee))
+    }
+
+    /// Drops the session from every registration and returns the calls it can no longer answer.
+    pub(crate) fn remove_session(&mut self, session: u64) -> Vec<PendingCall> {
+        for registration in self.registrations_of(session) {
+            self.unregister(session, registration);
+        }
+        self.invocations.retain(|_, call| call.caller != session);
+        let orphaned: Vec<u64> = self.invocations.iter()
+            .filter(|(_, call)| call.callee == session)
+            .map(|(request_id, _)| *request_id)
+            .collect();
+        orphaned.into_iter().filter_map(|request_id| self.invocations.remove(&request_id)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{error::WampErrorUri, protocol::{MatchPolicy, messages::InvokePolicy}};
+    use super::{Dealer, PendingCall};
+
+    #[test]
+    fn register_and_remove() {
+        let mut dealer = Dealer::default();
+        assert_eq!(dealer.register(1, "com.myapp.add", MatchPolicy::Exact, InvokePolicy::Single, || 20), Ok(20));
+        assert_eq!(dealer.register(2, "com.myapp.add", MatchPolicy::Exact, InvokePolicy::Single, || 21), Err(WampErrorUri::ProcedureAlreadyExists));
+        assert!(!dealer.unregister(2, 20));
+        assert_eq!(dealer.route("com.myapp.add").unwrap().1, 1);
+        dealer.invocations.insert(5, PendingCall { caller: 2, request_id: 7, registration: 20, callee: 1, progress: false });
+        let orphaned = dealer.remove_session(1);
+        assert_eq!(orphaned.len(), 1);
+        assert_eq!(orphaned[0].request_id, 7);
+        assert!(dealer.route("com.myapp.add").is_none());
+    }
+
+    #[test]
+    fn shared_registration() {
+        let mut dealer = Dealer::default();
+        for callee in 1..=3 {
+            assert_eq!(dealer.register(callee, "com.myapp.add", MatchPolicy::Exact, InvokePolicy::RoundRobin, || 20), Ok(20));
+        }
+        assert_eq!(dealer.register(1, "com.myapp.add", MatchPolicy::Exact, InvokePolicy::RoundRobin, || 21), Err(WampErrorUri::ProcedureAlreadyExists));
+        assert_eq!(dealer.register(4, "com.myapp.add", MatchPolicy::Exact, InvokePolicy::Last, || 21), Err(WampErrorUri::ProcedureExistsWithDifferentInvocationPolicy));
+        let callees: Vec<u64> = (0..4).map(|_| dealer.route("com.myapp.add").unwrap().1).collect();
+        assert_eq!(callees, [1, 2, 3, 1]);
+        assert!(dealer.unregister(1, 20));
+        let callees: Vec<u64> = (0..3).map(|_| dealer.route("com.myapp.add").unwrap().1).collect();
+        assert_eq!(callees, [2, 3, 2]);
+
+        dealer.register(1, "com.myapp.sub", MatchPolicy::Exact, InvokePolicy::Last, || 30).unwrap();
+        dealer.register(2, "com.myapp.sub", MatchPolicy::Exact, InvokePolicy::Last, || 31).unwrap();
+        assert_eq!(dealer.route("com.myapp.sub").unwrap().1, 2);
+        dealer.remove_session(2);
+        assert_eq!(dealer.route("com.myapp.sub").unwrap().1, 1);
+    }
+
+    #[test]
+    fn pattern_registrations() {
+        let mut dealer = Dealer::default();
+        dealer.register(1, "com.myapp.", MatchPolicy::Prefix, InvokePolicy::Single, || 20).unwrap();
+        dealer.register(2, "com..add", MatchPolicy::Wildcard, InvokePolicy::Single, || 21).unwrap();
+        dealer.register(3, "com.myapp.add", MatchPolicy::Exact, InvokePolicy::Single, || 22).unwrap();
+        assert_eq!(dealer.route("com.myapp.add").unwrap().0.id, 22);
+        assert_eq!(dealer.route("com.myapp.sub").unwrap().0.id, 20);
+        let (registration, callee) = dealer.route("com.other.add").unwrap();
+        assert_eq!((registration.policy, callee), (MatchPolicy::Wildcard, 2));
+        assert!(dealer.route("org.myapp.add").is_none());
+        assert_eq!(dealer.lookup("com.myapp.", MatchPolicy::Prefix), Some(20));
+        assert_eq!(dealer.matched("com.other.add"), Some(21));
+        assert_eq!(dealer.list().exact, [22]);
+        assert_eq!(dealer.registrations_of(3), [22]);
+    }
+}
@@ -0,0 +1,9 @@
+mod broker;
+mod dealer;
+mod session;
+mod rawsocket;
+mod trie;
+pub mod realm;
+pub use realm::Realm;
+pub mod router;
+pub use router::Router;
@@ -0,0 +1,84 @@
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tungstenite::Message;
+use core::{error::Error, protocol::{Serializer, rawsocket::{FrameHeader, FrameType, Handshake, HandshakeError}}};
+
+/// Answers the client's handshake with the router's own receive limit, or refuses it.
+pub(crate) async fn accept<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S, supported: &[Serializer], max_length: u32) -> Result<Handshake, Error> {
+    let mut request = [0; 4];
+    stream.read_exact(&mut request).await?;
+    let refusal = match Handshake::decode(request) {
+        Ok(handshake) if supported.contains(&handshake.serializer) => {
+            stream.write_all(&Handshake::new(handshake.serializer).max_length(max_length).encode()).await?;
+            return Ok(handshake);
+        },
+        Ok(_) => HandshakeError::SerializerUnsupported,
+        // Not a RawSocket peer, there is nobody to explain the refusal to.
+        Err(HandshakeError::InvalidMagic) => return Err(HandshakeError::InvalidMagic.into()),
+        Err(error) => error
+    };
+    stream.write_all(&refusal.encode()).await?;
+    Err(refusal.into())
+}
+
+/// Reads the next frame, rejecting payloads longer than the router announced.
+pub(crate) async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R, max_length: u32) -> Result<(FrameType, Vec<u8>), Error> {
+    let mut header = [0; 4];
+    reader.read_exact(&mut header).await?;
+    let header = FrameHeader::decode(header)?;
+    if header.length > max_length {
+        return Err(Error::Error("Client sent a message longer than the RawSocket handshake allows."));
+    }
+    let mut payload = vec![0; header.length as usize];
+    reader.read_exact(&mut payload).await?;
+    Ok((header.frame_type, payload))
+}
+
+/// Writes a queued WebSocket style message as the matching RawSocket frame, close messages are dropped.
+/// Fails when the payload is longer than the client announced it accepts.
+pub(crate) async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, message: Message, max_length: u32) -> Result<(), Error> {
+    let frame_type = match message {
+        Message::Text(_) | Message::Binary(_) => FrameType::Message,
+        Message::Ping(_) => FrameType::Ping,
+        Message::Pong(_) => FrameType::Pong,
+        Message::Close(_) | Message::Frame(_) => return Ok(())
+    };
+    let payload = message.into_data();
+    if payload.len() > max_length as usize {
+        return Err(Error::Error("Message exceeds the length the client accepts over RawSocket."));
+    }
+    writer.write_all(&FrameHeader::new(frame_type, payload.len())?.encode()).await?;
+    writer.write_all(&payload).await?;
+    Ok(writer.flush().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+    use tungstenite::Message;
+    use core::protocol::{Serializer, rawsocket::{FrameType, Handshake, HandshakeError}};
+    use super::{accept, read_frame, write_frame};
+
+    #[test]
+    fn handshake_and_frames() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        runtime.block_on(async {
+            let (mut client, mut router) = duplex(1024);
+            client.write_all(&Handshake::new(Serializer::Cbor).max_length(512).encode()).await.unwrap();
+            let handshake = accept(&mut router, &[Serializer::Cbor], 1 << 16).await.unwrap();
+            assert_eq!(handshake, Handshake::new(Serializer::Cbor).max_length(512));
+            let mut reply = [0; 4];
+            client.read_exact(&mut reply).await.unwrap();
+            assert_eq!(Handshake::decode(reply), Ok(Handshake::new(Serializer::Cbor).max_length(1 << 16)));
+
+            write_frame(&mut router, Message::Pong(vec![7]), handshake.max_length).await.unwrap();
+            assert_eq!(read_frame(&mut client, 512).await.unwrap(), (FrameType::Pong, vec![7]));
+            assert!(write_frame(&mut router, Message::Binary(vec![0; 513]), handshake.max_length).await.is_err());
+
+            let (mut client, mut router) = duplex(1024);
+            client.write_all(&Handshake::new(Serializer::Json).encode()).await.unwrap();
+            assert!(accept(&mut router, &[Serializer::Cbor], 1 << 16).await.is_err());
+            client.read_exact(&mut reply).await.unwrap();
+            assert_eq!(Handshake::decode(reply), Err(HandshakeError::SerializerUnsupported));
+        });
+    }
+}
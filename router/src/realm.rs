@@ -0,0 +1,1059 @@
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+use serde_json::{Value, json};
+use core::{error::{Error, WampErrorUri}, protocol::{Feature, IdGenerator, MatchPolicy, RandomIdGenerator, meta::{self, RegistrationDetails, SubscriptionDetails}, roles::Roles}, protocol::messages::{Call, Cancel, CancelMode, Event, Interrupt, Invocation, Messages, Publish, PublishOptions, Published, Register, Registered, Subscribe, Subscribed, Unregister, Unregistered, Unsubscribe, Unsubscribed, WampError, WampErrorEvent, WampResult, Yield}};
+
+use super::{broker::{Broker, Publication, Subscription}, dealer::{Dealer, PendingCall, Registration}, session::Session};
+
+#[derive(Default)]
+struct RealmState {
+    sessions: HashMap<u64, Session>,
+    broker: Broker,
+    dealer: Dealer
+}
+
+/// Routing domain joined through `Hello`, every realm has its own broker and dealer.
+pub struct Realm {
+    pub name: String,
+    /// Whether callers and publishers may have their identity disclosed with `disclose_me`.
+    pub disclose_me: bool,
+    /// Router scope IDs, for subscriptions, registrations and the request IDs of invocations.
+    ids: IdGenerator,
+    state: Mutex<RealmState>
+}
+
+impl Realm {
+    pub fn new<T: ToString>(name: T) -> Self {
+        Self {
+            name: name.to_string(),
+            disclose_me: true,
+            ids: IdGenerator::new(),
+            state: Mutex::new(RealmState::default())
+        }
+    }
+
+    /// Allows or forbids `disclose_me`, requests asking for it in a realm that forbids it fail with `wamp.error.disclose_me.not_allowed`.
+    pub fn disclose_me(mut self, allowed: bool) -> Self {
+        self.disclose_me = allowed;
+        self
+    }
+
+    /// Keeps the latest `limit` publications of every topic for `wamp.subscription.get_events`, none are kept by default.
+    pub fn event_history(self, limit: usize) -> Self {
+        self.state.lock().unwrap().broker.history_limit = limit;
+        self
+    }
+
+    pub(crate) fn keeps_history(&self) -> bool {
+        self.state.lock().unwrap().broker.history_limit > 0
+    }
+
+    fn next_id(&self) -> u64 {
+        self.ids.next()
+    }
+
+    /// Attaches a new session and returns its ID, drawn at random as session IDs are in the global scope.
+    /// The session is anonymous, its authid is the ID.
+    pub(crate) fn join(&self, mut session: Session) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let id = loop {
+            let id = RandomIdGenerator.next();
+            if !state.sessions.contains_key(&id) {
+                break id;
+            }
+        };
+        session.authid = Some(id.to_string());
+        let details = session.details(id);
+        state.sessions.insert(id, session);
+        self.meta_event(&state, meta::SESSION_ON_JOIN, json!([Value::from(details)]));
+        id
+    }
+
+    pub(crate) fn session(&self, id: u64) -> Option<Session> {
+        self.state.lock().unwrap().sessions.get(&id).cloned()
+    }
+
+    /// Detaches the session, dropping its subscriptions and registrations, interrupting calls it made and failing calls it was answering.
+    pub(crate) fn leave(&self, session: u64) {
+        let mut state = self.state.lock().unwrap();
+        let Some(left) = state.sessions.remove(&session) else {
+            return;
+        };
+        let subscriptions = state.broker.subscriptions_of(session);
+        state.broker.remove_session(session);
+        for subscription in subscriptions {
+            self.meta_event(&state, meta::SUBSCRIPTION_ON_UNSUBSCRIBE, json!([session, subscription]));
+            if state.broker.subscription(subscription).is_none() {
+                self.meta_event(&state, meta::SUBSCRIPTION_ON_DELETE, json!([session, subscription]));
+            }
+        }
+        let registrations = state.dealer.registrations_of(session);
+        // Calls the session was waiting on are interrupted, no one is left to take their result.
+        let abandoned: Vec<(u64, u64)> = state.dealer.invocations.iter()
+            .filter(|(_, call)| call.caller == session)
+            .map(|(invocation, call)| (*invocation, call.callee))
+            .collect();
+        for (invocation, callee) in abandoned {
+            if let Some(callee) = state.sessions.get(&callee).filter(|callee| callee.supports(Roles::Callee, Feature::CallCanceling)) {
+                let _ = callee.send(Interrupt::new(invocation, CancelMode::KillNoWait));
+            }
+        }
+        for call in state.dealer.remove_session(session) {
+            if let Some(caller) = state.sessions.get(&call.caller) {
+                let _ = caller.send(error(WampErrorEvent::Call, call.request_id, WampErrorUri::Canceled));
+            }
+        }
+        for registration in registrations {
+            self.meta_event(&state, meta::REGISTRATION_ON_UNREGISTER, json!([session, registration]));
+            if state.dealer.registration(registration).is_none() {
+                self.meta_event(&state, meta::REGISTRATION_ON_DELETE, json!([session, registration]));
+            }
+        }
+        self.meta_event(&state, meta::SESSION_ON_LEAVE, json!([session, left.authid, left.authrole]));
+    }
+
+    /// Publishes an event of the router's meta API to the sessions subscribed to it.
+    fn meta_event(&self, state: &RealmState, topic: &str, args: Value) {
+        let publication = RandomIdGenerator.next();
+        for subscription in state.broker.matches(topic) {
+            let event = Event {
+                subscription: subscription.id,
+                publication,
+                details: match subscription.policy {
+                    MatchPolicy::Exact => json!({}),
+                    _ => json!({"topic": topic})
+                },
+                args: args.clone(),
+                kwargs: Value::Null
+            };
+            for subscriber in subscription.subscribers.iter().filter_map(|id| state.sessions.get(id)) {
+                let _ = subscriber.send(event.clone());
+            }
+        }
+    }
+
+    /// Routes a message received from an established session.
+    pub(crate) fn handle(self: &Arc<Self>, session: u64, message: Messages) -> Result<(), Error> {
+        match message {
+            Messages::Subscribe(subscribe) => self.subscribe(session, subscribe),
+            Messages::Unsubscribe(unsubscribe) => self.unsubscribe(session, unsubscribe),
+            Messages::Publish(publish) => self.publish(session, publish),
+            Messages::Register(register) => self.register(session, register),
+            Messages::Unregister(unregister) => self.unregister(session, unregister),
+            Messages::Call(call) => self.call(session, call),
+            Messages::Cancel(cancel) => self.cancel(session, cancel),
+            Messages::Yield(r#yield) => self.r#yield(session, r#yield),
+            Messages::Error(error) if error.event == WampErrorEvent::Invocation => self.invocation_error(session, error),
+            message => Err(Error::InvalidFrameReceived(message))
+        }
+    }
+
+    fn subscribe(&self, session: u64, subscribe: Subscribe) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        let policy = match subscribe.match_policy() {
+            Some(policy) => policy,
+            None => return state.sessions[&session].send(error(WampErrorEvent::Subscribe, subscribe.request_id, WampErrorUri::InvalidUri))
+        };
+        let created = state.broker.lookup(&subscribe.topic, policy).is_none();
+        let subscription = state.broker.subscribe(session, &subscribe.topic, policy, || self.next_id());
+        let subscriber = &state.sessions[&session];
+        subscriber.send(Subscribed {
+            request_id: subscribe.request_id,
+            subscription
+        })?;
+        if created {
+            let details = SubscriptionDetails { id: subscription, uri: subscribe.topic.to_string(), match_policy: policy };
+            self.meta_event(&state, meta::SUBSCRIPTION_ON_CREATE, json!([session, Value::from(details)]));
+        }
+        self.meta_event(&state, meta::SUBSCRIPTION_ON_SUBSCRIBE, json!([session, subscription]));
+        if !subscribe.get_retained() {
+            return Ok(());
+        }
+        for (topic, retained) in state.broker.retained(&subscribe.topic, policy) {
+            if !subscriber.admitted(session, &retained.options) {
+                continue;
+            }
+            let mut details = json!({"retained": true});
+            if policy != MatchPolicy::Exact {
+                details["topic"] = json!(topic);
+            }
+            if let Some(publisher) = &retained.disclosed {
+                publisher.disclose(&mut details, "publisher");
+            }
+            subscriber.send(Event {
+                subscription,
+                publication: retained.publication,
+                details,
+                args: retained.args.clone(),
+                kwargs: retained.kwargs.clone()
+            })?;
+        }
+        Ok(())
+    }
+
+    fn unsubscribe(&self, session: u64, unsubscribe: Unsubscribe) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        if !state.broker.unsubscribe(session, unsubscribe.subscription) {
+            return state.sessions[&session].send(error(WampErrorEvent::Unsubscribe, unsubscribe.request_id, WampErrorUri::NoSuchSubscription));
+        }
+        state.sessions[&session].send(Unsubscribed { request_id: unsubscribe.request_id })?;
+        self.meta_event(&state, meta::SUBSCRIPTION_ON_UNSUBSCRIBE, json!([session, unsubscribe.subscription]));
+        if state.broker.subscription(unsubscribe.subscription).is_none() {
+            self.meta_event(&state, meta::SUBSCRIPTION_ON_DELETE, json!([session, unsubscribe.subscription]));
+        }
+        Ok(())
+    }
+
+    fn publish(&self, session: u64, publish: Publish) -> Result<(), Error> {
+        let publication = RandomIdGenerator.next();
+        let options = publish.typed_options();
+        let mut state = self.state.lock().unwrap();
+        if options.disclose_me && !self.disclose_me {
+            return state.sessions[&session].send(error(WampErrorEvent::Publish, publish.request_id, WampErrorUri::DiscloseMeNotAllowed));
+        }
+        let publisher = options.disclose_me.then(|| state.sessions[&session].identity(session));
+        for subscription in state.broker.matches(&publish.topic) {
+            let mut details = json!({});
+            // Pattern subscribers cannot tell the topic from the subscription.
+            if subscription.policy != MatchPolicy::Exact {
+                details["topic"] = json!(publish.topic);
+            }
+            if let Some(publisher) = &publisher {
+                publisher.disclose(&mut details, "publisher");
+            }
+            let event = Event {
+                subscription: subscription.id,
+                publication,
+                details,
+                args: publish.args.clone(),
+                kwargs: publish.kwargs.clone()
+            };
+            for (id, subscriber) in subscription.subscribers.iter().filter_map(|id| Some((*id, state.sessions.get(id)?))) {
+                if admits(&options, session, id, subscriber) {
+                    let _ = subscriber.send(event.clone());
+                }
+            }
+        }
+        let acknowledge = options.acknowledge;
+        state.broker.record(&publish.topic, Publication {
+            publication,
+            sequence: 0,
+            disclosed: publisher,
+            options,
+            args: publish.args,
+            kwargs: publish.kwargs
+        });
+        if acknowledge {
+            state.sessions[&session].send(Published {
+                request_id: publish.request_id,
+                publication
+            })?;
+        }
+        Ok(())
+    }
+
+    fn register(&self, session: u64, register: Register) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        // The `wamp.` namespace is the router's own, its meta procedures are answered before any registration is looked at.
+        if register.procedure.starts_with("wamp.") {
+            return state.sessions[&session].send(error(WampErrorEvent::Register, register.request_id, WampErrorUri::InvalidUri));
+        }
+        let Some(policy) = register.match_policy() else {
+            return state.sessions[&session].send(error(WampErrorEvent::Register, register.request_id, WampErrorUri::InvalidUri));
+        };
+        let Some(invoke) = register.invoke_policy() else {
+            return state.sessions[&session].send(error(WampErrorEvent::Register, register.request_id, WampErrorUri::InvalidArgument));
+        };
+        let created = state.dealer.lookup(&register.procedure, policy).is_none();
+        let registration = match state.dealer.register(session, &register.procedure, policy, invoke, || self.next_id()) {
+            Ok(registration) => registration,
+            Err(uri) => return state.sessions[&session].send(error(WampErrorEvent::Register, register.request_id, uri))
+        };
+        state.sessions[&session].send(Registered {
+            request_id: register.request_id,
+            registration
+        })?;
+        if created {
+            let details = RegistrationDetails { id: registration, uri: register.procedure.into(), match_policy: policy, invoke };
+            self.meta_event(&state, meta::REGISTRATION_ON_CREATE, json!([session, Value::from(details)]));
+        }
+        self.meta_event(&state, meta::REGISTRATION_ON_REGISTER, json!([session, registration]));
+        Ok(())
+    }
+
+    fn unregister(&self, session: u64, unregister: Unregister) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        if !state.dealer.unregister(session, unregister.registration) {
+            return state.sessions[&session].send(error(WampErrorEvent::Unregister, unregister.request_id, WampErrorUri::NoSuchRegistration));
+        }
+        state.sessions[&session].send(Unregistered { request_id: unregister.request_id })?;
+        self.meta_event(&state, meta::REGISTRATION_ON_UNREGISTER, json!([session, unregister.registration]));
+        if state.dealer.registration(unregister.registration).is_none() {
+            self.meta_event(&state, meta::REGISTRATION_ON_DELETE, json!([session, unregister.registration]));
+        }
+        Ok(())
+    }
+
+    fn call(self: &Arc<Self>, session: u64, call: Call) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        // Later chunks of a progressive invocation reuse the request ID of the first one.
+        let continued = state.dealer.invocations.iter()
+            .find(|(_, pending)| pending.caller == session && pending.request_id == call.request_id)
+            .map(|(invocation, pending)| (*invocation, pending.registration, pending.callee));
+        if let Some((request_id, registration, callee)) = continued {
+            let sent = state.sessions[&callee].send(Invocation {
+                request_id,
+                registration,
+                details: match call.is_progress() {
+                    true => json!({"progress": true}),
+                    false => json!({})
+                },
+                args: call.args,
+                kwargs: call.kwargs
+            });
+            // The callee is leaving, the call can not be finished.
+            if sent.is_err() {
+                state.dealer.invocations.remove(&request_id);
+                return state.sessions[&session].send(error(WampErrorEvent::Call, call.request_id, WampErrorUri::Canceled));
+            }
+            return Ok(());
+        }
+        match self.meta(&state, session, &call) {
+            Some(Ok(args)) => return state.sessions[&session].send(WampResult {
+                request_id: call.request_id,
+                details: json!({}),
+                args,
+                kwargs: Value::Null
+            }),
+            Some(Err(uri)) => return state.sessions[&session].send(error(WampErrorEvent::Call, call.request_id, uri)),
+            None => {}
+        }
+
+        let (registration, callee, policy) = match state.dealer.route(&call.procedure) {
+            Some((registration, callee)) => (registration.id, callee, registration.policy),
+            None => return state.sessions[&session].send(error(WampErrorEvent::Call, call.request_id, WampErrorUri::NoSuchProcedure))
+        };
+        if call.disclose_me() && !self.disclose_me {
+            return state.sessions[&session].send(error(WampErrorEvent::Call, call.request_id, WampErrorUri::DiscloseMeNotAllowed));
+        }
+        if call.is_progress() && !state.sessions[&callee].supports(Roles::Callee, Feature::ProgressiveCallInvocations) {
+            return state.sessions[&session].send(error(WampErrorEvent::Call, call.request_id, WampErrorUri::FeatureNotSupported));
+        }
+        let request_id = self.next_id();
+        if let Some(timeout) = call.timeout() {
+            let realm = Arc::clone(self);
+            tokio::spawn(async move {
+                tokio::time::sleep(timeout).await;
+                realm.expire(request_id);
+            });
+        }
+        let progress = call.receive_progress() && state.sessions[&callee].supports(Roles::Callee, Feature::ProgressiveCallResults);
+        state.dealer.invocations.insert(request_id, PendingCall {
+            caller: session,
+            request_id: call.request_id,
+            registration,
+            callee,
+            progress
+        });
+        let mut details = json!({});
+        if policy != MatchPolicy::Exact {
+            details["procedure"] = json!(call.procedure);
+        }
+        if call.disclose_me() {
+            state.sessions[&session].identity(session).disclose(&mut details, "caller");
+        }
+        if progress {
+            details["receive_progress"] = json!(true);
+        }
+        if call.is_progress() {
+            details["progress"] = json!(true);
+        }
+        let sent = state.sessions[&callee].send(Invocation {
+            request_id,
+            registration,
+            details,
+            args: call.args,
+            kwargs: call.kwargs
+        });
+        // A callee that is leaving fails the call, not the caller's session.
+        if sent.is_err() {
+            state.dealer.invocations.remove(&request_id);
+            return state.sessions[&session].send(error(WampErrorEvent::Call, call.request_id, WampErrorUri::NoAvailableCallee));
+        }
+        Ok(())
+    }
+
+    /// Answers the procedures the router implements itself, None for any other procedure.
+    fn meta(&self, state: &RealmState, session: u64, call: &Call) -> Option<Result<Value, WampErrorUri>> {
+        match call.procedure.as_str() {
+            "wamp.subscription.get_events" => Some(get_events(state, session, call)),
+            "wamp.session.count" => Some(sessions(state, call).map(|sessions| json!([sessions.len()]))),
+            "wamp.session.list" => Some(sessions(state, call).map(|sessions| json!([sessions]))),
+            "wamp.session.get" => Some(session_get(state, call)),
+            "wamp.subscription.list" => Some(Ok(json!([Value::from(state.broker.list())]))),
+            "wamp.subscription.lookup" => Some(lookup(call).map(|(topic, policy)| json!([state.broker.lookup(topic, policy)]))),
+            "wamp.subscription.match" => Some(uri(call).map(|topic| json!([state.broker.matches(topic).iter().map(|subscription| subscription.id).collect::<Vec<u64>>()]))),
+            "wamp.subscription.get" => Some(subscription_get(state, call)),
+            "wamp.subscription.list_subscribers" => Some(subscription(state, call).map(|subscription| {
+                let mut subscribers: Vec<u64> = subscription.subscribers.iter().copied().collect();
+                subscribers.sort_unstable();
+                json!([subscribers])
+            })),
+            "wamp.registration.list" => Some(Ok(json!([Value::from(state.dealer.list())]))),
+            "wamp.registration.lookup" => Some(lookup(call).map(|(procedure, policy)| json!([state.dealer.lookup(procedure, policy)]))),
+            "wamp.registration.match" => Some(uri(call).map(|procedure| json!([state.dealer.matched(procedure)]))),
+            "wamp.registration.get" => Some(registration(state, call).map(|registration| json!([Value::from(RegistrationDetails {
+                id: registration.id,
+                uri: registration.procedure.clone(),
+                match_policy: registration.policy,
+                invoke: registration.invoke
+            })]))),
+            "wamp.registration.list_callees" => Some(registration(state, call).map(|registration| json!([registration.callees]))),
+            _ => None
+        }
+    }
+
+    /// Cancels a call the session is still waiting on, a callee without call canceling is never interrupted and every mode acts as `skip`.
+    fn cancel(&self, session: u64, cancel: Cancel) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        let mode = match cancel.mode() {
+            Some(mode) => mode,
+            None => return state.sessions[&session].send(error(WampErrorEvent::Cancel, cancel.request_id, WampErrorUri::InvalidArgument))
+        };
+        let pending = state.dealer.invocations.iter()
+            .find(|(_, call)| call.caller == session && call.request_id == cancel.request_id)
+            .map(|(invocation, call)| (*invocation, call.callee));
+        let (invocation, callee) = match pending {
+            Some(pending) => pending,
+            // The call was answered before the cancel arrived.
+            None => return Ok(())
+        };
+        let interrupt = mode != CancelMode::Skip && state.sessions.get(&callee).is_some_and(|callee| callee.supports(Roles::Callee, Feature::CallCanceling));
+        if interrupt {
+            let _ = state.sessions[&callee].send(Interrupt::new(invocation, mode));
+            if mode == CancelMode::Kill {
+                // The callee's error is forwarded once it answers.
+                return Ok(());
+            }
+        }
+        state.dealer.invocations.remove(&invocation);
+        state.sessions[&session].send(error(WampErrorEvent::Call, cancel.request_id, WampErrorUri::Canceled))
+    }
+
+    /// Fails a call that ran past its `options.timeout`, interrupting the callee when it supports call canceling.
+    fn expire(&self, invocation: u64) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(call) = state.dealer.invocations.remove(&invocation) {
+            if let Some(callee) = state.sessions.get(&call.callee).filter(|callee| callee.supports(Roles::Callee, Feature::CallCanceling)) {
+                let _ = callee.send(Interrupt::new(invocation, CancelMode::KillNoWait));
+            }
+            if let Some(caller) = state.sessions.get(&call.caller) {
+                let _ = caller.send(error(WampErrorEvent::Call, call.request_id, WampErrorUri::Timeout));
+            }
+        }
+    }
+
+    /// Forwards the callee's result, a progressive one keeps the call pending and is dropped when the caller did not ask for it.
+    fn r#yield(&self, session: u64, r#yield: Yield) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        let progress = r#yield.is_progress();
+        match state.dealer.invocations.remove(&r#yield.request_id) {
+            Some(call) if call.callee == session => {
+                if let Some(caller) = state.sessions.get(&call.caller).filter(|_| !progress || call.progress) {
+                    let _ = caller.send(WampResult {
+                        request_id: call.request_id,
+                        details: match progress {
+                            true => json!({"progress": true}),
+                            false => json!({})
+                        },
+                        args: r#yield.args,
+                        kwargs: r#yield.kwargs
+                    });
+                }
+                if progress {
+                    state.dealer.invocations.insert(r#yield.request_id, call);
+                }
+                Ok(())
+            },
+            Some(call) => {
+                state.dealer.invocations.insert(r#yield.request_id, call);
+                Ok(())
+            },
+            None => Ok(())
+        }
+    }
+
+    fn invocation_error(&self, session: u64, invocation_error: WampError) -> Result<(), Error> {
+        let mut state = self.state.lock().unwrap();
+        match state.dealer.invocations.remove(&invocation_error.request_id) {
+            Some(call) if call.callee == session => {
+                if let Some(caller) = state.sessions.get(&call.caller) {
+                    let _ = caller.send(WampError {
+                        event: WampErrorEvent::Call,
+                        request_id: call.request_id,
+                        details: invocation_error.details,
+                        error: invocation_error.error
+                    });
+                }
+                Ok(())
+            },
+            Some(call) => {
+                state.dealer.invocations.insert(invocation_error.request_id, call);
+                Ok(())
+            },
+            None => Ok(())
+        }
+    }
+}
+
+/// `wamp.subscription.get_events(subscription, limit = 10)`, the latest events of the subscription the caller may receive, oldest first.
+fn get_events(state: &RealmState, session: u64, call: &Call) -> Result<Value, WampErrorUri> {
+    let subscription = call.args[0].as_u64().ok_or(WampErrorUri::InvalidArgument)?;
+    let limit = match &call.args[1] {
+        Value::Null => 10,
+        limit => limit.as_u64().ok_or(WampErrorUri::InvalidArgument)? as usize
+    };
+    let history = state.broker.history(subscription).ok_or(WampErrorUri::NoSuchSubscription)?;
+    let caller = &state.sessions[&session];
+    let events: Vec<Value> = history.into_iter()
+        .filter(|(_, publication)| caller.admitted(session, &publication.options))
+        .map(|(topic, publication)| {
+            let mut event = json!({
+                "subscription": subscription,
+                "publication": publication.publication,
+                "topic": topic,
+                "args": publication.args,
+                "kwargs": publication.kwargs
+            });
+            if let Some(publisher) = &publication.disclosed {
+                publisher.disclose(&mut event, "publisher");
+            }
+            event
+        })
+        .collect();
+    Ok(json!([events[events.len().saturating_sub(limit)..]]))
+}
+
+/// Sessions of the realm, only those with one of the authroles in the first argument when it is given.
+fn sessions(state: &RealmState, call: &Call) -> Result<Vec<u64>, WampErrorUri> {
+    let authroles = match &call.args[0] {
+        Value::Null => None,
+        Value::Array(authroles) => Some(authroles),
+        _ => return Err(WampErrorUri::InvalidArgument)
+    };
+    let mut sessions: Vec<u64> = state.sessions.iter()
+        .filter(|(_, session)| authroles.is_none_or(|authroles| authroles.contains(&json!(session.authrole))))
+        .map(|(id, _)| *id)
+        .collect();
+    sessions.sort_unstable();
+    Ok(sessions)
+}
+
+fn session_get(state: &RealmState, call: &Call) -> Result<Value, WampErrorUri> {
+    let id = call.args[0].as_u64().ok_or(WampErrorUri::InvalidArgument)?;
+    let session = state.sessions.get(&id).ok_or(WampErrorUri::NoSuchSession)?;
+    Ok(json!([Value::from(session.details(id))]))
+}
+
+fn subscription<'a>(state: &'a RealmState, call: &Call) -> Result<&'a Subscription, WampErrorUri> {
+    let id = call.args[0].as_u64().ok_or(WampErrorUri::InvalidArgument)?;
+    state.broker.subscription(id).ok_or(WampErrorUri::NoSuchSubscription)
+}
+
+fn subscription_get(state: &RealmState, call: &Call) -> Result<Value, WampErrorUri> {
+    let subscription = subscription(state, call)?;
+    Ok(json!([Value::from(SubscriptionDetails {
+        id: subscription.id,
+        uri: subscription.topic.clone(),
+        match_policy: subscription.policy
+    })]))
+}
+
+fn registration<'a>(state: &'a RealmState, call: &Call) -> Result<&'a Registration, WampErrorUri> {
+    let id = call.args[0].as_u64().ok_or(WampErrorUri::InvalidArgument)?;
+    state.dealer.registration(id).ok_or(WampErrorUri::NoSuchRegistration)
+}
+
+/// The URI in the first argument.
+fn uri(call: &Call) -> Result<&str, WampErrorUri> {
+    call.args[0].as_str().ok_or(WampErrorUri::InvalidArgument)
+}
+
+/// The URI in the first argument and the policy in the `match` of the options in the second one.
+fn lookup(call: &Call) -> Result<(&str, MatchPolicy), WampErrorUri> {
+    let policy = match &call.args[1]["match"] {
+        Value::Null => MatchPolicy::Exact,
+        policy => policy.as_str().and_then(MatchPolicy::from_match).ok_or(WampErrorUri::InvalidArgument)?
+    };
+    Ok((uri(call)?, policy))
+}
+
+/// Whether a subscriber receives a publication as it happens, the publisher only gets its own events when it turned `exclude_me` off.
+fn admits(options: &PublishOptions, publisher: u64, id: u64, subscriber: &Session) -> bool {
+    (id != publisher || !options.exclude_me) && subscriber.admitted(id, options)
+}
+
+fn error(event: WampErrorEvent, request_id: u64, error: WampErrorUri) -> WampError {
+    WampError {
+        event,
+        request_id,
+        details: json!({}),
+        error: error.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use serde_json::{Value, json};
+    use tokio::sync::mpsc;
+    use tungstenite::Message;
+    use core::protocol::{
+        Serializer, IdGenerator, IntoUri, MatchPolicy, Uri,
+        messages::{Call, Cancel, CancelMode, Disclosed, InvokePolicy, Messages, WampErrorEvent, WampResult, Yield},
+        meta::{self, Listing, MetaCall, RegistrationDetails, SessionDetails, SubscriptionDetails}
+    };
+    use crate::session::Session;
+    use super::Realm;
+
+    /// A session attached to the realm, reading what the router sends it.
+    struct Peer {
+        id: u64,
+        messages: mpsc::UnboundedReceiver<Message>,
+        ids: IdGenerator
+    }
+
+    impl Peer {
+        fn join(realm: &Realm, details: Value) -> Self {
+            let (outgoing, messages) = mpsc::unbounded_channel();
+            let id = realm.join(Session::new(Serializer::Json, outgoing).announce(&details));
+            Self { id, messages, ids: IdGenerator::new() }
+        }
+
+        /// Messages received since the last look.
+        fn received(&mut self) -> Vec<Messages> {
+            let mut received = vec![];
+            while let Ok(message) = self.messages.try_recv() {
+                received.push(Serializer::Json.from_message(message).unwrap());
+            }
+            received
+        }
+    }
+
+    fn callee() -> Value {
+        json!({"roles": {"callee": {"features": {"progressive_call_invocations": true, "call_canceling": true}}}})
+    }
+
+    fn registered(realm: &Arc<Realm>, peer: &mut Peer, procedure: &'static str) -> u64 {
+        realm.handle(peer.id, core::register!(peer.ids, procedure).into()).unwrap();
+        match peer.received().pop() {
+            Some(Messages::Registered(registered)) => registered.registration,
+            received => panic!("expected Registered, got {received:?}")
+        }
+    }
+
+    /// A chunk of a progressive call to `com.myapp.upload`, every chunk reuses the request ID of the first one.
+    fn chunk(request_id: u64, progress: bool, args: Value) -> Messages {
+        Call {
+            request_id,
+            options: json!({"progress": progress}),
+            procedure: Uri::loose("com.myapp.upload").unwrap(),
+            args,
+            kwargs: Value::Null
+        }.into()
+    }
+
+    #[test]
+    fn callee_gone() {
+        let realm = Arc::new(Realm::new("realm1"));
+        let mut callee = Peer::join(&realm, callee());
+        let mut caller = Peer::join(&realm, json!({}));
+        registered(&realm, &mut callee, "com.myapp.add");
+        drop(callee.messages);
+
+        // The callee's writer is gone but it has not left yet, only the call fails.
+        realm.handle(caller.id, core::call!(caller.ids, "com.myapp.add").into()).unwrap();
+        match &caller.received()[..] {
+            [Messages::Error(error)] => assert_eq!(error.error, "wamp.error.no_available_callee"),
+            received => panic!("expected an error, got {received:?}")
+        }
+        assert!(realm.state.lock().unwrap().dealer.invocations.is_empty());
+    }
+
+    #[test]
+    fn callee_gone_mid_call() {
+        let realm = Arc::new(Realm::new("realm1"));
+        let mut callee = Peer::join(&realm, callee());
+        let mut caller = Peer::join(&realm, json!({}));
+        registered(&realm, &mut callee, "com.myapp.upload");
+        let request_id = caller.ids.next();
+        realm.handle(caller.id, chunk(request_id, true, json!(["chunk"]))).unwrap();
+        assert!(matches!(&callee.received()[..], [Messages::Invocation(_)]));
+        drop(callee.messages);
+
+        realm.handle(caller.id, chunk(request_id, false, json!(["chunk"]))).unwrap();
+        match &caller.received()[..] {
+            [Messages::Error(error)] => assert_eq!(error.error, "wamp.error.canceled"),
+            received => panic!("expected an error, got {received:?}")
+        }
+        assert!(realm.state.lock().unwrap().dealer.invocations.is_empty());
+    }
+
+    /// A call to `com.myapp.slow` the callee was invoked for, as the caller's request ID and the invocation's.
+    fn invoked(realm: &Arc<Realm>, caller: &mut Peer, callee: &mut Peer, options: Value) -> (u64, u64) {
+        let call = core::call!(caller.ids, "com.myapp.slow", options);
+        let request_id = call.request_id;
+        realm.handle(caller.id, call.into()).unwrap();
+        match &callee.received()[..] {
+            [Messages::Invocation(invocation)] => (request_id, invocation.request_id),
+            received => panic!("expected an Invocation, got {received:?}")
+        }
+    }
+
+    #[test]
+    fn cancel() {
+        let realm = Arc::new(Realm::new("realm1"));
+        let mut callee = Peer::join(&realm, callee());
+        let mut caller = Peer::join(&realm, json!({}));
+        registered(&realm, &mut callee, "com.myapp.slow");
+
+        // Skip answers the caller at once and leaves the callee running, its late result is dropped.
+        let (request_id, invocation) = invoked(&realm, &mut caller, &mut callee, json!({}));
+        realm.handle(caller.id, Cancel::new(request_id, CancelMode::Skip).into()).unwrap();
+        assert!(callee.received().is_empty());
+        match &caller.received()[..] {
+            [Messages::Error(error)] => assert_eq!((error.request_id, error.error.as_str()), (request_id, "wamp.error.canceled")),
+            received => panic!("expected an error, got {received:?}")
+        }
+        realm.handle(callee.id, Yield { request_id: invocation, options: json!({}), args: json!([]), kwargs: Value::Null }.into()).unwrap();
+        assert!(caller.received().is_empty());
+
+        // Kill interrupts the callee and waits for its error.
+        let (request_id, invocation) = invoked(&realm, &mut caller, &mut callee, json!({}));
+        realm.handle(caller.id, Cancel::new(request_id, CancelMode::Kill).into()).unwrap();
+        match &callee.received()[..] {
+            [Messages::Interrupt(interrupt)] => assert_eq!((interrupt.request_id, interrupt.mode()), (invocation, Some(CancelMode::Kill))),
+            received => panic!("expected an Interrupt, got {received:?}")
+        }
+        assert!(caller.received().is_empty());
+        realm.handle(callee.id, core::error!(invocation, WampErrorEvent::Invocation, "com.myapp.error.interrupted").into()).unwrap();
+        match &caller.received()[..] {
+            [Messages::Error(error)] => assert_eq!((error.request_id, error.error.as_str()), (request_id, "com.myapp.error.interrupted")),
+            received => panic!("expected an error, got {received:?}")
+        }
+
+        // Killnowait interrupts the callee and answers the caller without waiting.
+        let (request_id, invocation) = invoked(&realm, &mut caller, &mut callee, json!({}));
+        realm.handle(caller.id, Cancel::new(request_id, CancelMode::KillNoWait).into()).unwrap();
+        match &callee.received()[..] {
+            [Messages::Interrupt(interrupt)] => assert_eq!((interrupt.request_id, interrupt.mode()), (invocation, Some(CancelMode::KillNoWait))),
+            received => panic!("expected an Interrupt, got {received:?}")
+        }
+        match &caller.received()[..] {
+            [Messages::Error(error)] => assert_eq!(error.error, "wamp.error.canceled"),
+            received => panic!("expected an error, got {received:?}")
+        }
+        assert!(realm.state.lock().unwrap().dealer.invocations.is_empty());
+    }
+
+    #[test]
+    fn cancel_without_call_canceling() {
+        let realm = Arc::new(Realm::new("realm1"));
+        let mut callee = Peer::join(&realm, json!({"roles": {"callee": {}}}));
+        let mut caller = Peer::join(&realm, json!({}));
+        registered(&realm, &mut callee, "com.myapp.slow");
+
+        // The callee can not be interrupted, kill acts as skip.
+        let (request_id, _) = invoked(&realm, &mut caller, &mut callee, json!({}));
+        realm.handle(caller.id, Cancel::new(request_id, CancelMode::Kill).into()).unwrap();
+        assert!(callee.received().is_empty());
+        match &caller.received()[..] {
+            [Messages::Error(error)] => assert_eq!(error.error, "wamp.error.canceled"),
+            received => panic!("expected an error, got {received:?}")
+        }
+
+        // Cancelling a call that is already answered does nothing.
+        realm.handle(caller.id, Cancel::new(request_id, CancelMode::Kill).into()).unwrap();
+        assert!(caller.received().is_empty());
+    }
+
+    #[test]
+    fn timeout() {
+        let realm = Arc::new(Realm::new("realm1"));
+        let mut callee = Peer::join(&realm, callee());
+        let mut caller = Peer::join(&realm, json!({}));
+        registered(&realm, &mut callee, "com.myapp.slow");
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        let (request_id, invocation) = runtime.block_on(async {
+            let invoked = invoked(&realm, &mut caller, &mut callee, json!({"timeout": 10}));
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            invoked
+        });
+        match &callee.received()[..] {
+            [Messages::Interrupt(interrupt)] => assert_eq!((interrupt.request_id, interrupt.mode()), (invocation, Some(CancelMode::KillNoWait))),
+            received => panic!("expected an Interrupt, got {received:?}")
+        }
+        match &caller.received()[..] {
+            [Messages::Error(error)] => assert_eq!((error.request_id, error.error.as_str()), (request_id, "wamp.error.timeout")),
+            received => panic!("expected an error, got {received:?}")
+        }
+        assert!(realm.state.lock().unwrap().dealer.invocations.is_empty());
+    }
+
+    #[test]
+    fn progressive_invocation() {
+        let realm = Arc::new(Realm::new("realm1"));
+        let mut callee = Peer::join(&realm, callee());
+        let mut caller = Peer::join(&realm, json!({}));
+        let registration = registered(&realm, &mut callee, "com.myapp.upload");
+        let request_id = caller.ids.next();
+        for (args, progress) in [(json!([1]), true), (json!([2]), true), (json!([3]), false)] {
+            realm.handle(caller.id, chunk(request_id, progress, args)).unwrap();
+        }
+
+        // Every chunk reaches the callee under the invocation the first one started.
+        let invocations: Vec<(u64, u64, Value, bool)> = callee.received().into_iter().map(|message| match message {
+            Messages::Invocation(invocation) => (invocation.request_id, invocation.registration, invocation.args[0].clone(), invocation.is_progress()),
+            message => panic!("expected an Invocation, got {message:?}")
+        }).collect();
+        assert_eq!(invocations.len(), 3);
+        assert!(invocations.iter().all(|(invocation, chunk_registration, _, _)| *invocation == invocations[0].0 && *chunk_registration == registration));
+        let chunks: Vec<(Value, bool)> = invocations.iter().map(|(_, _, chunk, progress)| (chunk.clone(), *progress)).collect();
+        assert_eq!(chunks, [(json!(1), true), (json!(2), true), (json!(3), false)]);
+
+        realm.handle(callee.id, Yield { request_id: invocations[0].0, options: json!({}), args: json!([6]), kwargs: Value::Null }.into()).unwrap();
+        match &caller.received()[..] {
+            [Messages::Result(result)] => assert_eq!((result.request_id, result.args.clone()), (request_id, json!([6]))),
+            received => panic!("expected a Result, got {received:?}")
+        }
+        assert!(realm.state.lock().unwrap().dealer.invocations.is_empty());
+    }
+
+    fn subscribed(realm: &Arc<Realm>, peer: &mut Peer, topic: &'static str, options: Value) -> u64 {
+        realm.handle(peer.id, core::subscribe!(peer.ids, topic, options).into()).unwrap();
+        match peer.received().first() {
+            Some(Messages::Subscribed(subscribed)) => subscribed.subscription,
+            received => panic!("expected Subscribed, got {received:?}")
+        }
+    }
+
+    #[test]
+    fn authid() {
+        let realm = Arc::new(Realm::new("realm1"));
+        let mut subscriber = Peer::join(&realm, json!({"authid": "admin"}));
+        let publisher = Peer::join(&realm, json!({}));
+        subscribed(&realm, &mut subscriber, "com.myapp.topic", json!({}));
+        assert_eq!(realm.session(subscriber.id).unwrap().authid, Some(subscriber.id.to_string()));
+
+        // The authid the client claimed is not the one eligibility is checked against.
+        realm.handle(publisher.id, core::publish!(publisher.ids, "com.myapp.topic", json!({"eligible_authid": ["admin"]})).into()).unwrap();
+        assert!(subscriber.received().is_empty());
+        realm.handle(publisher.id, core::publish!(publisher.ids, "com.myapp.topic", json!({"eligible_authid": [subscriber.id.to_string()]})).into()).unwrap();
+        assert!(matches!(&subscriber.received()[..], [Messages::Event(_)]));
+        realm.handle(publisher.id, core::publish!(publisher.ids, "com.myapp.topic", json!({"exclude_authid": [subscriber.id.to_string()]})).into()).unwrap();
+        assert!(subscriber.received().is_empty());
+    }
+
+    #[test]
+    fn disclose_me() {
+        let realm = Arc::new(Realm::new("realm1"));
+        let mut callee = Peer::join(&realm, callee());
+        let peer = Peer::join(&realm, json!({"authid": "admin"}));
+        registered(&realm, &mut callee, "com.myapp.slow");
+        subscribed(&realm, &mut callee, "com.myapp.topic", json!({}));
+        let disclosed = Disclosed { session: peer.id, authid: Some(peer.id.to_string()), authrole: Some("anonymous".to_string()) };
+
+        realm.handle(peer.id, core::call!(peer.ids, "com.myapp.slow", json!({"disclose_me": true})).into()).unwrap();
+        realm.handle(peer.id, core::publish!(peer.ids, "com.myapp.topic", json!({"disclose_me": true})).into()).unwrap();
+        match &callee.received()[..] {
+            [Messages::Invocation(invocation), Messages::Event(event)] => {
+                assert_eq!(invocation.caller().as_ref(), Some(&disclosed));
+                assert_eq!(event.publisher().as_ref(), Some(&disclosed));
+            },
+            received => panic!("expected an Invocation and an Event, got {received:?}")
+        }
+    }
+
+    /// Calls a procedure of the router's meta API, as its result or the URI of its error.
+    fn meta(realm: &Arc<Realm>, peer: &mut Peer, procedure: impl IntoUri, args: Value) -> Result<WampResult, Uri> {
+        realm.handle(peer.id, core::call!(peer.ids, procedure, json!({}), args: args).into()).unwrap();
+        match peer.received().pop() {
+            Some(Messages::Result(result)) => Ok(result),
+            Some(Messages::Error(error)) => Err(error.error),
+            received => panic!("expected a Result or an error, got {received:?}")
+        }
+    }
+
+    /// Sends the meta call and reads its result the way clients do.
+    fn answered<T>(realm: &Arc<Realm>, peer: &mut Peer, call: MetaCall<T>) -> Result<T, Uri> {
+        let result = meta(realm, peer, call.call.procedure.clone(), call.call.args.clone())?;
+        Ok(call.read(&result).expect("the result matches the meta procedure"))
+    }
+
+    #[test]
+    fn get_retained() {
+        let realm = Arc::new(Realm::new("realm1"));
+        let publisher = Peer::join(&realm, json!({}));
+        let mut eligible = Peer::join(&realm, json!({}));
+        let mut other = Peer::join(&realm, json!({}));
+        realm.handle(publisher.id, core::publish!(publisher.ids, "com.myapp.private", json!({"retain": true, "eligible": [eligible.id]})).into()).unwrap();
+        realm.handle(publisher.id, core::publish!(publisher.ids, "com.myapp.public", json!({"retain": true, "exclude": [eligible.id]})).into()).unwrap();
+
+        // Each subscriber is only handed the retained events it could have received when they were published.
+        for (peer, topic) in [(&mut eligible, "com.myapp.private"), (&mut other, "com.myapp.public")] {
+            realm.handle(peer.id, core::subscribe!(peer.ids, "com.myapp.", json!({"match": "prefix", "get_retained": true})).into()).unwrap();
+            match &peer.received()[..] {
+                [Messages::Subscribed(_), Messages::Event(event)] => {
+                    assert_eq!((event.topic(), &event.details["retained"]), (Some(topic), &json!(true)));
+                },
+                received => panic!("expected Subscribed and one Event, got {received:?}")
+            }
+        }
+    }
+
+    #[test]
+    fn get_events() {
+        let realm = Arc::new(Realm::new("realm1").event_history(10));
+        let publisher = Peer::join(&realm, json!({}));
+        let mut subscriber = Peer::join(&realm, json!({}));
+        let mut other = Peer::join(&realm, json!({}));
+        let subscription = subscribed(&realm, &mut subscriber, "com.myapp.", json!({"match": "prefix"}));
+        for options in [json!({}), json!({"eligible": [other.id]}), json!({"exclude": [subscriber.id]}), json!({}), json!({})] {
+            realm.handle(publisher.id, core::publish!(publisher.ids, "com.myapp.topic", options).into()).unwrap();
+        }
+        let publications: Vec<u64> = realm.state.lock().unwrap().broker.history(subscription).unwrap().iter()
+            .map(|(_, publication)| publication.publication)
+            .collect();
+
+        // The caller only gets the events it may receive, the latest `limit` of them.
+        let events = |args: Value| -> Vec<u64> {
+            args[0].as_array().unwrap().iter().map(|event| event["publication"].as_u64().unwrap()).collect()
+        };
+        let got = meta(&realm, &mut subscriber, "wamp.subscription.get_events", json!([subscription])).unwrap().args;
+        assert_eq!(events(got), [publications[0], publications[3], publications[4]]);
+        let got = meta(&realm, &mut subscriber, "wamp.subscription.get_events", json!([subscription, 2])).unwrap().args;
+        assert_eq!(events(got), [publications[3], publications[4]]);
+        let got = meta(&realm, &mut other, "wamp.subscription.get_events", json!([subscription, 4])).unwrap().args;
+        assert_eq!(events(got), publications[1..].to_vec());
+
+        assert_eq!(meta(&realm, &mut other, "wamp.subscription.get_events", json!([subscription, "all"])).unwrap_err(), "wamp.error.invalid_argument");
+        assert_eq!(meta(&realm, &mut other, "wamp.subscription.get_events", json!([subscription + 1])).unwrap_err(), "wamp.error.no_such_subscription");
+    }
+
+    #[test]
+    fn meta_procedures() {
+        let realm = Arc::new(Realm::new("realm1"));
+        let mut callee = Peer::join(&realm, callee());
+        let mut subscriber = Peer::join(&realm, json!({}));
+        let registration = registered(&realm, &mut callee, "com.myapp.add");
+        let subscription = subscribed(&realm, &mut subscriber, "com.myapp.", json!({"match": "prefix"}));
+        let mut sessions = vec![callee.id, subscriber.id];
+        sessions.sort_unstable();
+
+        assert_eq!(answered(&realm, &mut callee, meta::session_count(None)).unwrap(), 2);
+        assert_eq!(answered(&realm, &mut callee, meta::session_count(Some(vec!["admin".to_string()]))).unwrap(), 0);
+        assert_eq!(answered(&realm, &mut callee, meta::session_list(Some(vec!["anonymous".to_string()]))).unwrap(), sessions);
+        assert_eq!(answered(&realm, &mut callee, meta::session_get(subscriber.id)).unwrap(), SessionDetails {
+            session: subscriber.id,
+            authid: Some(subscriber.id.to_string()),
+            authrole: Some("anonymous".to_string()),
+            authmethod: Some("anonymous".to_string())
+        });
+        assert_eq!(answered(&realm, &mut callee, meta::session_get(0)).unwrap_err(), "wamp.error.no_such_session");
+        assert_eq!(meta(&realm, &mut callee, "wamp.session.get", json!(["me"])).unwrap_err(), "wamp.error.invalid_argument");
+
+        assert_eq!(answered(&realm, &mut callee, meta::subscription_list()).unwrap(), Listing { prefix: vec![subscription], ..Default::default() });
+        assert_eq!(answered(&realm, &mut callee, meta::subscription_lookup("com.myapp.", MatchPolicy::Prefix)).unwrap(), Some(subscription));
+        assert_eq!(answered(&realm, &mut callee, meta::subscription_lookup("com.myapp.", MatchPolicy::Exact)).unwrap(), None);
+        assert_eq!(answered(&realm, &mut callee, meta::subscription_match("com.myapp.topic")).unwrap(), [subscription]);
+        assert_eq!(answered(&realm, &mut callee, meta::subscription_get(subscription)).unwrap(), SubscriptionDetails {
+            id: subscription,
+            uri: "com.myapp.".to_string(),
+            match_policy: MatchPolicy::Prefix
+        });
+        assert_eq!(answered(&realm, &mut callee, meta::subscription_list_subscribers(subscription)).unwrap(), [subscriber.id]);
+        assert_eq!(answered(&realm, &mut callee, meta::subscription_get(registration)).unwrap_err(), "wamp.error.no_such_subscription");
+
+        assert_eq!(answered(&realm, &mut callee, meta::registration_list()).unwrap(), Listing { exact: vec![registration], ..Default::default() });
+        assert_eq!(answered(&realm, &mut callee, meta::registration_lookup("com.myapp.add", MatchPolicy::Exact)).unwrap(), Some(registration));
+        assert_eq!(answered(&realm, &mut callee, meta::registration_match("com.myapp.add")).unwrap(), Some(registration));
+        assert_eq!(answered(&realm, &mut callee, meta::registration_match("com.myapp.sub")).unwrap(), None);
+        assert_eq!(answered(&realm, &mut callee, meta::registration_get(registration)).unwrap(), RegistrationDetails {
+            id: registration,
+            uri: "com.myapp.add".to_string(),
+            match_policy: MatchPolicy::Exact,
+            invoke: InvokePolicy::Single
+        });
+        assert_eq!(answered(&realm, &mut callee, meta::registration_list_callees(registration)).unwrap(), [callee.id]);
+        assert_eq!(answered(&realm, &mut callee, meta::registration_get(subscription)).unwrap_err(), "wamp.error.no_such_registration");
+    }
+
+    #[test]
+    fn meta_events() {
+        let realm = Arc::new(Realm::new("realm1"));
+        let mut observer = Peer::join(&realm, json!({}));
+        subscribed(&realm, &mut observer, "wamp.", json!({"match": "prefix"}));
+        observer.received();
+
+        let mut peer = Peer::join(&realm, json!({}));
+        let subscription = subscribed(&realm, &mut peer, "com.myapp.topic", json!({}));
+        let registration = registered(&realm, &mut peer, "com.myapp.add");
+        realm.leave(peer.id);
+
+        let events: Vec<(String, Value)> = observer.received().into_iter().map(|message| match message {
+            Messages::Event(event) => (event.topic().unwrap().to_string(), event.args),
+            message => panic!("expected an Event, got {message:?}")
+        }).collect();
+        let session = json!({"session": peer.id, "authid": peer.id.to_string(), "authrole": "anonymous", "authmethod": "anonymous"});
+        let expected: Vec<(String, Value)> = [
+            (meta::SESSION_ON_JOIN, json!([session])),
+            (meta::SUBSCRIPTION_ON_CREATE, json!([peer.id, Value::from(SubscriptionDetails {
+                id: subscription,
+                uri: "com.myapp.topic".to_string(),
+                match_policy: MatchPolicy::Exact
+            })])),
+            (meta::SUBSCRIPTION_ON_SUBSCRIBE, json!([peer.id, subscription])),
+            (meta::REGISTRATION_ON_CREATE, json!([peer.id, Value::from(RegistrationDetails {
+                id: registration,
+                uri: "com.myapp.add".to_string(),
+                match_policy: MatchPolicy::Exact,
+                invoke: InvokePolicy::Single
+            })])),
+            (meta::REGISTRATION_ON_REGISTER, json!([peer.id, registration])),
+            (meta::SUBSCRIPTION_ON_UNSUBSCRIBE, json!([peer.id, subscription])),
+            (meta::SUBSCRIPTION_ON_DELETE, json!([peer.id, subscription])),
+            (meta::REGISTRATION_ON_UNREGISTER, json!([peer.id, registration])),
+            (meta::REGISTRATION_ON_DELETE, json!([peer.id, registration])),
+            (meta::SESSION_ON_LEAVE, json!([peer.id, peer.id.to_string(), "anonymous"]))
+        ].into_iter().map(|(topic, args)| (topic.to_string(), args)).collect();
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn reserved_procedures() {
+        let realm = Arc::new(Realm::new("realm1"));
+        let mut callee = Peer::join(&realm, callee());
+        realm.handle(callee.id, core::register!(callee.ids, "wamp.session.count").into()).unwrap();
+        match &callee.received()[..] {
+            [Messages::Error(error)] => assert_eq!((error.event.clone(), error.error.as_str()), (WampErrorEvent::Register, "wamp.error.invalid_uri")),
+            received => panic!("expected an error, got {received:?}")
+        }
+        assert_eq!(answered(&realm, &mut callee, meta::registration_list()).unwrap(), Listing::default());
+    }
+
+    #[test]
+    fn caller_gone() {
+        let realm = Arc::new(Realm::new("realm1"));
+        let mut callee = Peer::join(&realm, callee());
+        let mut caller = Peer::join(&realm, json!({}));
+        registered(&realm, &mut callee, "com.myapp.slow");
+        let (_, invocation) = invoked(&realm, &mut caller, &mut callee, json!({}));
+
+        // The callee is told to stop working on a call no one waits for anymore.
+        realm.leave(caller.id);
+        match &callee.received()[..] {
+            [Messages::Interrupt(interrupt)] => assert_eq!((interrupt.request_id, interrupt.mode()), (invocation, Some(CancelMode::KillNoWait))),
+            received => panic!("expected an Interrupt, got {received:?}")
+        }
+        assert!(realm.state.lock().unwrap().dealer.invocations.is_empty());
+    }
+}
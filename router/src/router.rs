@@ -0,0 +1,417 @@
+use std::{collections::HashMap, io::ErrorKind, sync::Arc};
+#[cfg(unix)]
+use std::path::Path;
+use futures_util::{SinkExt, Stream, StreamExt, stream};
+use serde_json::json;
+use tokio::{io::{AsyncRead, AsyncWrite, AsyncWriteExt}, net::{TcpListener, TcpStream, ToSocketAddrs}, sync::mpsc};
+#[cfg(unix)]
+use tokio::net::UnixListener;
+use tokio_tungstenite::accept_hdr_async;
+use tungstenite::{Message, handshake::server::{Request, Response, ErrorResponse}, http::{HeaderValue, StatusCode}};
+use core::{error::{CloseUri, Error, WampErrorUri}, protocol::{Feature, Serializer, Uri, messages::{Abort, Call, Goodbye, Messages, Register, RouterRoles, Subscribe, WampError, WampErrorEvent, WampMessage, Welcome, WelcomeDetails}, rawsocket::{FrameType, MAX_LENGTH}, roles::Roles}};
+
+use super::{rawsocket, realm::Realm, session::{ANONYMOUS, Session}};
+
+/// Subprotocols in order of preference when a client offers several.
+const SERIALIZERS: [Serializer; 3] = [Serializer::Cbor, Serializer::MsgPack, Serializer::Json];
+
+/// Embedded WAMP router, acting as broker and dealer for each of its realms.
+///
+/// Clones share their realms, so one router can serve WebSocket and RawSocket listeners side by side.
+#[derive(Clone)]
+pub struct Router {
+    realms: HashMap<String, Arc<Realm>>
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self { realms: HashMap::new() }
+    }
+
+    pub fn realm(mut self, realm: Realm) -> Self {
+        self.realms.insert(realm.name.clone(), Arc::new(realm));
+        self
+    }
+
+    pub async fn listen<A: ToSocketAddrs>(self, address: A) -> Result<(), Error> {
+        self.serve(TcpListener::bind(address).await?).await
+    }
+
+    /// Accepts connections forever, each one is handled on its own task.
+    pub async fn serve(self, listener: TcpListener) -> Result<(), Error> {
+        let realms = Arc::new(self.realms);
+        loop {
+            let (stream, _) = listener.accept().await?;
+            tokio::spawn(handle_websocket(Arc::clone(&realms), stream));
+        }
+    }
+
+    pub async fn listen_rawsocket<A: ToSocketAddrs>(self, address: A) -> Result<(), Error> {
+        self.serve_rawsocket(TcpListener::bind(address).await?).await
+    }
+
+    /// Accepts RawSocket connections over TCP forever, each one is handled on its own task.
+    pub async fn serve_rawsocket(self, listener: TcpListener) -> Result<(), Error> {
+        let realms = Arc::new(self.realms);
+        loop {
+            let (stream, _) = listener.accept().await?;
+            tokio::spawn(handle_rawsocket(Arc::clone(&realms), stream));
+        }
+    }
+
+    #[cfg(unix)]
+    pub async fn listen_unix<P: AsRef<Path>>(self, path: P) -> Result<(), Error> {
+        self.serve_unix(UnixListener::bind(path)?).await
+    }
+
+    /// Accepts RawSocket connections over a Unix domain socket forever, each one is handled on its own task.
+    #[cfg(unix)]
+    pub async fn serve_unix(self, listener: UnixListener) -> Result<(), Error> {
+        let realms = Arc::new(self.realms);
+        loop {
+            let (stream, _) = listener.accept().await?;
+            tokio::spawn(handle_rawsocket(Arc::clone(&realms), stream));
+        }
+    }
+}
+
+impl Default for Router {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Picks the serializer from the offered subprotocols, the upgrade is refused when none is supported.
+fn negotiate(request: &Request, mut response: Response, serializer: &mut Serializer) -> Result<Response, ErrorResponse> {
+    let offered: Vec<&str> = request.headers().get_all("Sec-WebSocket-Protocol").iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|protocol| protocol.trim())
+        .collect();
+    if let Some(supported) = SERIALIZERS.into_iter().find(|supported| offered.contains(&supported.protocol())) {
+        response.headers_mut().insert("Sec-WebSocket-Protocol", HeaderValue::from_static(supported.protocol()));
+        *serializer = supported;
+        Ok(response)
+    } else {
+        let mut error = ErrorResponse::new(Some("No supported WAMP subprotocol offered.".to_string()));
+        *error.status_mut() = StatusCode::BAD_REQUEST;
+        Err(error)
+    }
+}
+
+async fn handle_websocket(realms: Arc<HashMap<String, Arc<Realm>>>, stream: TcpStream) -> Result<(), Error> {
+    let mut serializer = Serializer::default();
+    let socket = accept_hdr_async(stream, |request: &Request, response: Response| negotiate(request, response, &mut serializer)).await?;
+    let (mut sink, stream) = socket.split();
+    let (outgoing, mut receiver) = mpsc::unbounded_channel::<Message>();
+    let writer = tokio::spawn(async move {
+        while let Some(message) = receiver.recv().await {
+            if sink.send(message).await.is_err() {
+                break;
+            }
+        }
+        let _ = sink.close().await;
+    });
+    let result = run_session(realms, Session::new(serializer, outgoing), serializer, stream.map(|frame| frame.map_err(Error::from))).await;
+    let _ = writer.await;
+    result
+}
+
+/// Serves a RawSocket connection, pings are answered directly and every message frame is handed to the session.
+async fn handle_rawsocket<S: AsyncRead + AsyncWrite + Unpin + Send + 'static>(realms: Arc<HashMap<String, Arc<Realm>>>, mut stream: S) -> Result<(), Error> {
+    let handshake = rawsocket::accept(&mut stream, &SERIALIZERS, MAX_LENGTH).await?;
+    let (reader, mut sink) = tokio::io::split(stream);
+    let (outgoing, mut receiver) = mpsc::unbounded_channel::<Message>();
+    let writer = tokio::spawn(async move {
+        while let Some(message) = receiver.recv().await {
+            if rawsocket::write_frame(&mut sink, message, handshake.max_length).await.is_err() {
+                break;
+            }
+        }
+        let _ = sink.shutdown().await;
+    });
+    let pongs = outgoing.clone();
+    let frames = Box::pin(stream::unfold(reader, move |mut reader| {
+        let pongs = pongs.clone();
+        async move {
+            loop {
+                match rawsocket::read_frame(&mut reader, MAX_LENGTH).await {
+                    Ok((FrameType::Message, payload)) => return Some((Ok(Message::Binary(payload)), reader)),
+                    Ok((FrameType::Ping, payload)) => {
+                        let _ = pongs.send(Message::Pong(payload));
+                    },
+                    Ok((FrameType::Pong, _)) => {},
+                    Err(Error::IoError(error)) if error.kind() == ErrorKind::UnexpectedEof => return None,
+                    Err(error) => return Some((Err(error), reader))
+                }
+            }
+        }
+    }));
+    let result = run_session(realms, Session::new(handshake.serializer, outgoing), handshake.serializer, frames).await;
+    let _ = writer.await;
+    result
+}
+
+/// Drives a session from HELLO to GOODBYE over frames of any transport, the connection is closed once it returns.
+async fn run_session<F>(realms: Arc<HashMap<String, Arc<Realm>>>, connection: Session, serializer: Serializer, mut stream: F) -> Result<(), Error>
+where
+    F: Stream<Item = Result<Message, Error>> + Unpin
+{
+    let mut session: Option<(Arc<Realm>, u64)> = None;
+    let result = loop {
+        let frame = match stream.next().await {
+            Some(Ok(frame)) => frame,
+            Some(Err(error)) => break Err(error),
+            None => break Ok(())
+        };
+        let data = match frame {
+            Message::Text(_) | Message::Binary(_) => frame.into_data(),
+            Message::Close(_) => break Ok(()),
+            _ => continue
+        };
+        let message = match serializer.decode(&data) {
+            Ok(message) => message,
+            // A request naming an invalid URI is refused on its own, the session goes on.
+            Err(error) => match session.as_ref().and_then(|_| invalid_uri(serializer, &data)) {
+                Some(refused) => match connection.send(refused) {
+                    Ok(()) => continue,
+                    Err(error) => break Err(error)
+                },
+                None => {
+                    let _ = connection.send(abort(WampErrorUri::ProtocolViolation));
+                    break Err(error);
+                }
+            }
+        };
+        match (&session, message) {
+            (None, Messages::Hello(hello)) => {
+                match realms.get(&hello.realm) {
+                    Some(realm) => {
+                        let id = realm.join(connection.clone().announce(&hello.details));
+                        let details = welcome_details(realm, &realm.session(id).expect("the session joined just now"));
+                        session = Some((Arc::clone(realm), id));
+                        if let Err(error) = connection.send(Welcome {
+                            session: id,
+                            details: details.into()
+                        }) {
+                            break Err(error);
+                        }
+                    },
+                    None => {
+                        let _ = connection.send(abort(WampErrorUri::NoSuchRealm));
+                        break Ok(());
+                    }
+                }
+            },
+            (Some(_), Messages::Goodbye(_)) => {
+                let _ = connection.send(Goodbye {
+                    details: json!({}),
+                    reason: CloseUri::GoodbyeAndOut.to_string()
+                });
+                break Ok(());
+            },
+            (Some((realm, id)), message) => {
+                if let Err(error) = realm.handle(*id, message) {
+                    let _ = connection.send(abort(WampErrorUri::ProtocolViolation));
+                    break Err(error);
+                }
+            },
+            (None, message) => {
+                let _ = connection.send(abort(WampErrorUri::ProtocolViolation));
+                break Err(Error::InvalidFrameReceived(message));
+            }
+        }
+    };
+
+    if let Some((realm, id)) = session {
+        realm.leave(id);
+    }
+    result
+}
+
+/// Roles and features the router announces to every session, along with the identity it was joined as.
+fn welcome_details(realm: &Realm, session: &Session) -> WelcomeDetails {
+    let mut roles = RouterRoles::default()
+        .feature(Roles::Broker, Feature::PatternBasedSubscription)
+        .feature(Roles::Broker, Feature::PublisherExclusion)
+        .feature(Roles::Broker, Feature::SubscriberBlackwhiteListing)
+        .feature(Roles::Broker, Feature::EventRetention)
+        .feature(Roles::Dealer, Feature::CallCanceling)
+        .feature(Roles::Dealer, Feature::CallTimeout)
+        .feature(Roles::Dealer, Feature::ProgressiveCallResults)
+        .feature(Roles::Dealer, Feature::ProgressiveCallInvocations)
+        .feature(Roles::Dealer, Feature::PatternBasedRegistration)
+        .feature(Roles::Dealer, Feature::SharedRegistration)
+        .feature(Roles::Broker, Feature::SessionMetaApi)
+        .feature(Roles::Broker, Feature::SubscriptionMetaApi)
+        .feature(Roles::Dealer, Feature::SessionMetaApi)
+        .feature(Roles::Dealer, Feature::RegistrationMetaApi);
+    if realm.disclose_me {
+        roles = roles
+            .feature(Roles::Broker, Feature::PublisherIdentification)
+            .feature(Roles::Dealer, Feature::CallerIdentification);
+    }
+    if realm.keeps_history() {
+        roles = roles.feature(Roles::Broker, Feature::EventHistory);
+    }
+    WelcomeDetails {
+        roles,
+        authid: session.authid.clone(),
+        authrole: Some(session.authrole.clone()),
+        authmethod: Some(ANONYMOUS.to_string()),
+        ..Default::default()
+    }
+}
+
+/// The `wamp.error.invalid_uri` error for a SUBSCRIBE, REGISTER or CALL that failed to decode because of its URI,
+/// None for any other frame that failed to decode.
+fn invalid_uri(serializer: Serializer, data: &[u8]) -> Option<WampError> {
+    let message = serializer.decode_value(data).ok()?;
+    let (event, valid): (WampErrorEvent, fn(&str) -> bool) = match message[0].as_u64()? {
+        Subscribe::ID => (WampErrorEvent::Subscribe, |uri| Uri::pattern(uri).is_ok()),
+        Register::ID => (WampErrorEvent::Register, |uri| Uri::pattern(uri).is_ok()),
+        Call::ID => (WampErrorEvent::Call, |uri| Uri::loose(uri).is_ok()),
+        _ => return None
+    };
+    let request_id = message[1].as_u64()?;
+    let uri = message[3].as_str()?;
+    (!valid(uri)).then(|| core::error!(request_id, event, Uri::from(WampErrorUri::InvalidUri)))
+}
+
+fn abort(reason: WampErrorUri) -> Abort {
+    Abort {
+        details: json!({}),
+        reason: reason.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, thread};
+    use futures_util::StreamExt;
+    use serde_json::{Value, json};
+    use tokio::net::TcpListener;
+    use client::{AsyncClient, Client, WampRequest};
+    use core::{error::CloseUri, protocol::{Serializer, messages::{WampErrorEvent, Yield}, rawsocket::Handshake}};
+    use crate::realm::Realm;
+    use super::{Router, invalid_uri};
+
+    fn run<F: std::future::Future>(test: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap().block_on(test)
+    }
+
+    /// Reads and dispatches the next message.
+    fn step(client: &mut Client) {
+        let message = client.read().unwrap();
+        client.read_contexts(message).unwrap();
+    }
+
+    #[test]
+    fn invalid_uris() {
+        for serializer in [Serializer::Json, Serializer::MsgPack, Serializer::Cbor] {
+            for (frame, event) in [
+                (json!([32, 1, {}, "com.my app.topic"]), WampErrorEvent::Subscribe),
+                (json!([64, 2, {"match": "prefix"}, "com.myapp#"]), WampErrorEvent::Register),
+                (json!([48, 3, {}, "com..add", []]), WampErrorEvent::Call)
+            ] {
+                let error = invalid_uri(serializer, &serializer.encode(&frame).unwrap()).unwrap();
+                assert_eq!((error.event, error.request_id, error.error.as_str()), (event, frame[1].as_u64().unwrap(), "wamp.error.invalid_uri"));
+            }
+
+            // Frames that are broken for another reason still end the session.
+            for frame in [json!([32, 1, {}, "com.myapp."]), json!([32, "one", {}, "com my app"]), json!([32, 1, {}]), json!([16, 4, {}, "com my app"])] {
+                assert!(invalid_uri(serializer, &serializer.encode(&frame).unwrap()).is_none(), "{frame}");
+            }
+        }
+    }
+
+    #[test]
+    fn websocket() {
+        run(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let uri = format!("ws://{}", listener.local_addr().unwrap());
+            tokio::spawn(Router::new().realm(Realm::new("realm1")).serve(listener));
+
+            let (callee, _) = AsyncClient::connect(WampRequest { uri: uri.clone(), protocol: "wamp.2.json" }).await.unwrap();
+            let (caller, _) = AsyncClient::connect(WampRequest { uri, protocol: "wamp.2.json" }).await.unwrap();
+            let sessions = (callee.join(core::hello!("realm1")).await.unwrap().session, caller.join(core::hello!("realm1")).await.unwrap().session);
+            assert_ne!(sessions.0, sessions.1);
+
+            let mut events = callee.subscribe(core::subscribe!(callee.ids(), "com.myapp.topic")).await.unwrap();
+            let published = caller.publish(core::publish!(caller.ids(), "com.myapp.topic", json!({"acknowledge": true}), args: json!([1]))).await.unwrap();
+            let event = events.next().await.unwrap();
+            assert_eq!((event.subscription, event.publication, event.args), (events.subscribed.subscription, published.unwrap().publication, json!([1])));
+
+            callee.register(core::register!(callee.ids(), "com.myapp.add"), |invocation| async move {
+                let sum = invocation.args[0].as_i64().unwrap() + invocation.args[1].as_i64().unwrap();
+                Ok(Yield { request_id: invocation.request_id, options: json!({}), args: json!([sum]), kwargs: Value::Null })
+            }).await.unwrap();
+            let result = caller.call(core::call!(caller.ids(), "com.myapp.add", json!({}), args: json!([2, 3]))).await.unwrap();
+            assert_eq!(result.args, json!([5]));
+
+            for client in [caller, callee] {
+                assert_eq!(client.leave(CloseUri::SystemShutdown).await.unwrap().reason, "wamp.close.goodbye_and_out");
+            }
+        });
+    }
+
+    #[test]
+    fn rawsocket() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        listener.set_nonblocking(true).unwrap();
+        thread::spawn(move || run(async { Router::new().realm(Realm::new("realm1")).serve_rawsocket(TcpListener::from_std(listener).unwrap()).await.unwrap() }));
+
+        let outcomes = Rc::new(RefCell::new(vec![]));
+        let mut callee = Client::connect_rawsocket(address, Handshake::new(Serializer::Json)).unwrap();
+        let mut caller = Client::connect_rawsocket(address, Handshake::new(Serializer::Json)).unwrap();
+        for client in [&mut callee, &mut caller] {
+            let welcomes = Rc::clone(&outcomes);
+            client.on_welcome(Box::new(move |ctx, _| {
+                welcomes.borrow_mut().push("welcome".to_string());
+                ctx
+            }));
+            client.join(core::hello!("realm1")).unwrap();
+            step(client);
+        }
+
+        let events = Rc::clone(&outcomes);
+        callee.subscribe(core::subscribe!(callee.ids(), "com.myapp.topic"), Box::new(move |mut ctx, subscribed| {
+            let events = Rc::clone(&events);
+            ctx.event(subscribed.unwrap(), Box::new(move |ctx, event| {
+                events.borrow_mut().push(format!("event {}", event.args[0]));
+                ctx
+            })).unwrap();
+            ctx
+        })).unwrap();
+        step(&mut callee);
+        let publications = Rc::clone(&outcomes);
+        caller.publish(core::publish!(caller.ids(), "com.myapp.topic", json!({"acknowledge": true}), args: json!([1])), Box::new(move |ctx, published| {
+            publications.borrow_mut().push(format!("published {}", published.is_ok()));
+            ctx
+        })).unwrap();
+        step(&mut caller);
+        step(&mut callee);
+
+        callee.register(core::register!(callee.ids(), "com.myapp.add"), Box::new(|mut ctx, registered| {
+            ctx.invocation(registered.unwrap(), Box::new(|ctx, invocation| {
+                let sum = invocation.args[0].as_i64().unwrap() + invocation.args[1].as_i64().unwrap();
+                (ctx, Ok(Some(Yield { request_id: invocation.request_id, options: json!({}), args: json!([sum]), kwargs: Value::Null })))
+            })).unwrap();
+            ctx
+        })).unwrap();
+        step(&mut callee);
+        let calls = Rc::clone(&outcomes);
+        caller.call(core::call!(caller.ids(), "com.myapp.add", json!({}), args: json!([2, 3])), Box::new(move |ctx, result| {
+            calls.borrow_mut().push(format!("result {}", result.unwrap().args[0]));
+            ctx
+        })).unwrap();
+        step(&mut callee);
+        step(&mut caller);
+        assert_eq!(*outcomes.borrow(), ["welcome", "welcome", "published true", "event 1", "result 5"]);
+
+        for client in [&mut caller, &mut callee] {
+            assert_eq!(client.leave(CloseUri::SystemShutdown).unwrap().reason, "wamp.close.goodbye_and_out");
+        }
+    }
+}
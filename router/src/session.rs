@@ -0,0 +1,65 @@
+use tokio::sync::mpsc;
+use tungstenite::Message;
+use serde::Serialize;
+use serde_json::{Value, json};
+use core::{error::Error, protocol::{Feature, Serializer, messages::{Disclosed, PublishOptions}, meta::SessionDetails, roles::Roles}};
+
+/// A client attached to a realm, messages are encoded with the connection's serializer and queued to its writer task.
+#[derive(Clone)]
+pub(crate) struct Session {
+    serializer: Serializer,
+    outgoing: mpsc::UnboundedSender<Message>,
+    /// `Hello.details.roles`, what the client announced it implements.
+    roles: Value,
+    /// Identity the router established, `Hello.details.authid` is never trusted as the router authenticates no one.
+    /// Set when joining a realm, anonymous sessions are known by their session ID.
+    pub(crate) authid: Option<String>,
+    pub(crate) authrole: String
+}
+
+/// Role of every session, the router does not authenticate anyone.
+pub(crate) const ANONYMOUS: &str = "anonymous";
+
+impl Session {
+    pub(crate) fn new(serializer: Serializer, outgoing: mpsc::UnboundedSender<Message>) -> Self {
+        Self { serializer, outgoing, roles: Value::Null, authid: None, authrole: ANONYMOUS.to_string() }
+    }
+
+    /// Keeps the roles announced in `Hello.details`, for checks on features the router may only use when the client supports them.
+    pub(crate) fn announce(mut self, details: &Value) -> Self {
+        self.roles = details["roles"].clone();
+        self
+    }
+
+    pub(crate) fn supports(&self, role: Roles, feature: Feature) -> bool {
+        self.roles[role.as_str()]["features"][feature.as_str()] == json!(true)
+    }
+
+    /// What the router tells other sessions about this one when it asks to be disclosed, only identities the router established are told.
+    pub(crate) fn identity(&self, id: u64) -> Disclosed {
+        Disclosed {
+            session: id,
+            authid: self.authid.clone(),
+            authrole: Some(self.authrole.clone())
+        }
+    }
+
+    /// What the session meta API tells about this session.
+    pub(crate) fn details(&self, id: u64) -> SessionDetails {
+        SessionDetails {
+            session: id,
+            authid: self.authid.clone(),
+            authrole: Some(self.authrole.clone()),
+            authmethod: Some(ANONYMOUS.to_string())
+        }
+    }
+
+    /// Whether the publication's exclusion and eligibility lists let this session receive it.
+    pub(crate) fn admitted(&self, id: u64, options: &PublishOptions) -> bool {
+        options.admits(id, self.authid.as_deref(), Some(&self.authrole))
+    }
+
+    pub(crate) fn send<T: Serialize>(&self, message: T) -> Result<(), Error> {
+        self.outgoing.send(self.serializer.to_message(&message)?).map_err(|_| Error::Close)
+    }
+}
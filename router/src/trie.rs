@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use core::protocol::MatchPolicy;
+
+/// Prefix patterns, stored character by character so a URI is matched against all of them in one walk.
+#[derive(Default)]
+struct PrefixNode {
+    children: HashMap<char, PrefixNode>,
+    ids: Vec<u64>
+}
+
+/// Wildcard patterns, stored component by component with empty components under `any`.
+#[derive(Default)]
+struct WildcardNode {
+    children: HashMap<String, WildcardNode>,
+    any: Option<Box<WildcardNode>>,
+    ids: Vec<u64>
+}
+
+/// Index of the exact, prefix and wildcard patterns of a realm, finding every pattern a concrete URI matches.
+#[derive(Default)]
+pub(crate) struct UriTrie {
+    exact: HashMap<String, Vec<u64>>,
+    prefix: PrefixNode,
+    wildcard: WildcardNode
+}
+
+impl UriTrie {
+    pub(crate) fn insert(&mut self, pattern: &str, policy: MatchPolicy, id: u64) {
+        match policy {
+            MatchPolicy::Exact => self.exact.entry(pattern.to_string()).or_default().push(id),
+            MatchPolicy::Prefix => {
+                let node = pattern.chars().fold(&mut self.prefix, |node, character| node.children.entry(character).or_default());
+                node.ids.push(id);
+            },
+            MatchPolicy::Wildcard => {
+                let node = pattern.split('.').fold(&mut self.wildcard, |node, component| match component.is_empty() {
+                    true => node.any.get_or_insert_with(Default::default),
+                    false => node.children.entry(component.to_string()).or_default()
+                });
+                node.ids.push(id);
+            }
+        }
+    }
+
+    /// Drops the pattern, emptied branches are left in place as they are few and likely to be reused.
+    pub(crate) fn remove(&mut self, pattern: &str, policy: MatchPolicy, id: u64) {
+        let ids = match policy {
+            MatchPolicy::Exact => self.exact.get_mut(pattern),
+            MatchPolicy::Prefix => pattern.chars().try_fold(&mut self.prefix, |node, character| node.children.get_mut(&character)).map(|node| &mut node.ids),
+            MatchPolicy::Wildcard => pattern.split('.').try_fold(&mut self.wildcard, |node, component| match component.is_empty() {
+                true => node.any.as_deref_mut(),
+                false => node.children.get_mut(component)
+            }).map(|node| &mut node.ids)
+        };
+        if let Some(ids) = ids {
+            ids.retain(|entry| *entry != id);
+        }
+        if policy == MatchPolicy::Exact && self.exact.get(pattern).is_some_and(Vec::is_empty) {
+            self.exact.remove(pattern);
+        }
+    }
+
+    /// IDs of every pattern matching `uri`, exact ones first, then prefixes from the longest, then wildcards with concrete components before empty ones.
+    pub(crate) fn matches(&self, uri: &str) -> Vec<u64> {
+        let mut ids = self.exact.get(uri).cloned().unwrap_or_default();
+        let mut prefixes = vec![&self.prefix.ids];
+        let mut node = &self.prefix;
+        for character in uri.chars() {
+            match node.children.get(&character) {
+                Some(child) => {
+                    prefixes.push(&child.ids);
+                    node = child;
+                },
+                None => break
+            }
+        }
+        ids.extend(prefixes.into_iter().rev().flatten());
+        let components: Vec<&str> = uri.split('.').collect();
+        collect_wildcards(&self.wildcard, &components, &mut ids);
+        ids
+    }
+}
+
+fn collect_wildcards(node: &WildcardNode, components: &[&str], ids: &mut Vec<u64>) {
+    match components.split_first() {
+        None => ids.extend(&node.ids),
+        Some((component, rest)) => {
+            if let Some(child) = node.children.get(*component) {
+                collect_wildcards(child, rest, ids);
+            }
+            if let Some(any) = &node.any {
+                collect_wildcards(any, rest, ids);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::protocol::MatchPolicy;
+    use super::UriTrie;
+
+    #[test]
+    fn matches() {
+        let mut trie = UriTrie::default();
+        trie.insert("com.myapp.topic.emergency", MatchPolicy::Exact, 1);
+        trie.insert("com.myapp.topic.emerg", MatchPolicy::Prefix, 2);
+        trie.insert("com.myapp", MatchPolicy::Prefix, 3);
+        trie.insert("com..topic.", MatchPolicy::Wildcard, 4);
+        trie.insert("com.myapp..emergency", MatchPolicy::Wildcard, 5);
+        assert_eq!(trie.matches("com.myapp.topic.emergency"), vec![1, 2, 3, 5, 4]);
+        assert_eq!(trie.matches("com.myapp.topic.emergency.11"), vec![2, 3]);
+        assert_eq!(trie.matches("com.other.topic.x"), vec![4]);
+        assert!(trie.matches("org.myapp").is_empty());
+
+        trie.remove("com.myapp", MatchPolicy::Prefix, 3);
+        trie.remove("com..topic.", MatchPolicy::Wildcard, 4);
+        trie.remove("com.myapp.topic.emergency", MatchPolicy::Exact, 1);
+        assert_eq!(trie.matches("com.myapp.topic.emergency"), vec![2, 5]);
+    }
+}
@@ -6,4 +6,7 @@ pub use core::{
 };
 
 use client;
-pub use client::{};
\ No newline at end of file
+pub use client::{};
+
+use router;
+pub use router::{Router, Realm};
\ No newline at end of file